@@ -0,0 +1,222 @@
+//! Conversions between [ndarray](https://docs.rs/ndarray) arrays and XGBoost data.
+//!
+//! Only available when the `ndarray` feature is enabled.
+use ndarray::{Array1, Array2, Array3, ArrayD, ArrayView2, Dimension, IxDyn};
+
+use crate::{Booster, DMatrix, PredictConfig, PredictConfigBuilder, PredictType, Prediction, XGBError, XGBResult};
+
+/// Creates a JSON-encoded array interface string for a 2-D f32 array view with non-negative strides.
+fn make_array_interface_view(view: &ArrayView2<f32>) -> String {
+    let ptr = view.as_ptr() as usize;
+    let (num_rows, num_cols) = view.dim();
+    let item_size = std::mem::size_of::<f32>() as isize;
+    let strides = view.strides();
+    format!(
+        r#"{{"data":[{},true],"shape":[{},{}],"strides":[{},{}],"typestr":"<f4","version":3}}"#,
+        ptr,
+        num_rows,
+        num_cols,
+        strides[0] * item_size,
+        strides[1] * item_size
+    )
+}
+
+/// Convert flat prediction output and the shape reported by XGBoost into an array of the requested dimension.
+fn into_array<D: Dimension>(prediction: Prediction) -> XGBResult<ndarray::Array<f32, D>> {
+    let (data, shape) = prediction.into_parts();
+    let array = ArrayD::from_shape_vec(IxDyn(&shape), data).map_err(|err| XGBError::new(err.to_string()))?;
+    array.into_dimensionality::<D>().map_err(|_| {
+        XGBError::new(format!(
+            "Expected {}-dimensional prediction output, got shape {:?} (use predict_matrix_array for \
+             multi-output models)",
+            D::NDIM.unwrap_or(0),
+            shape
+        ))
+    })
+}
+
+impl DMatrix {
+    /// Create a new `DMatrix` from a 2-D array, where each row is an instance and each column a feature.
+    ///
+    /// Arrays in either row-major (C) or column-major (Fortran) order, as well as strided views (e.g. slices of a
+    /// larger array) are passed directly to XGBoost without first copying into an intermediate buffer. Only views
+    /// with negative strides (e.g. reversed axes) are copied into standard layout first.
+    ///
+    /// `NaN` values are treated as missing.
+    ///
+    /// ```
+    /// use ndarray::array;
+    /// use xgb::DMatrix;
+    ///
+    /// let x = array![[1.0f32, 2.0, 3.0],
+    ///                [4.0, 5.0, 6.0]];
+    /// let dmat = DMatrix::from_array_view(x.view()).unwrap();
+    /// assert_eq!(dmat.shape(), (2, 3));
+    ///
+    /// // column-major layout is also supported
+    /// let dmat = DMatrix::from_array_view(x.t()).unwrap();
+    /// assert_eq!(dmat.shape(), (3, 2));
+    /// ```
+    pub fn from_array_view(view: ArrayView2<f32>) -> XGBResult<Self> {
+        if view.strides().iter().any(|stride| *stride < 0) {
+            let owned = view.as_standard_layout();
+            return DMatrix::from_dense_array_interface(&make_array_interface_view(&owned.view()), &Default::default());
+        }
+        DMatrix::from_dense_array_interface(&make_array_interface_view(&view), &Default::default())
+    }
+}
+
+impl Prediction {
+    /// Consume this prediction, returning its values as an array with the shape reported by XGBoost.
+    pub fn into_array(self) -> XGBResult<ArrayD<f32>> {
+        into_array(self)
+    }
+}
+
+impl<'a> TryFrom<ArrayView2<'a, f32>> for DMatrix {
+    type Error = XGBError;
+
+    fn try_from(view: ArrayView2<'a, f32>) -> XGBResult<Self> {
+        DMatrix::from_array_view(view)
+    }
+}
+
+impl Booster {
+    /// Predict results for given data using the given configuration, returning an array with the shape
+    /// reported by XGBoost.
+    pub fn predict_matrix_array(&self, dmat: &DMatrix, config: &PredictConfig) -> XGBResult<ArrayD<f32>> {
+        into_array(self.predict_with(dmat, config)?)
+    }
+
+    /// Predict results for given data, returning one value per row.
+    ///
+    /// Returns an error for models with multiple outputs per row (e.g. multi-class or multi-target models),
+    /// use [`predict_proba_array`](#method.predict_proba_array) for those.
+    pub fn predict_array(&self, dmat: &DMatrix) -> XGBResult<Array1<f32>> {
+        into_array(self.predict_with(dmat, &PredictConfig::default())?)
+    }
+
+    /// Predict results for given data, returning an array of shape (number of samples, number of outputs).
+    ///
+    /// For multi-class models using `MultiSoftprob` each column holds the probability of a class, for binary
+    /// classification and regression models there is a single column.
+    pub fn predict_proba_array(&self, dmat: &DMatrix) -> XGBResult<Array2<f32>> {
+        let config = PredictConfigBuilder::default().strict_shape(true).build().unwrap();
+        into_array(self.predict_with(dmat, &config)?)
+    }
+
+    /// Get feature contributions (SHAP values) for each prediction.
+    ///
+    /// Returns an array of shape (number of samples, number of features + 1), the final column contains the bias
+    /// term. Returns an error for models with multiple outputs per row, use
+    /// [`predict_matrix_array`](#method.predict_matrix_array) for those.
+    pub fn predict_contributions_array(&self, dmat: &DMatrix) -> XGBResult<Array2<f32>> {
+        let config = PredictConfigBuilder::default()
+            .predict_type(PredictType::PredictContribitions)
+            .build()
+            .unwrap();
+        into_array(self.predict_with(dmat, &config)?)
+    }
+
+    /// Get SHAP interaction values for each pair of features for each prediction.
+    ///
+    /// Returns an array of shape (number of samples, number of features + 1, number of features + 1), the final
+    /// row and column contain the bias terms. Returns an error for models with multiple outputs per row, use
+    /// [`predict_matrix_array`](#method.predict_matrix_array) for those.
+    pub fn predict_interactions_array(&self, dmat: &DMatrix) -> XGBResult<Array3<f32>> {
+        let config = PredictConfigBuilder::default()
+            .predict_type(PredictType::PredictFeatureInteractions)
+            .build()
+            .unwrap();
+        into_array(self.predict_with(dmat, &config)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::{self, learning, tree};
+    use ndarray::{Array2, ShapeBuilder, s};
+
+    fn train_booster(dtrain: &DMatrix, objective: learning::Objective) -> Booster {
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .max_depth(2)
+            .build()
+            .unwrap();
+        let learning_params = learning::LearningTaskParametersBuilder::default()
+            .objective(objective)
+            .build()
+            .unwrap();
+        let booster_params = parameters::BoosterParametersBuilder::default()
+            .booster_type(parameters::BoosterType::Tree(tree_params))
+            .learning_params(learning_params)
+            .verbose(false)
+            .build()
+            .unwrap();
+        let training_params = parameters::TrainingParametersBuilder::default()
+            .booster_params(booster_params)
+            .dtrain(dtrain)
+            .boost_rounds(5)
+            .build()
+            .unwrap();
+        Booster::train(&training_params).unwrap()
+    }
+
+    #[test]
+    fn from_array_view_layouts() {
+        let x = Array2::from_shape_fn((4, 3), |(i, j)| (i * 3 + j) as f32);
+        let dmat = DMatrix::from_array_view(x.view()).unwrap();
+        assert_eq!(dmat.shape(), (4, 3));
+
+        // same data stored in column-major order
+        let mut x_f = Array2::zeros((4, 3).f());
+        x_f.assign(&x);
+        let dmat_f = DMatrix::try_from(x_f.view()).unwrap();
+        assert_eq!(dmat_f.shape(), (4, 3));
+
+        // strided view, and view with negative strides
+        assert_eq!(DMatrix::from_array_view(x.slice(s![..;2, ..])).unwrap().shape(), (2, 3));
+        assert_eq!(
+            DMatrix::from_array_view(x.slice(s![..;-1, ..])).unwrap().shape(),
+            (4, 3)
+        );
+    }
+
+    #[test]
+    fn predict_arrays() {
+        let x = Array2::from_shape_fn((20, 3), |(i, j)| ((i + j) % 5) as f32);
+        let y: Vec<f32> = (0..20).map(|i| (i % 2) as f32).collect();
+        let mut dtrain = DMatrix::from_array_view(x.view()).unwrap();
+        dtrain.set_labels(&y).unwrap();
+        let booster = train_booster(&dtrain, learning::Objective::BinaryLogistic);
+
+        let preds = booster.predict_array(&dtrain).unwrap();
+        assert_eq!(preds.to_vec(), booster.predict(&dtrain).unwrap());
+        assert_eq!(booster.predict_proba_array(&dtrain).unwrap().dim(), (20, 1));
+        assert_eq!(booster.predict_contributions_array(&dtrain).unwrap().dim(), (20, 4));
+        assert_eq!(booster.predict_interactions_array(&dtrain).unwrap().dim(), (20, 4, 4));
+    }
+
+    #[test]
+    fn predict_multiclass_arrays() {
+        let x = Array2::from_shape_fn((30, 2), |(i, j)| ((i * (j + 1)) % 7) as f32);
+        let y: Vec<f32> = (0..30).map(|i| (i % 3) as f32).collect();
+        let mut dtrain = DMatrix::from_array_view(x.view()).unwrap();
+        dtrain.set_labels(&y).unwrap();
+        let booster = train_booster(&dtrain, learning::Objective::MultiSoftprob(3));
+
+        let proba = booster.predict_proba_array(&dtrain).unwrap();
+        assert_eq!(proba.dim(), (30, 3));
+        for row in proba.rows() {
+            assert!((row.sum() - 1.0).abs() < 1e-5);
+        }
+        assert!(booster.predict_array(&dtrain).is_err());
+
+        let config = PredictConfigBuilder::default()
+            .predict_type(PredictType::PredictContribitions)
+            .build()
+            .unwrap();
+        let contributions = booster.predict_matrix_array(&dtrain, &config).unwrap();
+        assert_eq!(contributions.shape(), &[30, 3, 3]);
+    }
+}
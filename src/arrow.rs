@@ -0,0 +1,351 @@
+//! Conversions from [Apache Arrow](https://docs.rs/arrow) record batches to XGBoost data.
+//!
+//! Only available when the `arrow` feature is enabled.
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, RecordBatch};
+use arrow_buffer::{Buffer, NullBuffer};
+use arrow_schema::DataType;
+
+use crate::dmatrix::make_column_interface;
+use crate::{DMatrix, FeatureType, XGBError, XGBResult};
+
+/// Array interface type string of a numeric Arrow type, or `None` for types that aren't numeric.
+fn typestr(data_type: &DataType) -> Option<&'static str> {
+    let typestr = match data_type {
+        DataType::Int8 => "|i1",
+        DataType::Int16 => "<i2",
+        DataType::Int32 => "<i4",
+        DataType::Int64 => "<i8",
+        DataType::UInt8 => "|u1",
+        DataType::UInt16 => "<u2",
+        DataType::UInt32 => "<u4",
+        DataType::UInt64 => "<u8",
+        DataType::Float32 => "<f4",
+        DataType::Float64 => "<f8",
+        _ => return None,
+    };
+    Some(typestr)
+}
+
+/// Get the category code of each dictionary value, its index in `categories`.
+fn category_codes(values: &dyn Array, name: &str, categories: &[&str]) -> XGBResult<Vec<Option<usize>>> {
+    let strings: Vec<Option<&str>> = match values.data_type() {
+        DataType::Utf8 => values.as_string::<i32>().iter().collect(),
+        DataType::LargeUtf8 => values.as_string::<i64>().iter().collect(),
+        DataType::Utf8View => values.as_string_view().iter().collect(),
+        data_type => {
+            return Err(XGBError::new(format!(
+                "Unsupported dictionary value type {} for column '{}', only string values are supported",
+                data_type, name
+            )));
+        }
+    };
+    strings
+        .into_iter()
+        .map(|value| {
+            value
+                .map(|value| {
+                    categories
+                        .iter()
+                        .position(|category| *category == value)
+                        .ok_or_else(|| {
+                            XGBError::new(format!(
+                                "Value '{}' of column '{}' is not in its categories",
+                                value, name
+                            ))
+                        })
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+/// Creates a JSON-encoded array interface for a column, with a bitmap `mask` marking null values if there are any.
+///
+/// Dictionary columns are encoded as the index of each value in `categories`, which must be given for them.
+///
+/// Any buffers created while converting the column are pushed to `buffers`, which must be kept alive until the
+/// `DMatrix` has been created.
+fn column_interface(
+    column: &dyn Array,
+    name: &str,
+    categories: Option<&[&str]>,
+    buffers: &mut Vec<Buffer>,
+) -> XGBResult<(String, FeatureType)> {
+    let mut feature_type = FeatureType::Quantitative;
+    let (ptr, typestr) = if let Some(typestr) = typestr(column.data_type()) {
+        let data = column.to_data();
+        let byte_width = column.data_type().primitive_width().unwrap();
+        let ptr = data.buffers()[0].as_ptr() as usize + data.offset() * byte_width;
+        (ptr, typestr)
+    } else if let DataType::Boolean = column.data_type() {
+        // bits can't be described by an array interface, so unpack into bytes
+        let bytes: Vec<u8> = column.as_boolean().values().iter().map(u8::from).collect();
+        let buffer = Buffer::from_vec(bytes);
+        let ptr = buffer.as_ptr() as usize;
+        buffers.push(buffer);
+        (ptr, "|u1")
+    } else if let DataType::Dictionary(_, _) = column.data_type() {
+        // dictionary keys depend on the order values were first seen in, so they aren't usable as category codes
+        let categories = categories.ok_or_else(|| {
+            XGBError::new(format!(
+                "Dictionary column '{}' has no fixed category codes, pass its categories to \
+                 DMatrix::from_record_batch_with_categories() instead",
+                name
+            ))
+        })?;
+        let dictionary = column.as_any_dictionary();
+        let value_codes = category_codes(dictionary.values().as_ref(), name, categories)?;
+        // keys of null entries may point anywhere, but those entries are masked out
+        let codes: Vec<f32> = dictionary
+            .normalized_keys()
+            .into_iter()
+            .map(|key| {
+                value_codes
+                    .get(key)
+                    .copied()
+                    .flatten()
+                    .map_or(f32::NAN, |code| code as f32)
+            })
+            .collect();
+        let buffer = Buffer::from_vec(codes);
+        let ptr = buffer.as_ptr() as usize;
+        buffers.push(buffer);
+        feature_type = FeatureType::Categorical;
+        (ptr, "<f4")
+    } else {
+        return Err(XGBError::new(format!(
+            "Unsupported data type {} for column '{}', only numeric, boolean and dictionary columns are supported",
+            column.data_type(),
+            name
+        )));
+    };
+
+    let mask = column
+        .logical_nulls()
+        .filter(|nulls| nulls.null_count() > 0)
+        .map(|nulls| null_bitmap(&nulls, buffers));
+    let interface = make_column_interface(ptr, column.len(), typestr, mask);
+    Ok((interface, feature_type))
+}
+
+/// Get a pointer to a validity bitmap starting at bit 0, copying the bitmap if its offset isn't byte aligned.
+fn null_bitmap(nulls: &NullBuffer, buffers: &mut Vec<Buffer>) -> usize {
+    let bitmap = nulls.inner().sliced();
+    let ptr = bitmap.as_ptr() as usize;
+    buffers.push(bitmap);
+    ptr
+}
+
+impl DMatrix {
+    /// Create a new `DMatrix` from an Arrow `RecordBatch`, where each row is an instance and each column a feature.
+    ///
+    /// Columns are passed to XGBoost's columnar constructor in their own numeric type without first being
+    /// converted into a dense row-major buffer. Nulls are treated as missing values, and column names become feature
+    /// names.
+    ///
+    /// Supported column types are integers, floats and booleans. Dictionary columns are rejected, as their keys
+    /// depend on the order values were first seen in, so differ between record batches, use
+    /// [`from_record_batch_with_categories`](DMatrix::from_record_batch_with_categories) for them instead.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use arrow_array::{Float32Array, Int32Array, RecordBatch};
+    /// use xgb::DMatrix;
+    ///
+    /// let age = Float32Array::from(vec![Some(31.0), None, Some(45.0)]);
+    /// let rooms = Int32Array::from(vec![2, 3, 1]);
+    /// let batch = RecordBatch::try_from_iter(vec![
+    ///     ("age", Arc::new(age) as _),
+    ///     ("rooms", Arc::new(rooms) as _),
+    /// ]).unwrap();
+    ///
+    /// let dmat = DMatrix::from_record_batch(&batch).unwrap();
+    /// assert_eq!(dmat.shape(), (3, 2));
+    /// assert_eq!(dmat.get_feature_names().unwrap(), vec!["age", "rooms"]);
+    /// ```
+    pub fn from_record_batch(batch: &RecordBatch) -> XGBResult<Self> {
+        DMatrix::from_record_batch_with_categories(batch, &[])
+    }
+
+    /// Create a new `DMatrix` from an Arrow `RecordBatch` like
+    /// [`from_record_batch`](DMatrix::from_record_batch), with dictionary columns as categorical features.
+    ///
+    /// `categories` gives the fixed list of categories of each dictionary column by column name, and each value is
+    /// encoded as its index in that list, so record batches with differently ordered dictionaries encode categories
+    /// the same way. Every dictionary column must have its categories given, dictionary values must be strings, and
+    /// values missing from the list are an error.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use arrow_array::{DictionaryArray, Float32Array, RecordBatch, types::Int32Type};
+    /// use xgb::{DMatrix, FeatureType};
+    ///
+    /// let age = Float32Array::from(vec![Some(31.0), None, Some(45.0)]);
+    /// let city: DictionaryArray<Int32Type> = vec!["london", "paris", "london"].into_iter().collect();
+    /// let batch = RecordBatch::try_from_iter(vec![
+    ///     ("age", Arc::new(age) as _),
+    ///     ("city", Arc::new(city) as _),
+    /// ]).unwrap();
+    ///
+    /// let cities: &[&str] = &["berlin", "london", "paris"];
+    /// let dmat = DMatrix::from_record_batch_with_categories(&batch, &[("city", cities)]).unwrap();
+    /// assert_eq!(dmat.shape(), (3, 2));
+    /// assert_eq!(dmat.get_feature_names().unwrap(), vec!["age", "city"]);
+    /// assert_eq!(dmat.get_feature_types().unwrap(), vec![FeatureType::Quantitative, FeatureType::Categorical]);
+    /// ```
+    pub fn from_record_batch_with_categories(batch: &RecordBatch, categories: &[(&str, &[&str])]) -> XGBResult<Self> {
+        let schema = batch.schema();
+        if let Some((name, _)) = categories
+            .iter()
+            .find(|(name, _)| schema.field_with_name(name).is_err())
+        {
+            return Err(XGBError::new(format!("Categories given for unknown column '{}'", name)));
+        }
+
+        let mut buffers = Vec::new();
+        let mut interfaces = Vec::with_capacity(batch.num_columns());
+        let mut feature_types = Vec::with_capacity(batch.num_columns());
+        for (column, field) in batch.columns().iter().zip(schema.fields()) {
+            let column_categories = categories
+                .iter()
+                .find(|(name, _)| name == field.name())
+                .map(|(_, categories)| *categories);
+            let (interface, feature_type) =
+                column_interface(column.as_ref(), field.name(), column_categories, &mut buffers)?;
+            interfaces.push(interface);
+            feature_types.push(feature_type);
+        }
+
+        let mut dmat = DMatrix::from_columnar_interface(&format!("[{}]", interfaces.join(",")))?;
+        drop(buffers);
+
+        let names: Vec<&str> = schema.fields().iter().map(|field| field.name().as_str()).collect();
+        dmat.set_feature_names(&names)?;
+        if feature_types.contains(&FeatureType::Categorical) {
+            dmat.set_feature_types(&feature_types)?;
+        }
+        Ok(dmat)
+    }
+}
+
+impl TryFrom<&RecordBatch> for DMatrix {
+    type Error = XGBError;
+
+    fn try_from(batch: &RecordBatch) -> XGBResult<Self> {
+        DMatrix::from_record_batch(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::types::UInt8Type;
+    use arrow_array::{BooleanArray, DictionaryArray, Float64Array, Int32Array};
+    use std::sync::Arc;
+
+    #[test]
+    fn column_interfaces() {
+        let mut buffers = Vec::new();
+
+        let ints = Int32Array::from(vec![1, 2, 3, 4]).slice(1, 3);
+        let (interface, feature_type) = column_interface(&ints, "ints", None, &mut buffers).unwrap();
+        let expected_ptr = ints.values().as_ptr() as usize;
+        assert!(interface.starts_with(&format!(r#"{{"data":[{},true],"shape":[3]"#, expected_ptr)));
+        assert!(interface.contains(r#""typestr":"<i4""#));
+        assert!(!interface.contains("mask"));
+        assert_eq!(feature_type, FeatureType::Quantitative);
+
+        // misaligned validity bitmap is copied so that it starts at bit 0
+        let floats = Float64Array::from(vec![Some(1.0), None, Some(2.0), None]).slice(1, 3);
+        let (interface, _) = column_interface(&floats, "floats", None, &mut buffers).unwrap();
+        assert!(interface.contains(r#""mask":{"#));
+        assert_eq!(buffers.last().unwrap().as_slice()[0] & 0b111, 0b010);
+
+        let bools = BooleanArray::from(vec![true, false]);
+        let (interface, _) = column_interface(&bools, "bools", None, &mut buffers).unwrap();
+        assert!(interface.contains(r#""typestr":"|u1""#));
+        assert_eq!(buffers.last().unwrap().as_slice(), &[1, 0]);
+
+        // dictionary values are encoded by their position in the categories rather than their keys
+        let dict: DictionaryArray<UInt8Type> = vec![Some("a"), None, Some("b")].into_iter().collect();
+        let categories: &[&str] = &["b", "a"];
+        let (interface, feature_type) = column_interface(&dict, "dict", Some(categories), &mut buffers).unwrap();
+        assert!(interface.contains(r#""typestr":"<f4""#));
+        assert!(interface.contains(r#""mask":{"#));
+        assert_eq!(feature_type, FeatureType::Categorical);
+        let codes: &[f32] = buffers[buffers.len() - 2].typed_data();
+        assert_eq!(codes[0], 1.0);
+        assert_eq!(codes[2], 0.0);
+
+        let error = column_interface(&dict, "dict", None, &mut buffers)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Dictionary column 'dict' has no fixed category codes"));
+        let categories: &[&str] = &["a"];
+        let error = column_interface(&dict, "dict", Some(categories), &mut buffers)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Value 'b' of column 'dict' is not in its categories"));
+
+        let strings = arrow_array::StringArray::from(vec!["a"]);
+        assert!(column_interface(&strings, "strings", None, &mut buffers).is_err());
+    }
+
+    #[test]
+    fn from_record_batch() {
+        let x = Float64Array::from(vec![Some(1.0), None, Some(3.0), Some(4.0)]);
+        let flag = BooleanArray::from(vec![true, false, true, false]);
+        let colour: DictionaryArray<UInt8Type> =
+            vec![Some("red"), Some("blue"), None, Some("red")].into_iter().collect();
+        let batch = RecordBatch::try_from_iter(vec![
+            ("x", Arc::new(x) as _),
+            ("flag", Arc::new(flag) as _),
+            ("colour", Arc::new(colour) as _),
+        ])
+        .unwrap();
+
+        assert!(DMatrix::try_from(&batch).is_err());
+        let colours: &[&str] = &["blue", "red"];
+        let mut dmat = DMatrix::from_record_batch_with_categories(&batch, &[("colour", colours)]).unwrap();
+        assert_eq!(dmat.shape(), (4, 3));
+        assert_eq!(dmat.get_feature_names().unwrap(), vec!["x", "flag", "colour"]);
+        assert_eq!(
+            dmat.get_feature_types().unwrap(),
+            vec![
+                FeatureType::Quantitative,
+                FeatureType::Quantitative,
+                FeatureType::Categorical
+            ]
+        );
+        dmat.set_labels(&[0.0, 1.0, 0.0, 1.0]).unwrap();
+
+        // nulls are missing rather than zero, false is zero, and colours are encoded by their position in `colours`
+        let (indptr, indices, data) = dmat.get_data_as_csr().unwrap();
+        assert_eq!(indptr, vec![0, 3, 5, 7, 10]);
+        assert_eq!(indices, vec![0, 1, 2, 1, 2, 0, 1, 0, 1, 2]);
+        assert_eq!(data, vec![1.0, 1.0, 1.0, 0.0, 0.0, 3.0, 1.0, 4.0, 0.0, 1.0]);
+
+        let error = DMatrix::from_record_batch_with_categories(&batch, &[("color", colours)]).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Categories given for unknown column 'color'")
+        );
+    }
+
+    #[test]
+    fn from_record_batch_sliced_nulls() {
+        // validity bitmap of the sliced column doesn't start at a byte boundary
+        let x = Float64Array::from(vec![Some(1.0), None, Some(2.0), None]).slice(1, 3);
+        let y = Int32Array::from(vec![Some(0), Some(5), None, Some(7)]).slice(1, 3);
+        let batch = RecordBatch::try_from_iter(vec![("x", Arc::new(x) as _), ("y", Arc::new(y) as _)]).unwrap();
+
+        let dmat = DMatrix::from_record_batch(&batch).unwrap();
+        assert_eq!(dmat.shape(), (3, 2));
+        let (indptr, indices, data) = dmat.get_data_as_csr().unwrap();
+        assert_eq!(indptr, vec![0, 1, 2, 3]);
+        assert_eq!(indices, vec![1, 0, 1]);
+        assert_eq!(data, vec![5.0, 2.0, 7.0]);
+    }
+}
@@ -11,6 +11,7 @@ use indexmap::IndexMap;
 
 use super::XGBResult;
 use crate::parameters::{BoosterParameters, CallbackEnv, TrainingParameters};
+use crate::predict::{PredictConfig, PredictConfigBuilder, PredictType, Prediction, PredictionView};
 
 pub type CustomObjective = fn(&[f32], &DMatrix) -> (Vec<f32>, Vec<f32>);
 
@@ -33,42 +34,6 @@ enum PredictOption {
     PredictLeaf,
     PredictContribitions,
     //ApproximateContributions,
-    PredictInteractions,
-}
-
-#[derive(Default, Debug, Clone)]
-pub enum PredictType {
-    #[default]
-    Normal = 0,
-    OutputMargin = 1,
-    PredictContribitions = 2,
-    PredictApproximateContributions = 3,
-    PredictFeatureInteractions = 4,
-    PredictApproximateFeatureInteractions = 5,
-    PredictLeafTraining = 6,
-}
-
-#[derive(Default)]
-pub struct PredictConfig {
-    pub _type: PredictType,
-    pub training: bool,
-    pub iteration_begin: i64,
-    pub iteration_end: i64,
-    pub strict_shape: bool,
-}
-
-impl PredictConfig {
-    /// returns 0 terminated json of the config, mainly for usage in predict_matrix
-    pub fn as_json(&self) -> String {
-        format!(
-            "{{\"type\":{},\"training\":{},\"iteration_begin\":{},\"iteration_end\":{},\"strict_shape\":{}}}\0",
-            self._type.clone() as usize,
-            self.training,
-            self.iteration_begin,
-            self.iteration_end,
-            self.strict_shape
-        )
-    }
 }
 
 impl PredictOption {
@@ -81,7 +46,6 @@ impl PredictOption {
                 PredictOption::PredictLeaf => 0x02,
                 PredictOption::PredictContribitions => 0x04,
                 //PredictOption::ApproximateContributions => 0x08,
-                PredictOption::PredictInteractions => 0x10,
             };
             option_mask |= value;
         }
@@ -108,6 +72,9 @@ impl Booster {
     ///
     /// This model can then be trained using calls to update/boost as appropriate.
     ///
+    /// Returns an error if the parameters have feature constraints given by name, as there's no training matrix to
+    /// resolve them against, see [`new_with_cached_dmats`](struct.Booster.html#method.new_with_cached_dmats).
+    ///
     /// The [`train`](struct.Booster.html#method.train)  function is often a more convenient way of constructing,
     /// training and evaluating a Booster in a single call.
     pub fn new(params: &BoosterParameters) -> XGBResult<Self> {
@@ -118,6 +85,9 @@ impl Booster {
     ///
     /// Cached DMatrix can sometimes be used internally by XGBoost to speed up certain operations.
     ///
+    /// The first DMatrix is taken to be the training matrix, which any feature constraints are validated against,
+    /// and constraints given by name resolved against.
+    ///
     /// # Safety Note
     ///
     /// The DMatrix handles are only used during the `XGBoosterCreate` call to initialize
@@ -135,7 +105,7 @@ impl Booster {
         ))?;
 
         let mut booster = Booster { handle };
-        booster.set_params(params)?;
+        booster.set_params_for(params, dmats.first().copied())?;
         Ok(booster)
     }
 
@@ -151,11 +121,12 @@ impl Booster {
     pub fn save_buffer(&self, binary: bool) -> XGBResult<Vec<u8>> {
         trace!("Writing Booster to buffer");
         let config = format!("{{\"format\":\"{}\"}}", if binary { "ubj" } else { "json" });
+        let config = ffi::CString::new(config).unwrap();
         let mut out_len: xgboost_sys::bst_ulong = 0;
         let mut out_buffer = ptr::null();
         xgb_call!(xgboost_sys::XGBoosterSaveModelToBuffer(
             self.handle,
-            config.as_bytes().as_ptr() as *const raw::c_char,
+            config.as_ptr(),
             &mut out_len,
             &mut out_buffer
         ))?;
@@ -163,6 +134,17 @@ impl Booster {
         Ok(buffer)
     }
 
+    /// Save the configuration of this Booster, its parameters and those of its objective and tree updaters, as a
+    /// JSON string.
+    pub fn save_config(&self) -> XGBResult<String> {
+        trace!("Writing Booster config");
+        let mut out_len: xgboost_sys::bst_ulong = 0;
+        let mut out_str = ptr::null();
+        xgb_call!(xgboost_sys::XGBoosterSaveJsonConfig(self.handle, &mut out_len, &mut out_str))?;
+        let config = unsafe { slice::from_raw_parts(out_str as *const u8, out_len as usize) };
+        Ok(String::from_utf8_lossy(config).into_owned())
+    }
+
     /// Load a Booster from a binary file at given path.
     pub fn load<P: AsRef<Path>>(path: P) -> XGBResult<Self> {
         debug!("Loading Booster from: {}", path.as_ref().display());
@@ -185,12 +167,25 @@ impl Booster {
 
         let mut handle = ptr::null_mut();
         xgb_call!(xgboost_sys::XGBoosterCreate(ptr::null(), 0, &mut handle))?;
+        let mut booster = Booster { handle };
+        booster.load_model_buffer(bytes)?;
+        Ok(booster)
+    }
+
+    /// Replace this Booster's model with one loaded from a buffer, keeping its cached matrices.
+    pub(crate) fn load_model_buffer(&mut self, bytes: &[u8]) -> XGBResult<()> {
         xgb_call!(xgboost_sys::XGBoosterLoadModelFromBuffer(
-            handle,
+            self.handle,
             bytes.as_ptr() as *const _,
             bytes.len() as u64
-        ))?;
-        Ok(Booster { handle })
+        ))
+    }
+
+    /// Get the number of boosting rounds this model has been trained for.
+    pub fn boosted_rounds(&self) -> XGBResult<u32> {
+        let mut rounds = 0;
+        xgb_call!(xgboost_sys::XGBoosterBoostedRounds(self.handle, &mut rounds))?;
+        Ok(rounds as u32)
     }
 
     /// Convenience function for creating/training a new Booster.
@@ -206,6 +201,12 @@ impl Booster {
     /// * `dtrain` - matrix to train Booster with
     /// * `num_boost_round` - number of training iterations
     /// * `eval_sets` - list of datasets to evaluate after each boosting round
+    ///
+    /// If a [`checkpoint`](parameters/struct.TrainingParameters.html#method.checkpoint) config is set, the model is
+    /// saved periodically during training, and training continues from the latest saved model (up to a total of
+    /// `boost_rounds` rounds) if there is one. If a callback stopped the saved training run early, the restored model
+    /// is returned without training further. Returns an error if the latest checkpoint already has more than
+    /// `boost_rounds` rounds.
     pub fn train(params: &TrainingParameters) -> XGBResult<Self> {
         let cached_dmats = {
             let mut dmats = vec![params.dtrain];
@@ -218,7 +219,25 @@ impl Booster {
         };
 
         let mut bst = Booster::new_with_cached_dmats(&params.booster_params, &cached_dmats)?;
-        for i in 0..params.boost_rounds as i32 {
+        let (start_round, stopped) = match params.checkpoint {
+            Some(ref checkpoint) => checkpoint.restore(&mut bst)?,
+            None => (0, false),
+        };
+        if start_round > params.boost_rounds {
+            return Err(XGBError::new(format!(
+                "Checkpoint has {} rounds, more than the {} boosting rounds to train",
+                start_round, params.boost_rounds
+            )));
+        }
+        if start_round > 0 {
+            // training parameters aren't stored in the saved model
+            bst.set_params_for(&params.booster_params, Some(params.dtrain))?;
+        }
+        if stopped {
+            debug!("Training was stopped early after {} rounds", start_round);
+            return Ok(bst);
+        }
+        for i in start_round as i32..params.boost_rounds as i32 {
             debug!("Updating in round: {}", i);
             if let Some(objective_fn) = params.custom_objective_fn {
                 bst.update_custom(params.dtrain, i, objective_fn)?;
@@ -226,6 +245,12 @@ impl Booster {
                 bst.update(params.dtrain, i)?;
             }
 
+            if let Some(ref checkpoint) = params.checkpoint
+                && checkpoint.is_due(i as u32 + 1, params.boost_rounds)
+            {
+                checkpoint.save(&bst, i as u32 + 1, false)?;
+            }
+
             // Collect evaluation results if evaluation sets are provided
             let evaluation_results = if let Some(eval_sets) = params.evaluation_sets {
                 let mut dmat_eval_results = bst.eval_set(eval_sets, i)?;
@@ -276,6 +301,9 @@ impl Booster {
                     if !callback(&callback_env) {
                         // Callback returned false, stop training early
                         debug!("Callback requested early stopping at iteration {}", i);
+                        if let Some(ref checkpoint) = params.checkpoint {
+                            checkpoint.save(&bst, i as u32 + 1, true)?;
+                        }
                         return Ok(bst);
                     }
                 }
@@ -286,8 +314,18 @@ impl Booster {
     }
 
     /// Update this Booster's parameters.
+    ///
+    /// Returns an error if the parameters have feature constraints given by name, as there's no training matrix to
+    /// resolve them against.
     pub fn set_params(&mut self, p: &BoosterParameters) -> XGBResult<()> {
-        for (key, value) in p.as_string_pairs() {
+        self.set_params_for(p, None)
+    }
+
+    /// Update this Booster's parameters, validating feature constraints against `dtrain` if given.
+    fn set_params_for(&mut self, p: &BoosterParameters, dtrain: Option<&DMatrix>) -> XGBResult<()> {
+        let mut pairs = p.as_string_pairs();
+        pairs.extend(p.booster_type().resolve_constraints(dtrain)?);
+        for (key, value) in pairs {
             debug!("Setting parameter: {}={}", &key, &value);
             self.set_param(&key, &value)?;
         }
@@ -355,7 +393,7 @@ impl Booster {
         ))
     }
 
-    fn eval_set(
+    pub(crate) fn eval_set(
         &self,
         evals: &[(&DMatrix, &str)],
         iteration: i32,
@@ -443,6 +481,12 @@ impl Booster {
         xgb_call!(xgboost_sys::XGBoosterSetAttr(self.handle, key.as_ptr(), value.as_ptr()))
     }
 
+    /// Delete the attribute with given key from this model, if it's set.
+    pub(crate) fn remove_attribute(&mut self, key: &str) -> XGBResult<()> {
+        let key = ffi::CString::new(key).unwrap();
+        xgb_call!(xgboost_sys::XGBoosterSetAttr(self.handle, key.as_ptr(), ptr::null()))
+    }
+
     /// Get names of all attributes stored in this model. Values can then be fetched with calls to `get_attribute`.
     pub fn get_attribute_names(&self) -> XGBResult<Vec<String>> {
         let mut out_len = 0;
@@ -546,6 +590,9 @@ impl Booster {
     ///
     /// config_json should be a 0 terminated string, preferred created by PredictConfig::as_json
     /// Returns an array containing one entry per row in the given data and its shape as array.
+    ///
+    /// [`predict_with`](#method.predict_with) provides the same functionality using a typed
+    /// [`PredictConfig`](struct.PredictConfig.html) instead of JSON.
     pub fn predict_matrix(&self, dmat: &DMatrix, config_json: &str) -> XGBResult<(Vec<f32>, Vec<u64>)> {
         let str_buffer: std::ffi::CString;
         let cfg = if !config_json.is_empty() && config_json.ends_with('\u{0}') {
@@ -554,26 +601,154 @@ impl Booster {
             str_buffer = std::ffi::CString::new(config_json).unwrap();
             str_buffer.as_c_str()
         };
+        let (data, shape) = self.predict_from_dmatrix(dmat, cfg)?;
+        Ok((data.to_vec(), shape.to_vec()))
+    }
+
+    /// Predict results for given data using the given configuration.
+    ///
+    /// Returns the predicted values along with the shape reported by XGBoost, which depends on the type of
+    /// prediction and the model, e.g. (rows, classes) for multi-class probabilities.
+    ///
+    /// ```no_run
+    /// # use xgb::{Booster, DMatrix, PredictConfigBuilder, PredictType};
+    /// # let booster = Booster::load("xgb.model").unwrap();
+    /// # let dmat = DMatrix::from_dense(&[1.0, 2.0], 1).unwrap();
+    /// let config = PredictConfigBuilder::default()
+    ///     .predict_type(PredictType::OutputMargin)
+    ///     .iteration_range(0..10)
+    ///     .build()
+    ///     .unwrap();
+    /// let prediction = booster.predict_with(&dmat, &config).unwrap();
+    /// println!("{:?} {:?}", prediction.shape(), prediction.data());
+    /// ```
+    pub fn predict_with(&self, dmat: &DMatrix, config: &PredictConfig) -> XGBResult<Prediction> {
+        let cfg = ffi::CString::new(config.to_json()).unwrap();
+        let (data, shape) = self.predict_from_dmatrix(dmat, &cfg)?;
+        Ok(Prediction::new(
+            data.to_vec(),
+            shape.iter().map(|dim| *dim as usize).collect(),
+        ))
+    }
+
+    /// Predict results for given data, writing one entry per row into `out`.
+    ///
+    /// `out` is cleared first and then filled, so reusing the same `Vec` across calls avoids allocating once its
+    /// capacity is large enough. Equivalent to [`predict`](#method.predict) otherwise.
+    ///
+    /// ```no_run
+    /// # use xgb::{Booster, DMatrix};
+    /// # let booster = Booster::load("xgb.model").unwrap();
+    /// # let batches: Vec<DMatrix> = Vec::new();
+    /// let mut preds = Vec::new();
+    /// for dmat in &batches {
+    ///     booster.predict_into(dmat, &mut preds).unwrap();
+    ///     println!("{:?}", preds);
+    /// }
+    /// ```
+    pub fn predict_into(&self, dmat: &DMatrix, out: &mut Vec<f32>) -> XGBResult<()> {
+        let cfg = ffi::CString::new(PredictConfig::default().to_json()).unwrap();
+        let (data, _) = self.predict_from_dmatrix(dmat, &cfg)?;
+        out.clear();
+        out.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Predict results for given data using the given configuration, without copying the output.
+    ///
+    /// The returned view borrows the output buffer owned by XGBoost, which is overwritten by the next prediction
+    /// made with this Booster. The view therefore holds a mutable borrow of the Booster, so no other method can be
+    /// called on it until the view is dropped.
+    ///
+    /// ```no_run
+    /// # use xgb::{Booster, DMatrix, PredictConfig};
+    /// # let mut booster = Booster::load("xgb.model").unwrap();
+    /// # let dmat = DMatrix::from_dense(&[1.0, 2.0], 1).unwrap();
+    /// let view = booster.predict_view(&dmat, &PredictConfig::default()).unwrap();
+    /// let max = view.data().iter().cloned().fold(f32::MIN, f32::max);
+    /// // view must no longer be used once the booster is used again
+    /// let preds = booster.predict(&dmat).unwrap();
+    /// ```
+    pub fn predict_view(&mut self, dmat: &DMatrix, config: &PredictConfig) -> XGBResult<PredictionView<'_>> {
+        let cfg = ffi::CString::new(config.to_json()).unwrap();
+        let (data, shape) = self.predict_from_dmatrix(dmat, &cfg)?;
+        Ok(PredictionView::new(data, shape))
+    }
+
+    /// Predict results directly from a dense array in row-major order, without first creating a `DMatrix`.
+    ///
+    /// Values equal to the config's [`missing`](struct.PredictConfig.html#method.missing) value are treated as
+    /// missing. Only `Normal` and `OutputMargin` prediction types are supported.
+    pub fn predict_inplace(&self, data: &[f32], num_rows: usize, config: &PredictConfig) -> XGBResult<Prediction> {
+        if num_rows == 0 || !data.len().is_multiple_of(num_rows) {
+            return Err(XGBError::new(format!(
+                "Data of length {} can't be split into {} rows",
+                data.len(),
+                num_rows
+            )));
+        }
+        let array_interface = format!(
+            r#"{{"data":[{},true],"shape":[{},{}],"strides":null,"typestr":"<f4","version":3}}"#,
+            data.as_ptr() as usize,
+            num_rows,
+            data.len() / num_rows
+        );
+        let values = ffi::CString::new(array_interface).unwrap();
+        let cfg = ffi::CString::new(config.to_inplace_json()).unwrap();
+        let mut out_shape = ptr::null();
+        let mut out_shape_dim = 0;
+        let mut out_result = ptr::null();
+        xgb_call!(xgboost_sys::XGBoosterPredictFromDense(
+            self.handle,
+            values.as_ptr(),
+            cfg.as_ptr(),
+            ptr::null_mut(),
+            &mut out_shape,
+            &mut out_shape_dim,
+            &mut out_result
+        ))?;
+        let (data, shape) = unsafe { Self::prediction_slices(out_result, out_shape, out_shape_dim) };
+        Ok(Prediction::new(
+            data.to_vec(),
+            shape.iter().map(|dim| *dim as usize).collect(),
+        ))
+    }
+
+    /// Run a prediction through `XGBoosterPredictFromDMatrix`, returning XGBoost's output buffers.
+    ///
+    /// The returned slices point into memory owned by XGBoost, which is reused by the next prediction made
+    /// with this Booster, so they must be copied (or otherwise dropped) before another prediction is made.
+    fn predict_from_dmatrix(&self, dmat: &DMatrix, config: &ffi::CStr) -> XGBResult<(&[f32], &[u64])> {
         let mut out_shape = ptr::null();
         let mut out_shape_dim = 0;
         let mut out_result = ptr::null();
         xgb_call!(xgboost_sys::XGBoosterPredictFromDMatrix(
             self.handle,
             dmat.handle,
-            cfg.as_ptr() as *const raw::c_char,
+            config.as_ptr(),
             &mut out_shape,
             &mut out_shape_dim,
             &mut out_result
         ))?;
-        assert!(!out_result.is_null());
-        let shape = unsafe { slice::from_raw_parts(out_shape, out_shape_dim as usize).to_vec() };
-        let mut data_size = 1;
-        for dim in &shape {
-            data_size *= dim;
-        }
-        let data = unsafe { slice::from_raw_parts(out_result, data_size as usize).to_vec() };
+        Ok(unsafe { Self::prediction_slices(out_result, out_shape, out_shape_dim) })
+    }
 
-        Ok((data, shape))
+    /// Wrap prediction output buffers returned by XGBoost as slices.
+    ///
+    /// # Safety
+    ///
+    /// Pointers must be the output of a successful XGBoost prediction call, and must remain valid for the
+    /// lifetime `'a`.
+    unsafe fn prediction_slices<'a>(
+        out_result: *const f32,
+        out_shape: *const u64,
+        out_shape_dim: u64,
+    ) -> (&'a [f32], &'a [u64]) {
+        assert!(!out_result.is_null());
+        let shape = unsafe { slice::from_raw_parts(out_shape, out_shape_dim as usize) };
+        let data_size: u64 = shape.iter().product();
+        let data = unsafe { slice::from_raw_parts(out_result, data_size as usize) };
+        (data, shape)
     }
 
     /// Predict results for given data.
@@ -600,6 +775,23 @@ impl Booster {
         Ok(data)
     }
 
+    /// Predict results for given data, keeping one output per target.
+    ///
+    /// Returns an array of shape (number of samples, number of targets) as a tuple of (data, shape). For
+    /// multi-target regression this is the number of targets, for multi-class classification with
+    /// `MultiSoftprob` it is the number of classes, and for single-output models it is 1.
+    pub fn predict_multi_output(&self, dmat: &DMatrix) -> XGBResult<(Vec<f32>, (usize, usize))> {
+        let config = PredictConfigBuilder::default().strict_shape(true).build().unwrap();
+        let (data, shape) = self.predict_with(dmat, &config)?.into_parts();
+        if shape.len() != 2 {
+            return Err(XGBError::new(format!(
+                "Expected 2-dimensional prediction output, got shape {:?}",
+                shape
+            )));
+        }
+        Ok((data, (shape[0], shape[1])))
+    }
+
     /// Predict margin for given data.
     ///
     /// Returns an array containing one entry per row in the given data.
@@ -687,26 +879,18 @@ impl Booster {
     /// Returns an array of shape (number of samples, number of features + 1, number of features + 1).
     /// The final row and column contain the bias terms.
     pub fn predict_interactions(&self, dmat: &DMatrix) -> XGBResult<(Vec<f32>, (usize, usize, usize))> {
-        let option_mask = PredictOption::options_as_mask(&[PredictOption::PredictInteractions]);
-        let ntree_limit = 0;
-        let mut out_len = 0;
-        let mut out_result = ptr::null();
-        xgb_call!(xgboost_sys::XGBoosterPredict(
-            self.handle,
-            dmat.handle,
-            option_mask,
-            ntree_limit,
-            0,
-            &mut out_len,
-            &mut out_result
-        ))?;
-        assert!(!out_result.is_null());
-
-        let data = unsafe { slice::from_raw_parts(out_result, out_len as usize).to_vec() };
-        let num_rows = dmat.num_rows();
-
-        let dim = ((data.len() / num_rows) as f64).sqrt() as usize;
-        Ok((data, (num_rows, dim, dim)))
+        let config = PredictConfigBuilder::default()
+            .predict_type(PredictType::PredictFeatureInteractions)
+            .build()
+            .unwrap();
+        let (data, shape) = self.predict_with(dmat, &config)?.into_parts();
+        match shape[..] {
+            [num_rows, dim1, dim2] => Ok((data, (num_rows, dim1, dim2))),
+            _ => Err(XGBError::new(format!(
+                "Expected 3-dimensional interaction output, got shape {:?}",
+                shape
+            ))),
+        }
     }
 
     /// Get a dump of this model as a string.
@@ -884,7 +1068,9 @@ impl FeatureMap {
     }
 }
 
-/// Indicates the type of a feature, used when dumping models as text.
+/// Indicates the type of a feature, used when dumping models as text and as the feature types of a
+/// [`DMatrix`](struct.DMatrix.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FeatureType {
     /// Binary indicator feature.
     Binary,
@@ -894,6 +1080,9 @@ pub enum FeatureType {
 
     /// Integer feature (when hinted, decision boundary will be integer).
     Integer,
+
+    /// Categorical feature, with values given as category codes.
+    Categorical,
 }
 
 impl FromStr for FeatureType {
@@ -904,8 +1093,9 @@ impl FromStr for FeatureType {
             "i" => Ok(FeatureType::Binary),
             "q" => Ok(FeatureType::Quantitative),
             "int" => Ok(FeatureType::Integer),
+            "c" => Ok(FeatureType::Categorical),
             _ => Err(format!(
-                "unrecognised feature type '{}', must be one of: 'i', 'q', 'int'",
+                "unrecognised feature type '{}', must be one of: 'i', 'q', 'int', 'c'",
                 s
             )),
         }
@@ -918,6 +1108,7 @@ impl fmt::Display for FeatureType {
             FeatureType::Binary => "i",
             FeatureType::Quantitative => "q",
             FeatureType::Integer => "int",
+            FeatureType::Categorical => "c",
         };
         write!(f, "{}", s)
     }
@@ -1197,6 +1388,225 @@ mod tests {
         }
     }
 
+    #[test]
+    fn predict_with_config() {
+        let dmat_train =
+            DMatrix::load(r#"{"uri": "xgboost-sys/xgboost/demo/data/agaricus.txt.train?format=libsvm"}"#).unwrap();
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .max_depth(2)
+            .build()
+            .unwrap();
+        let params = parameters::BoosterParametersBuilder::default()
+            .booster_type(parameters::BoosterType::Tree(tree_params))
+            .verbose(false)
+            .build()
+            .unwrap();
+        let mut booster = Booster::new_with_cached_dmats(&params, &[&dmat_train]).unwrap();
+        for i in 0..4 {
+            booster.update(&dmat_train, i).expect("update failed");
+        }
+
+        let prediction = booster.predict_with(&dmat_train, &PredictConfig::default()).unwrap();
+        assert_eq!(prediction.shape(), &[dmat_train.num_rows()]);
+        assert_eq!(prediction.data(), &booster.predict(&dmat_train).unwrap()[..]);
+
+        // margin of the first two rounds only differs from the margin of all rounds
+        let margin_all = booster.predict_margin(&dmat_train).unwrap();
+        let config = PredictConfigBuilder::default()
+            .predict_type(PredictType::OutputMargin)
+            .iteration_range(0..2)
+            .build()
+            .unwrap();
+        let margin_first = booster.predict_with(&dmat_train, &config).unwrap().into_vec();
+        assert_eq!(margin_first.len(), margin_all.len());
+        assert_ne!(margin_first, margin_all);
+
+        let config = PredictConfigBuilder::default().strict_shape(true).build().unwrap();
+        let prediction = booster.predict_with(&dmat_train, &config).unwrap();
+        assert_eq!(prediction.shape(), &[dmat_train.num_rows(), 1]);
+    }
+
+    #[test]
+    fn predict_into_and_view() {
+        let dmat_train =
+            DMatrix::load(r#"{"uri": "xgboost-sys/xgboost/demo/data/agaricus.txt.train?format=libsvm"}"#).unwrap();
+        let params = parameters::BoosterParametersBuilder::default()
+            .verbose(false)
+            .build()
+            .unwrap();
+        let mut booster = Booster::new_with_cached_dmats(&params, &[&dmat_train]).unwrap();
+        for i in 0..3 {
+            booster.update(&dmat_train, i).expect("update failed");
+        }
+        let expected = booster.predict(&dmat_train).unwrap();
+
+        // stale contents are replaced, and the buffer is reused once large enough
+        let mut out = vec![42.0; 3];
+        booster.predict_into(&dmat_train, &mut out).unwrap();
+        assert_eq!(out, expected);
+        let capacity = out.capacity();
+        let rows = dmat_train.slice(&[0, 1]).unwrap();
+        booster.predict_into(&rows, &mut out).unwrap();
+        assert_eq!(out, &expected[..2]);
+        assert_eq!(out.capacity(), capacity);
+
+        let view = booster.predict_view(&dmat_train, &PredictConfig::default()).unwrap();
+        assert_eq!(view.shape(), &[dmat_train.num_rows() as u64]);
+        assert_eq!(view.data(), &expected[..]);
+        let copied = view.to_prediction();
+        assert_eq!(copied.into_vec(), expected);
+    }
+
+    #[test]
+    fn predict_inplace() {
+        let x = [1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0, -1.0, -1.0, 0.5, 0.5];
+        let y = [1.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+        let mut dtrain = DMatrix::from_dense(&x, 6).unwrap();
+        dtrain.set_labels(&y).unwrap();
+
+        let params = parameters::BoosterParametersBuilder::default()
+            .verbose(false)
+            .build()
+            .unwrap();
+        let mut booster = Booster::new_with_cached_dmats(&params, &[&dtrain]).unwrap();
+        for i in 0..3 {
+            booster.update(&dtrain, i).expect("update failed");
+        }
+
+        let expected = booster.predict(&dtrain).unwrap();
+        let prediction = booster.predict_inplace(&x, 6, &PredictConfig::default()).unwrap();
+        assert_eq!(prediction.shape(), &[6]);
+        for (pred, expected) in prediction.data().iter().zip(&expected) {
+            assert!((pred - expected).abs() < 1e-6);
+        }
+
+        // -1.0 marks missing values, so the fifth row is predicted as if it were entirely missing
+        let config = PredictConfigBuilder::default().missing(-1.0).build().unwrap();
+        let prediction = booster.predict_inplace(&x, 6, &config).unwrap();
+        let missing = booster
+            .predict_inplace(&[f32::NAN, f32::NAN], 1, &PredictConfig::default())
+            .unwrap();
+        assert_eq!(prediction.row(4), missing.data());
+
+        assert!(booster.predict_inplace(&x, 5, &PredictConfig::default()).is_err());
+    }
+
+    #[test]
+    fn predict_multi_output() {
+        // two targets, the first following feature 0 and the second following feature 1
+        let num_rows = 64;
+        let mut x = Vec::with_capacity(num_rows * 2);
+        let mut y = Vec::with_capacity(num_rows * 2);
+        for i in 0..num_rows {
+            let (a, b) = ((i % 8) as f32, (i / 8) as f32);
+            x.extend_from_slice(&[a, b]);
+            y.extend_from_slice(&[a * 2.0, -b]);
+        }
+        let mut dtrain = DMatrix::from_dense(&x, num_rows).unwrap();
+        dtrain.set_labels_2d(&y, 2).unwrap();
+
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .tree_method(tree::TreeMethod::Hist)
+            .multi_strategy(tree::MultiStrategy::MultiOutputTree)
+            .build()
+            .unwrap();
+        let booster_params = parameters::BoosterParametersBuilder::default()
+            .booster_type(parameters::BoosterType::Tree(tree_params))
+            .verbose(false)
+            .build()
+            .unwrap();
+        let training_params = parameters::TrainingParametersBuilder::default()
+            .booster_params(booster_params)
+            .dtrain(&dtrain)
+            .boost_rounds(20)
+            .build()
+            .unwrap();
+        let booster = Booster::train(&training_params).unwrap();
+
+        let (preds, shape) = booster.predict_multi_output(&dtrain).unwrap();
+        assert_eq!(shape, (num_rows, 2));
+        assert_eq!(preds.len(), num_rows * 2);
+        for (pred, label) in preds.iter().zip(&y) {
+            assert!((pred - label).abs() < 0.5, "prediction {} too far from label {}", pred, label);
+        }
+    }
+
+    #[test]
+    fn train_with_constraints() {
+        let num_rows = 32;
+        let mut x = Vec::with_capacity(num_rows * 2);
+        let mut y = Vec::with_capacity(num_rows);
+        for i in 0..num_rows {
+            let (a, b) = ((i % 4) as f32, (i / 4) as f32);
+            x.extend_from_slice(&[a, b]);
+            y.push(a - b);
+        }
+        let mut dtrain = DMatrix::from_dense(&x, num_rows).unwrap();
+        dtrain.set_labels(&y).unwrap();
+        dtrain.set_feature_names(&["a", "b"]).unwrap();
+
+        let booster_params_with = |tree_params: tree::TreeBoosterParameters| {
+            parameters::BoosterParametersBuilder::default()
+                .booster_type(parameters::BoosterType::Tree(tree_params))
+                .verbose(false)
+                .build()
+                .unwrap()
+        };
+        let train_with = |tree_params: tree::TreeBoosterParameters| {
+            let booster_params = booster_params_with(tree_params);
+            let training_params = parameters::TrainingParametersBuilder::default()
+                .booster_params(booster_params)
+                .dtrain(&dtrain)
+                .boost_rounds(5)
+                .build()
+                .unwrap();
+            Booster::train(&training_params)
+        };
+
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .monotone_constraints(Some(tree::MonotoneConstraints::ByName(vec![
+                ("a".to_owned(), tree::Monotonicity::Increasing),
+                ("b".to_owned(), tree::Monotonicity::Decreasing),
+            ])))
+            .interaction_constraints(Some(tree::InteractionConstraints::ByIndex(vec![vec![0], vec![1]])))
+            .build()
+            .unwrap();
+        let booster = train_with(tree_params).unwrap();
+        let preds = booster.predict(&dtrain).unwrap();
+        // increasing in a (consecutive rows), decreasing in b (every 4th row)
+        assert!(preds[1] >= preds[0]);
+        assert!(preds[4] <= preds[0]);
+
+        // names can only be resolved given a training matrix
+        let by_name = tree::TreeBoosterParametersBuilder::default()
+            .monotone_constraints(Some(tree::MonotoneConstraints::ByName(vec![(
+                "a".to_owned(),
+                tree::Monotonicity::Increasing,
+            )])))
+            .build()
+            .unwrap();
+        let booster_params = booster_params_with(by_name);
+        assert!(Booster::new(&booster_params).is_err());
+        assert!(Booster::new_with_cached_dmats(&booster_params, &[&dtrain]).is_ok());
+        let mut booster = Booster::new(&parameters::BoosterParameters::default()).unwrap();
+        assert!(booster.set_params(&booster_params).is_err());
+
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .monotone_constraints(Some(tree::MonotoneConstraints::ByName(vec![(
+                "c".to_owned(),
+                tree::Monotonicity::Increasing,
+            )])))
+            .build()
+            .unwrap();
+        assert!(train_with(tree_params).is_err());
+
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .interaction_constraints(Some(tree::InteractionConstraints::ByIndex(vec![vec![0, 2]])))
+            .build()
+            .unwrap();
+        assert!(train_with(tree_params).is_err());
+    }
+
     #[test]
     fn predict_leaf() {
         let dmat_train =
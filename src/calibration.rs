@@ -0,0 +1,718 @@
+//! Probability calibration of binary and multiclass classifiers.
+//!
+//! A [`CalibratedBooster`](struct.CalibratedBooster.html) maps the margin predicted by a trained classifier to a
+//! calibrated probability, using Platt scaling or isotonic regression fitted on held-out data that wasn't used for
+//! training. Multiclass models are calibrated one-vs-rest, with the calibrated probabilities of each row normalised
+//! to sum to 1.
+//!
+//! The calibrator is stored as attributes of the booster, so saving and loading the model keeps it.
+//!
+//! ```no_run
+//! use xgb::{Booster, DMatrix};
+//! use xgb::calibration::{self, CalibratedBooster, CalibrationMethod};
+//!
+//! let booster = Booster::load("model.json").unwrap();
+//! let dcalib = DMatrix::load("calibration.libsvm?format=libsvm").unwrap();
+//! let calibrated = CalibratedBooster::fit(booster, &dcalib, CalibrationMethod::Isotonic).unwrap();
+//! calibrated.save("calibrated.json").unwrap();
+//!
+//! let calibrated = CalibratedBooster::load("calibrated.json").unwrap();
+//! let dtest = DMatrix::load("test.libsvm?format=libsvm").unwrap();
+//! let (proba, (num_rows, num_classes)) = calibrated.predict_proba(&dtest).unwrap();
+//!
+//! // compare predicted probabilities of the positive class with observed frequencies
+//! let positive: Vec<f32> = proba.chunks(num_classes).map(|row| row[1]).collect();
+//! let curve = calibration::reliability_curve(&positive, dtest.get_labels().unwrap(), 10).unwrap();
+//! ```
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{Booster, DMatrix, XGBError, XGBResult};
+
+/// Booster attribute storing the calibration method.
+const ATTR_METHOD: &str = "calibration_method";
+/// Booster attribute storing the number of calibrators (1 for binary models, one per class otherwise).
+const ATTR_NUM_CALIBRATORS: &str = "calibration_num_calibrators";
+/// Prefix of the booster attributes storing each calibrator, followed by its index.
+const ATTR_CALIBRATOR_PREFIX: &str = "calibration_";
+
+/// How margins are mapped to calibrated probabilities.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationMethod {
+    /// Fit a sigmoid `1 / (1 + exp(a * margin + b))` ([Platt, 1999](https://www.researchgate.net/publication/2594015)).
+    /// Needs little data, but assumes the miscalibration is sigmoid shaped.
+    Platt,
+
+    /// Fit a non-decreasing step function with the pool adjacent violators algorithm, interpolating linearly between
+    /// steps. Corrects any monotonic miscalibration, but needs more data to avoid overfitting.
+    Isotonic,
+}
+
+impl Display for CalibrationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalibrationMethod::Platt => write!(f, "platt"),
+            CalibrationMethod::Isotonic => write!(f, "isotonic"),
+        }
+    }
+}
+
+impl FromStr for CalibrationMethod {
+    type Err = XGBError;
+
+    fn from_str(s: &str) -> XGBResult<Self> {
+        match s {
+            "platt" => Ok(CalibrationMethod::Platt),
+            "isotonic" => Ok(CalibrationMethod::Isotonic),
+            _ => Err(XGBError::new(format!("Unknown calibration method '{}'", s))),
+        }
+    }
+}
+
+/// A fitted mapping from margins to calibrated probabilities of a single class.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Calibrator {
+    /// Sigmoid `1 / (1 + exp(a * margin + b))`.
+    Platt { a: f64, b: f64 },
+
+    /// Probabilities `y` at increasing margins `x`, linearly interpolated in between and constant outside.
+    Isotonic { x: Vec<f64>, y: Vec<f64> },
+}
+
+impl Calibrator {
+    /// Fit a calibrator to margins and binary labels (1 for the positive class, 0 otherwise), with optional weights.
+    pub fn fit(method: CalibrationMethod, margins: &[f32], labels: &[f32], weights: &[f32]) -> XGBResult<Self> {
+        if margins.len() != labels.len() || (!weights.is_empty() && weights.len() != labels.len()) {
+            return Err(XGBError::new(format!(
+                "Expected one label and weight per margin ({} margins), got {} labels and {} weights",
+                margins.len(),
+                labels.len(),
+                weights.len()
+            )));
+        }
+        if margins.is_empty() {
+            return Err(XGBError::new("Can't fit calibrator without data"));
+        }
+        if let Some(label) = labels.iter().find(|label| **label != 0.0 && **label != 1.0) {
+            return Err(XGBError::new(format!("Label {} is not a binary label (0 or 1)", label)));
+        }
+        let weight = |i: usize| weights.get(i).map_or(1.0, |w| *w as f64);
+        let points: Vec<(f64, f64, f64)> = (0..margins.len())
+            .map(|i| (margins[i] as f64, labels[i] as f64, weight(i)))
+            .collect();
+        Ok(match method {
+            CalibrationMethod::Platt => fit_platt(&points),
+            CalibrationMethod::Isotonic => fit_isotonic(points),
+        })
+    }
+
+    /// Get the calibrated probability for `margin`.
+    pub fn transform(&self, margin: f32) -> f32 {
+        let margin = margin as f64;
+        let p = match self {
+            Calibrator::Platt { a, b } => 1.0 / (1.0 + (a * margin + b).exp()),
+            Calibrator::Isotonic { x, y } => {
+                let i = x.partition_point(|x| *x <= margin);
+                if i == 0 {
+                    y[0]
+                } else if i == x.len() {
+                    y[x.len() - 1]
+                } else {
+                    let t = (margin - x[i - 1]) / (x[i] - x[i - 1]);
+                    y[i - 1] + t * (y[i] - y[i - 1])
+                }
+            }
+        };
+        p as f32
+    }
+
+    /// Values stored in the booster attribute.
+    fn to_attribute(&self) -> String {
+        let values: Vec<f64> = match self {
+            Calibrator::Platt { a, b } => vec![*a, *b],
+            Calibrator::Isotonic { x, y } => x.iter().zip(y).flat_map(|(x, y)| [*x, *y]).collect(),
+        };
+        values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" ")
+    }
+
+    fn from_attribute(method: CalibrationMethod, value: &str) -> XGBResult<Self> {
+        let values = value
+            .split_whitespace()
+            .map(|v| v.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| XGBError::new(format!("Invalid calibrator '{}'", value)))?;
+        match method {
+            CalibrationMethod::Platt if values.len() == 2 => Ok(Calibrator::Platt {
+                a: values[0],
+                b: values[1],
+            }),
+            CalibrationMethod::Isotonic if !values.is_empty() && values.len().is_multiple_of(2) => {
+                Ok(Calibrator::Isotonic {
+                    x: values.iter().step_by(2).copied().collect(),
+                    y: values.iter().skip(1).step_by(2).copied().collect(),
+                })
+            }
+            _ => Err(XGBError::new(format!("Invalid {} calibrator '{}'", method, value))),
+        }
+    }
+}
+
+/// Fit Platt's sigmoid to `(margin, label, weight)` points by Newton's method with backtracking line search, using
+/// regularised targets to avoid overfitting ([Lin et al., 2007](https://doi.org/10.1007/s10994-007-5018-6)).
+fn fit_platt(points: &[(f64, f64, f64)]) -> Calibrator {
+    const MAX_ITERATIONS: usize = 100;
+    const MIN_STEP: f64 = 1e-10;
+    const SIGMA: f64 = 1e-12;
+    const EPSILON: f64 = 1e-5;
+
+    let positive: f64 = points.iter().filter(|p| p.1 > 0.0).map(|p| p.2).sum();
+    let negative: f64 = points.iter().filter(|p| p.1 <= 0.0).map(|p| p.2).sum();
+    let high_target = (positive + 1.0) / (positive + 2.0);
+    let low_target = 1.0 / (negative + 2.0);
+    let targets: Vec<f64> = points
+        .iter()
+        .map(|p| if p.1 > 0.0 { high_target } else { low_target })
+        .collect();
+
+    // negative log likelihood, computed without overflow
+    let objective = |a: f64, b: f64| -> f64 {
+        points
+            .iter()
+            .zip(&targets)
+            .map(|((f, _, w), t)| {
+                let z = f * a + b;
+                w * if z >= 0.0 {
+                    t * z + (-z).exp().ln_1p()
+                } else {
+                    (t - 1.0) * z + z.exp().ln_1p()
+                }
+            })
+            .sum()
+    };
+
+    let mut a = 0.0;
+    let mut b = ((negative + 1.0) / (positive + 1.0)).ln();
+    let mut value = objective(a, b);
+    for _ in 0..MAX_ITERATIONS {
+        let (mut h11, mut h22, mut h21, mut g1, mut g2) = (SIGMA, SIGMA, 0.0, 0.0, 0.0);
+        for ((f, _, w), t) in points.iter().zip(&targets) {
+            let z = f * a + b;
+            let (p, q) = if z >= 0.0 {
+                ((-z).exp() / (1.0 + (-z).exp()), 1.0 / (1.0 + (-z).exp()))
+            } else {
+                (1.0 / (1.0 + z.exp()), z.exp() / (1.0 + z.exp()))
+            };
+            let d2 = p * q;
+            h11 += w * f * f * d2;
+            h22 += w * d2;
+            h21 += w * f * d2;
+            let d1 = t - p;
+            g1 += w * f * d1;
+            g2 += w * d1;
+        }
+        if g1.abs() < EPSILON && g2.abs() < EPSILON {
+            break;
+        }
+
+        let det = h11 * h22 - h21 * h21;
+        let da = -(h22 * g1 - h21 * g2) / det;
+        let db = -(-h21 * g1 + h11 * g2) / det;
+        let gd = g1 * da + g2 * db;
+        let mut step = 1.0;
+        while step >= MIN_STEP {
+            let (new_a, new_b) = (a + step * da, b + step * db);
+            let new_value = objective(new_a, new_b);
+            if new_value < value + 1e-4 * step * gd {
+                (a, b, value) = (new_a, new_b, new_value);
+                break;
+            }
+            step /= 2.0;
+        }
+        if step < MIN_STEP {
+            debug!("Platt scaling line search failed");
+            break;
+        }
+    }
+    Calibrator::Platt { a, b }
+}
+
+/// Fit a non-decreasing function to `(margin, label, weight)` points with the pool adjacent violators algorithm.
+fn fit_isotonic(mut points: Vec<(f64, f64, f64)>) -> Calibrator {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // blocks of (first margin, last margin, weighted mean label, weight)
+    let mut blocks: Vec<(f64, f64, f64, f64)> = Vec::new();
+    for (x, y, w) in points {
+        if w <= 0.0 {
+            continue;
+        }
+        match blocks.last_mut() {
+            // equal margins must get the same probability
+            Some(last) if last.1 == x => {
+                last.2 = (last.2 * last.3 + y * w) / (last.3 + w);
+                last.3 += w;
+            }
+            _ => blocks.push((x, x, y, w)),
+        }
+        while blocks.len() > 1 && blocks[blocks.len() - 2].2 >= blocks[blocks.len() - 1].2 {
+            let last = blocks.pop().unwrap();
+            let prev = blocks.last_mut().unwrap();
+            prev.1 = last.1;
+            prev.2 = (prev.2 * prev.3 + last.2 * last.3) / (prev.3 + last.3);
+            prev.3 += last.3;
+        }
+    }
+
+    // constant within each block, linear between blocks
+    let (mut x, mut y) = (Vec::new(), Vec::new());
+    for (first, last, mean, _) in blocks {
+        x.push(first);
+        y.push(mean);
+        if last > first {
+            x.push(last);
+            y.push(mean);
+        }
+    }
+    if x.is_empty() {
+        (x, y) = (vec![0.0], vec![0.5]);
+    }
+    Calibrator::Isotonic { x, y }
+}
+
+/// A booster classifier with calibrated probabilities. Create with [`fit`](#method.fit) or load a saved model with
+/// [`load`](#method.load).
+pub struct CalibratedBooster {
+    booster: Booster,
+    method: CalibrationMethod,
+    calibrators: Vec<Calibrator>,
+}
+
+impl CalibratedBooster {
+    /// Calibrate a trained binary (`binary:logistic` or similar) or multiclass (`multi:softprob`) classifier on the
+    /// labels of `dcalib`, which should be held out from training. Weights of `dcalib` are used if set.
+    ///
+    /// The calibrator is stored as attributes of `booster`, replacing any previous calibration.
+    pub fn fit(mut booster: Booster, dcalib: &DMatrix, method: CalibrationMethod) -> XGBResult<Self> {
+        let margins = booster.predict_margin(dcalib)?;
+        let labels = dcalib.get_labels()?;
+        let weights = dcalib.get_weights()?;
+        let num_classes = num_classes(margins.len(), dcalib.num_rows())?;
+
+        let calibrators = if num_classes == 1 {
+            vec![Calibrator::fit(method, &margins, labels, weights)?]
+        } else {
+            if let Some(label) = labels
+                .iter()
+                .find(|label| label.fract() != 0.0 || **label < 0.0 || **label as usize >= num_classes)
+            {
+                return Err(XGBError::new(format!(
+                    "Label {} is not a class index in [0, {})",
+                    label, num_classes
+                )));
+            }
+            (0..num_classes)
+                .map(|class| {
+                    let class_margins: Vec<f32> = margins.iter().skip(class).step_by(num_classes).copied().collect();
+                    let is_class: Vec<f32> = labels
+                        .iter()
+                        .map(|label| (*label as usize == class) as u8 as f32)
+                        .collect();
+                    Calibrator::fit(method, &class_margins, &is_class, weights)
+                })
+                .collect::<XGBResult<Vec<_>>>()?
+        };
+
+        // a previous calibration may have stored more calibrators
+        for key in booster.get_attribute_names()? {
+            if key
+                .strip_prefix(ATTR_CALIBRATOR_PREFIX)
+                .is_some_and(|i| i.parse::<usize>().is_ok())
+            {
+                booster.remove_attribute(&key)?;
+            }
+        }
+        booster.set_attribute(ATTR_METHOD, &method.to_string())?;
+        booster.set_attribute(ATTR_NUM_CALIBRATORS, &calibrators.len().to_string())?;
+        for (i, calibrator) in calibrators.iter().enumerate() {
+            booster.set_attribute(&format!("{}{}", ATTR_CALIBRATOR_PREFIX, i), &calibrator.to_attribute())?;
+        }
+        Ok(CalibratedBooster {
+            booster,
+            method,
+            calibrators,
+        })
+    }
+
+    /// Use the calibrator stored in the attributes of `booster` by [`fit`](#method.fit).
+    pub fn from_booster(booster: Booster) -> XGBResult<Self> {
+        let missing = |key: &str| XGBError::new(format!("Model is missing the '{}' attribute", key));
+        let method: CalibrationMethod = booster
+            .get_attribute(ATTR_METHOD)?
+            .ok_or_else(|| missing(ATTR_METHOD))?
+            .parse()?;
+        let num_calibrators = booster
+            .get_attribute(ATTR_NUM_CALIBRATORS)?
+            .ok_or_else(|| missing(ATTR_NUM_CALIBRATORS))?;
+        let num_calibrators: usize = num_calibrators
+            .parse()
+            .map_err(|_| XGBError::new(format!("Invalid number of calibrators '{}'", num_calibrators)))?;
+        let calibrators = (0..num_calibrators)
+            .map(|i| {
+                let key = format!("{}{}", ATTR_CALIBRATOR_PREFIX, i);
+                let value = booster.get_attribute(&key)?.ok_or_else(|| missing(&key))?;
+                Calibrator::from_attribute(method, &value)
+            })
+            .collect::<XGBResult<Vec<_>>>()?;
+        Ok(CalibratedBooster {
+            booster,
+            method,
+            calibrators,
+        })
+    }
+
+    /// Load a model saved by [`save`](#method.save), along with its calibrator.
+    pub fn load<P: AsRef<Path>>(path: P) -> XGBResult<Self> {
+        CalibratedBooster::from_booster(Booster::load(path)?)
+    }
+
+    /// Save the model along with its calibrator to a file, in a format determined by the file extension as in
+    /// [`Booster::save`](../struct.Booster.html#method.save).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        self.booster.save(path)
+    }
+
+    /// Get the calibrated probability of each class for each row of `dmat`.
+    ///
+    /// Returns a flat array along with its shape (number of rows, number of classes), with two classes for binary
+    /// models.
+    pub fn predict_proba(&self, dmat: &DMatrix) -> XGBResult<(Vec<f32>, (usize, usize))> {
+        let margins = self.booster.predict_margin(dmat)?;
+        let num_rows = dmat.num_rows();
+        if num_classes(margins.len(), num_rows)? != self.calibrators.len() {
+            return Err(XGBError::new(format!(
+                "Model predicts {} margins per row, but has {} calibrators",
+                margins.len() / num_rows.max(1),
+                self.calibrators.len()
+            )));
+        }
+
+        if let [calibrator] = self.calibrators.as_slice() {
+            let proba = margins
+                .iter()
+                .flat_map(|margin| {
+                    let p = calibrator.transform(*margin);
+                    [1.0 - p, p]
+                })
+                .collect();
+            return Ok((proba, (num_rows, 2)));
+        }
+
+        let num_classes = self.calibrators.len();
+        let mut proba = Vec::with_capacity(margins.len());
+        for row in margins.chunks(num_classes) {
+            let start = proba.len();
+            proba.extend(row.iter().zip(&self.calibrators).map(|(m, c)| c.transform(*m)));
+            let sum: f32 = proba[start..].iter().sum();
+            for p in &mut proba[start..] {
+                *p = if sum > 0.0 { *p / sum } else { 1.0 / num_classes as f32 };
+            }
+        }
+        Ok((proba, (num_rows, num_classes)))
+    }
+
+    /// Reliability curve of the calibrated probabilities of each class against the labels of `dmat`, see
+    /// [`reliability_curve`](fn.reliability_curve.html). Binary models have a single curve for the positive class.
+    pub fn reliability_curves(&self, dmat: &DMatrix, num_bins: usize) -> XGBResult<Vec<Vec<ReliabilityBin>>> {
+        let (proba, (_, num_classes)) = self.predict_proba(dmat)?;
+        let labels = dmat.get_labels()?;
+        let classes = if num_classes == 2 { 1..2 } else { 0..num_classes };
+        classes
+            .map(|class| {
+                let class_proba: Vec<f32> = proba.iter().skip(class).step_by(num_classes).copied().collect();
+                let is_class: Vec<f32> = labels
+                    .iter()
+                    .map(|label| (*label as usize == class) as u8 as f32)
+                    .collect();
+                reliability_curve(&class_proba, &is_class, num_bins)
+            })
+            .collect()
+    }
+
+    /// Get the calibration method.
+    pub fn method(&self) -> CalibrationMethod {
+        self.method
+    }
+
+    /// Get the calibrator of each class (a single one for the positive class of binary models).
+    pub fn calibrators(&self) -> &[Calibrator] {
+        &self.calibrators
+    }
+
+    /// Get the underlying booster.
+    pub fn booster(&self) -> &Booster {
+        &self.booster
+    }
+
+    /// Take the underlying booster, which keeps the calibrator in its attributes.
+    pub fn into_booster(self) -> Booster {
+        self.booster
+    }
+}
+
+/// Number of classes for `num_margins` margins predicted for `num_rows` rows, 1 for binary models.
+fn num_classes(num_margins: usize, num_rows: usize) -> XGBResult<usize> {
+    if num_rows == 0 || num_margins == 0 || !num_margins.is_multiple_of(num_rows) {
+        return Err(XGBError::new(format!(
+            "Can't calibrate {} margins predicted for {} rows",
+            num_margins, num_rows
+        )));
+    }
+    Ok(num_margins / num_rows)
+}
+
+/// A bin of a [`reliability_curve`](fn.reliability_curve.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReliabilityBin {
+    /// Lower bound of the predicted probabilities in this bin.
+    pub lower: f32,
+    /// Upper bound of the predicted probabilities in this bin.
+    pub upper: f32,
+    /// Mean predicted probability of rows in this bin.
+    pub mean_predicted: f32,
+    /// Fraction of rows in this bin that are positive.
+    pub fraction_positive: f32,
+    /// Number of rows in this bin.
+    pub count: usize,
+}
+
+/// Split predicted probabilities of the positive class into `num_bins` equal width bins over [0, 1], and compare
+/// the mean prediction of each bin with the fraction of positive `labels` (1 for positive, 0 otherwise).
+///
+/// Probabilities are well calibrated when these are close in every bin. Empty bins are left out.
+///
+/// ```
+/// use xgb::calibration;
+///
+/// let curve = calibration::reliability_curve(&[0.1, 0.2, 0.8, 0.9], &[0.0, 1.0, 1.0, 1.0], 2).unwrap();
+/// assert_eq!(curve.len(), 2);
+/// assert_eq!(curve[0].fraction_positive, 0.5);
+/// assert_eq!(curve[1].mean_predicted, 0.85);
+/// ```
+pub fn reliability_curve(probabilities: &[f32], labels: &[f32], num_bins: usize) -> XGBResult<Vec<ReliabilityBin>> {
+    if probabilities.len() != labels.len() {
+        return Err(XGBError::new(format!(
+            "Number of probabilities ({}) does not match number of labels ({})",
+            probabilities.len(),
+            labels.len()
+        )));
+    }
+    if num_bins == 0 {
+        return Err(XGBError::new("Number of bins must be at least 1"));
+    }
+
+    // (sum of probabilities, sum of labels, count)
+    let mut bins = vec![(0.0f64, 0.0f64, 0usize); num_bins];
+    for (p, label) in probabilities.iter().zip(labels) {
+        let bin = ((*p as f64 * num_bins as f64) as usize).min(num_bins - 1);
+        bins[bin].0 += *p as f64;
+        bins[bin].1 += *label as f64;
+        bins[bin].2 += 1;
+    }
+    Ok(bins
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, _, count))| *count > 0)
+        .map(|(i, (p_sum, label_sum, count))| ReliabilityBin {
+            lower: i as f32 / num_bins as f32,
+            upper: (i + 1) as f32 / num_bins as f32,
+            mean_predicted: (p_sum / *count as f64) as f32,
+            fraction_positive: (label_sum / *count as f64) as f32,
+            count: *count,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::learning::Objective;
+    use crate::testing;
+
+    #[test]
+    fn platt() {
+        // labels drawn deterministically with probability sigmoid(2 * margin - 1)
+        let margins: Vec<f32> = (0..2000).map(|i| (i % 200) as f32 / 40.0 - 2.5).collect();
+        let labels: Vec<f32> = margins
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let p = 1.0 / (1.0 + (-(2.0 * m - 1.0)).exp());
+                ((i / 200) as f32 / 10.0 + 0.05 < p) as u8 as f32
+            })
+            .collect();
+        let Calibrator::Platt { a, b } = Calibrator::fit(CalibrationMethod::Platt, &margins, &labels, &[]).unwrap()
+        else {
+            panic!("expected Platt calibrator");
+        };
+        assert!((a + 2.0).abs() < 0.2, "a = {}", a);
+        assert!((b - 1.0).abs() < 0.2, "b = {}", b);
+
+        let calibrator = Calibrator::Platt { a, b };
+        assert!(calibrator.transform(-5.0) < 0.01);
+        assert!((calibrator.transform(0.5) - 0.5).abs() < 0.05);
+        assert!(calibrator.transform(5.0) > 0.99);
+
+        // weights count as duplicated rows
+        let weighted = Calibrator::fit(CalibrationMethod::Platt, &[0.0, 1.0], &[0.0, 1.0], &[2.0, 1.0]).unwrap();
+        let duplicated = Calibrator::fit(CalibrationMethod::Platt, &[0.0, 0.0, 1.0], &[0.0, 0.0, 1.0], &[]).unwrap();
+        assert_eq!(weighted, duplicated);
+
+        assert!(Calibrator::fit(CalibrationMethod::Platt, &[0.0], &[0.0, 1.0], &[]).is_err());
+        assert!(Calibrator::fit(CalibrationMethod::Platt, &[], &[], &[]).is_err());
+        assert!(Calibrator::fit(CalibrationMethod::Isotonic, &[0.0, 1.0], &[0.0, 0.5], &[]).is_err());
+    }
+
+    #[test]
+    fn isotonic() {
+        let margins = [1.0, 2.0, 3.0, 4.0, 5.0, 5.0];
+        let labels = [0.0, 1.0, 0.0, 1.0, 1.0, 0.0];
+        let calibrator = Calibrator::fit(CalibrationMethod::Isotonic, &margins, &labels, &[]).unwrap();
+        // 2 and 3 are pooled, then 4 and both 5s
+        assert_eq!(
+            calibrator,
+            Calibrator::Isotonic {
+                x: vec![1.0, 2.0, 3.0, 4.0, 5.0],
+                y: vec![0.0, 0.5, 0.5, 2.0 / 3.0, 2.0 / 3.0],
+            }
+        );
+        assert_eq!(calibrator.transform(0.0), 0.0);
+        assert_eq!(calibrator.transform(1.5), 0.25);
+        assert_eq!(calibrator.transform(2.5), 0.5);
+        assert_eq!(calibrator.transform(10.0), 2.0 / 3.0);
+
+        // weights count as duplicated rows, rows without weight are ignored
+        let weighted = Calibrator::fit(
+            CalibrationMethod::Isotonic,
+            &[1.0, 2.0, 3.0],
+            &[1.0, 0.0, 1.0],
+            &[2.0, 1.0, 0.0],
+        )
+        .unwrap();
+        assert_eq!(
+            weighted,
+            Calibrator::Isotonic {
+                x: vec![1.0, 2.0],
+                y: vec![2.0 / 3.0, 2.0 / 3.0],
+            }
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        for calibrator in [
+            Calibrator::Platt { a: -1.25, b: 0.1 },
+            Calibrator::Isotonic {
+                x: vec![-1.0, 0.3, 2.5],
+                y: vec![0.0, 1.0 / 3.0, 1.0],
+            },
+        ] {
+            let method = match calibrator {
+                Calibrator::Platt { .. } => CalibrationMethod::Platt,
+                Calibrator::Isotonic { .. } => CalibrationMethod::Isotonic,
+            };
+            let value = calibrator.to_attribute();
+            assert_eq!(Calibrator::from_attribute(method, &value).unwrap(), calibrator);
+        }
+        assert!(Calibrator::from_attribute(CalibrationMethod::Platt, "1").is_err());
+        assert!(Calibrator::from_attribute(CalibrationMethod::Isotonic, "1 2 3").is_err());
+        assert!(Calibrator::from_attribute(CalibrationMethod::Isotonic, "1 x").is_err());
+        assert_eq!(
+            "isotonic".parse::<CalibrationMethod>().unwrap(),
+            CalibrationMethod::Isotonic
+        );
+        assert!("sigmoid".parse::<CalibrationMethod>().is_err());
+    }
+
+    #[test]
+    fn reliability() {
+        let curve = reliability_curve(&[0.05, 0.15, 0.1, 0.95, 1.0], &[0.0, 1.0, 0.0, 1.0, 1.0], 10).unwrap();
+        let counts: Vec<usize> = curve.iter().map(|bin| bin.count).collect();
+        assert_eq!(counts, vec![1, 2, 2]);
+        assert_eq!(curve[1].lower, 0.1);
+        assert_eq!(curve[1].fraction_positive, 0.5);
+        assert_eq!(curve[2].upper, 1.0);
+        assert!((curve[2].mean_predicted - 0.975).abs() < 1e-6);
+        assert!(reliability_curve(&[0.5], &[], 10).is_err());
+        assert!(reliability_curve(&[0.5], &[1.0], 0).is_err());
+    }
+
+    #[test]
+    fn calibrate_binary() {
+        let x = testing::features(400);
+        let labels: Vec<f32> = x.chunks(2).map(|row| (row[0] + row[1] > 12.0) as u8 as f32).collect();
+        let booster = testing::train(&testing::dmatrix(&x, &labels), Objective::BinaryLogistic, 5);
+        let mut dcalib = DMatrix::from_dense(&x, 200).unwrap();
+        dcalib.set_labels(&labels).unwrap();
+
+        for method in [CalibrationMethod::Platt, CalibrationMethod::Isotonic] {
+            let calibrated = CalibratedBooster::fit(booster_clone(&booster), &dcalib, method).unwrap();
+            assert_eq!(calibrated.calibrators().len(), 1);
+            let (proba, shape) = calibrated.predict_proba(&dcalib).unwrap();
+            assert_eq!(shape, (200, 2));
+            assert!(proba.chunks(2).all(|row| (row[0] + row[1] - 1.0).abs() < 1e-6));
+            let curves = calibrated.reliability_curves(&dcalib, 5).unwrap();
+            assert_eq!(curves.len(), 1);
+
+            let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+            let path = tmp_dir.path().join("calibrated.json");
+            calibrated.save(&path).unwrap();
+            let loaded = CalibratedBooster::load(&path).unwrap();
+            assert_eq!(loaded.method(), method);
+            assert_eq!(loaded.calibrators(), calibrated.calibrators());
+            assert_eq!(loaded.predict_proba(&dcalib).unwrap(), (proba, shape));
+        }
+
+        assert!(CalibratedBooster::from_booster(booster_clone(&booster)).is_err());
+
+        // calibrators left over from an earlier calibration with more of them are removed
+        let mut stale = booster_clone(&booster);
+        stale.set_attribute("calibration_1", "1 0").unwrap();
+        stale.set_attribute("calibration_12", "1 0").unwrap();
+        let refit = CalibratedBooster::fit(stale, &dcalib, CalibrationMethod::Platt).unwrap();
+        let mut names = refit.into_booster().get_attribute_names().unwrap();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["calibration_0", "calibration_method", "calibration_num_calibrators"]
+        );
+
+        dcalib.set_labels(&vec![2.0; 200]).unwrap();
+        let result = CalibratedBooster::fit(booster, &dcalib, CalibrationMethod::Platt);
+        assert!(result.is_err_and(|err| err.to_string().contains("Label 2 is not a binary label")));
+    }
+
+    #[test]
+    fn calibrate_multiclass() {
+        let x = testing::features(300);
+        let labels: Vec<f32> = x.chunks(2).map(|row| (row[0] as u32 % 3) as f32).collect();
+        let booster = testing::train(&testing::dmatrix(&x, &labels), Objective::MultiSoftprob(3), 5);
+        let mut dcalib = DMatrix::from_dense(&x, 150).unwrap();
+        dcalib.set_labels(&labels).unwrap();
+
+        let calibrated = CalibratedBooster::fit(booster, &dcalib, CalibrationMethod::Isotonic).unwrap();
+        assert_eq!(calibrated.calibrators().len(), 3);
+        let (proba, shape) = calibrated.predict_proba(&dcalib).unwrap();
+        assert_eq!(shape, (150, 3));
+        assert!(proba.chunks(3).all(|row| (row.iter().sum::<f32>() - 1.0).abs() < 1e-5));
+        assert_eq!(calibrated.reliability_curves(&dcalib, 5).unwrap().len(), 3);
+
+        dcalib.set_labels(&vec![3.0; 150]).unwrap();
+        let booster = calibrated.into_booster();
+        assert!(CalibratedBooster::fit(booster, &dcalib, CalibrationMethod::Platt).is_err());
+    }
+
+    fn booster_clone(booster: &Booster) -> Booster {
+        Booster::load_buffer(&booster.save_buffer(true).unwrap()).unwrap()
+    }
+}
@@ -0,0 +1,346 @@
+//! Periodically saving models during training, and resuming training from the latest saved model.
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::{Booster, XGBResult};
+
+const PREFIX: &str = "checkpoint-";
+const EXTENSION: &str = ".ubj";
+const STOPPED: &str = "-stopped";
+
+/// Options for saving checkpoints during [`Booster::train`](struct.Booster.html#method.train), set through
+/// [`TrainingParametersBuilder::checkpoint`](parameters/struct.TrainingParametersBuilder.html#method.checkpoint).
+/// Create using [`CheckpointConfigBuilder`](struct.CheckpointConfigBuilder.html).
+///
+/// Checkpoints are saved in UBJSON format as `checkpoint-<rounds>.ubj` in `directory`, where `<rounds>` is the
+/// number of rounds boosted so far. Each checkpoint is first written to a temporary file, then renamed, so an
+/// interrupted write never leaves a partial checkpoint behind.
+///
+/// If a training callback stops training early, a final checkpoint named `checkpoint-<rounds>-stopped.ubj` is saved,
+/// and resuming from it returns the saved model without training further. To train it again, delete the directory or
+/// turn off `resume`.
+///
+/// When training with [`collective`](collective/index.html), give each worker its own directory.
+///
+/// ```no_run
+/// use xgb::{Booster, CheckpointConfigBuilder, DMatrix, parameters::TrainingParametersBuilder};
+///
+/// let dtrain = DMatrix::load("train.libsvm?format=libsvm").unwrap();
+/// // save every 50 rounds, keeping the 3 latest checkpoints, and resume from the latest one on restart
+/// let checkpoint = CheckpointConfigBuilder::default()
+///     .directory("checkpoints")
+///     .interval(50)
+///     .build()
+///     .unwrap();
+/// let params = TrainingParametersBuilder::default()
+///     .dtrain(&dtrain)
+///     .boost_rounds(1000)
+///     .checkpoint(Some(checkpoint))
+///     .build()
+///     .unwrap();
+/// let booster = Booster::train(&params).unwrap();
+/// ```
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct CheckpointConfig {
+    /// Directory checkpoints are saved in, created if it doesn't exist.
+    #[builder(setter(into))]
+    directory: PathBuf,
+
+    /// Number of boosting rounds between checkpoints. A checkpoint is always saved after the final round.
+    ///
+    /// *default*: 10
+    #[builder(default = "10")]
+    interval: u32,
+
+    /// Number of most recent checkpoints to keep, older ones are deleted.
+    ///
+    /// *default*: 3
+    #[builder(default = "3")]
+    keep: usize,
+
+    /// Whether to continue training from the latest checkpoint in `directory` if there is one.
+    ///
+    /// *default*: `true`
+    #[builder(default = "true")]
+    resume: bool,
+}
+
+impl CheckpointConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if self.interval == Some(0) {
+            return Err("Invalid value for 'interval' parameter, must be at least 1.".to_owned());
+        }
+        if self.keep == Some(0) {
+            return Err("Invalid value for 'keep' parameter, must be at least 1.".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl CheckpointConfig {
+    /// Get the directory checkpoints are saved in.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Get the number of boosting rounds between checkpoints.
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// Get the number of checkpoints to keep.
+    pub fn keep(&self) -> usize {
+        self.keep
+    }
+
+    /// Get whether training resumes from the latest checkpoint.
+    pub fn resume(&self) -> bool {
+        self.resume
+    }
+
+    /// Get the path and number of rounds of the latest checkpoint in the directory, if any.
+    pub fn latest(&self) -> XGBResult<Option<(PathBuf, u32)>> {
+        Ok(self.list()?.pop())
+    }
+
+    /// Whether a checkpoint should be saved after `rounds` of `total_rounds` boosting rounds.
+    pub(crate) fn is_due(&self, rounds: u32, total_rounds: u32) -> bool {
+        rounds.is_multiple_of(self.interval) || rounds == total_rounds
+    }
+
+    /// Save a checkpoint of `booster` after `rounds` boosting rounds, marked as `stopped` if training was stopped
+    /// early, then delete old checkpoints.
+    pub(crate) fn save(&self, booster: &Booster, rounds: u32, stopped: bool) -> XGBResult<()> {
+        debug!(
+            "Saving checkpoint after {} rounds to: {}",
+            rounds,
+            self.directory.display()
+        );
+        fs::create_dir_all(&self.directory)?;
+        let name = format!(
+            "{}{:08}{}{}",
+            PREFIX,
+            rounds,
+            if stopped { STOPPED } else { "" },
+            EXTENSION
+        );
+        let tmp_path = self.directory.join(format!(".{}.tmp", name));
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&booster.save_buffer(true)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, self.directory.join(name))?;
+
+        let checkpoints = self.list()?;
+        for (path, _) in &checkpoints[..checkpoints.len().saturating_sub(self.keep)] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Load the latest checkpoint into `booster` if resuming is enabled, returning the number of rounds it was
+    /// boosted for and whether training was stopped early.
+    pub(crate) fn restore(&self, booster: &mut Booster) -> XGBResult<(u32, bool)> {
+        if !self.resume {
+            return Ok((0, false));
+        }
+        let Some((path, _)) = self.latest()? else {
+            return Ok((0, false));
+        };
+        debug!("Resuming from checkpoint: {}", path.display());
+        booster.load_model_buffer(&fs::read(&path)?)?;
+        Ok((booster.boosted_rounds()?, is_stopped(&path)))
+    }
+
+    /// Checkpoints in the directory with their number of rounds, sorted from oldest to latest.
+    fn list(&self) -> XGBResult<Vec<(PathBuf, u32)>> {
+        if !self.directory.exists() {
+            return Ok(Vec::new());
+        }
+        let mut checkpoints = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            let rounds = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(PREFIX))
+                .and_then(|name| name.strip_suffix(EXTENSION))
+                .and_then(|rounds| rounds.strip_suffix(STOPPED).unwrap_or(rounds).parse().ok());
+            if let Some(rounds) = rounds {
+                checkpoints.push((path, rounds));
+            }
+        }
+        // a stopped checkpoint replaces a periodic one saved after the same round
+        checkpoints.sort_by_key(|(path, rounds)| (*rounds, is_stopped(path)));
+        Ok(checkpoints)
+    }
+}
+
+/// Whether the checkpoint at `path` was saved after training was stopped early.
+fn is_stopped(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.ends_with(&format!("{}{}", STOPPED, EXTENSION)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DMatrix;
+    use crate::parameters::{self, TrainingCallback, TrainingParametersBuilder, tree};
+
+    fn config(directory: &Path) -> CheckpointConfig {
+        CheckpointConfigBuilder::default()
+            .directory(directory)
+            .interval(2)
+            .keep(2)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(CheckpointConfigBuilder::default().build().is_err());
+        assert!(
+            CheckpointConfigBuilder::default()
+                .directory("a")
+                .interval(0)
+                .build()
+                .is_err()
+        );
+        assert!(
+            CheckpointConfigBuilder::default()
+                .directory("a")
+                .keep(0)
+                .build()
+                .is_err()
+        );
+
+        let config = config(Path::new("a"));
+        assert!(!config.is_due(1, 5));
+        assert!(config.is_due(2, 5));
+        assert!(config.is_due(5, 5));
+    }
+
+    #[test]
+    fn list_checkpoints() {
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let config = config(&tmp_dir.path().join("missing"));
+        assert_eq!(config.latest().unwrap(), None);
+
+        let config = self::config(tmp_dir.path());
+        for name in [
+            "checkpoint-00000010.ubj",
+            "checkpoint-00000002.ubj",
+            "checkpoint-00000002-stopped.ubj",
+            "other.ubj",
+            ".checkpoint-00000012.ubj.tmp",
+        ] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let rounds: Vec<u32> = config.list().unwrap().iter().map(|(_, rounds)| *rounds).collect();
+        assert_eq!(rounds, vec![2, 2, 10]);
+        assert!(is_stopped(&config.list().unwrap()[1].0));
+        assert_eq!(
+            config.latest().unwrap(),
+            Some((tmp_dir.path().join("checkpoint-00000010.ubj"), 10))
+        );
+    }
+
+    fn train(dtrain: &DMatrix, boost_rounds: u32, checkpoint: Option<CheckpointConfig>) -> Booster {
+        try_train(dtrain, boost_rounds, checkpoint, None).unwrap()
+    }
+
+    fn try_train(
+        dtrain: &DMatrix,
+        boost_rounds: u32,
+        checkpoint: Option<CheckpointConfig>,
+        callbacks: Option<Vec<TrainingCallback>>,
+    ) -> XGBResult<Booster> {
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .max_depth(3)
+            .build()
+            .unwrap();
+        let booster_params = parameters::BoosterParametersBuilder::default()
+            .booster_type(parameters::BoosterType::Tree(tree_params))
+            .verbose(false)
+            .build()
+            .unwrap();
+        let params = TrainingParametersBuilder::default()
+            .dtrain(dtrain)
+            .booster_params(booster_params)
+            .boost_rounds(boost_rounds)
+            .checkpoint(checkpoint)
+            .callbacks(callbacks)
+            .build()
+            .unwrap();
+        Booster::train(&params)
+    }
+
+    fn dtrain() -> DMatrix {
+        let x: Vec<f32> = (0..200).map(|i| ((i * 3) % 7) as f32).collect();
+        let mut dtrain = DMatrix::from_dense(&x, 100).unwrap();
+        dtrain
+            .set_labels(&(0..100).map(|i| (i % 5) as f32).collect::<Vec<_>>())
+            .unwrap();
+        dtrain
+    }
+
+    #[test]
+    fn train_and_resume() {
+        let dtrain = dtrain();
+
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let config = config(tmp_dir.path());
+        let booster = train(&dtrain, 5, Some(config.clone()));
+        assert_eq!(booster.boosted_rounds().unwrap(), 5);
+        let rounds: Vec<u32> = config.list().unwrap().iter().map(|(_, rounds)| *rounds).collect();
+        assert_eq!(rounds, vec![4, 5]);
+
+        // continues from the 5 rounds already trained
+        let resumed = train(&dtrain, 8, Some(config.clone()));
+        assert_eq!(resumed.boosted_rounds().unwrap(), 8);
+        assert_eq!(config.latest().unwrap().unwrap().1, 8);
+
+        let expected = train(&dtrain, 8, None).predict(&dtrain).unwrap();
+        for (pred, expected) in resumed.predict(&dtrain).unwrap().iter().zip(&expected) {
+            assert!((pred - expected).abs() < 1e-5);
+        }
+
+        // nothing left to train
+        let finished = train(&dtrain, 8, Some(config.clone()));
+        assert_eq!(finished.boosted_rounds().unwrap(), 8);
+
+        // the checkpoint has more rounds than requested
+        let result = try_train(&dtrain, 5, Some(config), None);
+        assert!(result.is_err_and(|err| err.to_string().contains("more than the 5 boosting rounds")));
+    }
+
+    #[test]
+    fn resume_after_early_stop() {
+        let dtrain = dtrain();
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let config = config(tmp_dir.path());
+        let stop_after_3: TrainingCallback = |env| env.iteration < 2;
+        let booster = try_train(&dtrain, 10, Some(config.clone()), Some(vec![stop_after_3])).unwrap();
+        assert_eq!(booster.boosted_rounds().unwrap(), 3);
+        let (path, rounds) = config.latest().unwrap().unwrap();
+        assert_eq!(rounds, 3);
+        assert!(is_stopped(&path));
+
+        // the restarted run keeps the early stop instead of training the remaining rounds
+        let resumed = train(&dtrain, 10, Some(config.clone()));
+        assert_eq!(resumed.boosted_rounds().unwrap(), 3);
+        assert_eq!(resumed.predict(&dtrain).unwrap(), booster.predict(&dtrain).unwrap());
+
+        // stopping right after a periodic checkpoint
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let config = self::config(tmp_dir.path());
+        let stop_after_4: TrainingCallback = |env| env.iteration < 3;
+        try_train(&dtrain, 10, Some(config.clone()), Some(vec![stop_after_4])).unwrap();
+        let (path, rounds) = config.latest().unwrap().unwrap();
+        assert_eq!(rounds, 4);
+        assert!(is_stopped(&path));
+        assert_eq!(train(&dtrain, 10, Some(config)).boosted_rounds().unwrap(), 4);
+    }
+}
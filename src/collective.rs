@@ -0,0 +1,456 @@
+//! Collective communication between worker processes, used for distributed training.
+//!
+//! A [`Tracker`](struct.Tracker.html) coordinates a fixed number of workers. Each worker connects to it by calling
+//! [`init`](fn.init.html) with the tracker's [`worker_args`](struct.Tracker.html#method.worker_args), after which
+//! [`Booster::train`](../struct.Booster.html#method.train) synchronises gradient statistics across all workers, so
+//! every worker trains the same model from its own shard of the data.
+//!
+//! ```no_run
+//! use xgb::collective::{self, Tracker, TrackerConfigBuilder};
+//!
+//! // in the coordinating process
+//! let config = TrackerConfigBuilder::default().n_workers(4).build().unwrap();
+//! let tracker = Tracker::new(&config).unwrap();
+//! tracker.start().unwrap();
+//! let worker_args = tracker.worker_args().unwrap();
+//! // ... start 4 worker processes, passing them `worker_args` ...
+//! tracker.wait(None).unwrap();
+//!
+//! // in each worker process
+//! let communicator = collective::init(&worker_args).unwrap();
+//! println!("worker {} of {}", collective::rank(), collective::world_size());
+//! // ... load this worker's shard and call Booster::train ...
+//! drop(communicator);
+//! ```
+//!
+//! Outside of an initialised communicator, the functions in this module behave as for a single worker: the rank is 0,
+//! the world size 1, and [`allreduce`](fn.allreduce.html) and [`broadcast`](fn.broadcast.html) leave their buffers
+//! unchanged.
+use std::ffi::{self, CStr};
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::time::Duration;
+
+use crate::{XGBError, XGBResult};
+
+/// Options used when creating a [`Tracker`](struct.Tracker.html). Create using
+/// [`TrackerConfigBuilder`](struct.TrackerConfigBuilder.html).
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct TrackerConfig {
+    /// Number of workers that will connect to the tracker.
+    n_workers: u32,
+
+    /// Address the tracker listens on.
+    ///
+    /// *default*: `"127.0.0.1"`
+    #[builder(default = "\"127.0.0.1\".to_owned()")]
+    host_ip: String,
+
+    /// Port the tracker listens on, 0 picks any free port.
+    ///
+    /// *default*: 0
+    #[builder(default)]
+    port: u16,
+
+    /// Maximum time in seconds to wait for workers to connect, `None` waits forever.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    timeout: Option<u32>,
+}
+
+impl TrackerConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if self.n_workers == Some(0) {
+            return Err("Invalid value for 'n_workers' parameter, must be at least 1.".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl TrackerConfig {
+    /// Get the number of workers.
+    pub fn n_workers(&self) -> u32 {
+        self.n_workers
+    }
+
+    /// Get the address the tracker listens on.
+    pub fn host_ip(&self) -> &str {
+        &self.host_ip
+    }
+
+    /// Get the port the tracker listens on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Get the timeout in seconds.
+    pub fn timeout(&self) -> Option<u32> {
+        self.timeout
+    }
+
+    fn to_json(&self) -> String {
+        // Escape backslashes and quotes for valid JSON
+        let host_ip = self.host_ip.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut json = format!(
+            r#"{{"dmlc_communicator":"rabit","n_workers":{},"host_ip":"{}","port":{}"#,
+            self.n_workers, host_ip, self.port
+        );
+        if let Some(timeout) = self.timeout {
+            json.push_str(&format!(r#","timeout":{}"#, timeout));
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// Tracker coordinating the workers of a distributed training job, running in the background of the process that
+/// created it.
+pub struct Tracker {
+    handle: xgboost_sys::TrackerHandle,
+}
+
+impl Tracker {
+    /// Create a new tracker, which doesn't accept connections until [`start`](#method.start) is called.
+    pub fn new(config: &TrackerConfig) -> XGBResult<Self> {
+        Tracker::from_json(&config.to_json())
+    }
+
+    pub(crate) fn from_json(config: &str) -> XGBResult<Self> {
+        let config = ffi::CString::new(config).map_err(|err| XGBError::new(err.to_string()))?;
+        let mut handle = ptr::null_mut();
+        xgb_call!(xgboost_sys::XGTrackerCreate(config.as_ptr(), &mut handle))?;
+        Ok(Tracker { handle })
+    }
+
+    /// Start accepting connections from workers in the background.
+    pub fn start(&self) -> XGBResult<()> {
+        let config = ffi::CString::new("{}").unwrap();
+        xgb_call!(xgboost_sys::XGTrackerRun(self.handle, config.as_ptr()))
+    }
+
+    /// Get the JSON config workers should pass to [`init`](fn.init.html) to connect to this tracker.
+    pub fn worker_args(&self) -> XGBResult<String> {
+        let mut out = ptr::null();
+        xgb_call!(xgboost_sys::XGTrackerWorkerArgs(self.handle, &mut out))?;
+        let args = unsafe { CStr::from_ptr(out) };
+        Ok(args.to_str().map_err(|err| XGBError::new(err.to_string()))?.to_owned())
+    }
+
+    /// Block until all workers have finished, or until `timeout` has passed.
+    pub fn wait(&self, timeout: Option<Duration>) -> XGBResult<()> {
+        let config = match timeout {
+            Some(timeout) => format!(r#"{{"timeout":{}}}"#, timeout.as_secs()),
+            None => "{}".to_owned(),
+        };
+        let config = ffi::CString::new(config).unwrap();
+        xgb_call!(xgboost_sys::XGTrackerWaitFor(self.handle, config.as_ptr()))
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        xgb_call!(xgboost_sys::XGTrackerFree(self.handle)).unwrap();
+    }
+}
+
+/// Connection of this process to the other workers, finalised when dropped.
+///
+/// Created by [`init`](fn.init.html).
+pub struct Communicator {
+    // only created through init
+    _private: (),
+}
+
+impl Drop for Communicator {
+    fn drop(&mut self) {
+        xgb_call!(xgboost_sys::XGCommunicatorFinalize()).unwrap();
+    }
+}
+
+/// Connect this process to the other workers, using the JSON config returned by
+/// [`Tracker::worker_args`](struct.Tracker.html#method.worker_args).
+///
+/// Additional settings such as `"dmlc_task_id"` (used to order workers) or `"dmlc_timeout"` can be added to the
+/// config object. Blocks until all workers have connected.
+pub fn init(config: &str) -> XGBResult<Communicator> {
+    let config = ffi::CString::new(config).map_err(|err| XGBError::new(err.to_string()))?;
+    xgb_call!(xgboost_sys::XGCommunicatorInit(config.as_ptr()))?;
+    Ok(Communicator { _private: () })
+}
+
+/// Get the rank of this worker, from 0 to [`world_size`](fn.world_size.html) - 1.
+pub fn rank() -> usize {
+    unsafe { xgboost_sys::XGCommunicatorGetRank() as usize }
+}
+
+/// Get the number of workers.
+pub fn world_size() -> usize {
+    unsafe { xgboost_sys::XGCommunicatorGetWorldSize() as usize }
+}
+
+/// Whether this process is connected to other workers.
+pub fn is_distributed() -> bool {
+    unsafe { xgboost_sys::XGCommunicatorIsDistributed() != 0 }
+}
+
+/// Print a message through the tracker.
+pub fn print(message: &str) -> XGBResult<()> {
+    let message = ffi::CString::new(message).map_err(|err| XGBError::new(err.to_string()))?;
+    xgb_call!(xgboost_sys::XGCommunicatorPrint(message.as_ptr()))
+}
+
+/// Get the name of the processor (host) this worker runs on.
+pub fn processor_name() -> XGBResult<String> {
+    let mut out: *const c_char = ptr::null();
+    xgb_call!(xgboost_sys::XGCommunicatorGetProcessorName(&mut out))?;
+    let name = unsafe { CStr::from_ptr(out) };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Element types supported by [`allreduce`](fn.allreduce.html) and [`broadcast`](fn.broadcast.html).
+pub trait CollectiveElement: Copy + sealed::Sealed {
+    /// XGBoost's identifier for this type.
+    #[doc(hidden)]
+    const DATA_TYPE: c_int;
+}
+
+macro_rules! impl_collective_element {
+    ($t:ty, $data_type:expr) => {
+        impl sealed::Sealed for $t {}
+        impl CollectiveElement for $t {
+            const DATA_TYPE: c_int = $data_type;
+        }
+    };
+}
+
+impl_collective_element!(f32, 1);
+impl_collective_element!(f64, 2);
+impl_collective_element!(i8, 4);
+impl_collective_element!(i16, 5);
+impl_collective_element!(i32, 6);
+impl_collective_element!(i64, 7);
+impl_collective_element!(u8, 8);
+impl_collective_element!(u16, 9);
+impl_collective_element!(u32, 10);
+impl_collective_element!(u64, 11);
+
+/// Reduction applied by [`allreduce`](fn.allreduce.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReduceOp {
+    /// Element-wise maximum.
+    Max,
+
+    /// Element-wise minimum.
+    Min,
+
+    /// Element-wise sum.
+    Sum,
+
+    /// Bitwise AND, only valid for integer types.
+    BitwiseAnd,
+
+    /// Bitwise OR, only valid for integer types.
+    BitwiseOr,
+
+    /// Bitwise XOR, only valid for integer types.
+    BitwiseXor,
+}
+
+impl ReduceOp {
+    fn as_c_int(self) -> c_int {
+        match self {
+            ReduceOp::Max => 0,
+            ReduceOp::Min => 1,
+            ReduceOp::Sum => 2,
+            ReduceOp::BitwiseAnd => 3,
+            ReduceOp::BitwiseOr => 4,
+            ReduceOp::BitwiseXor => 5,
+        }
+    }
+}
+
+/// Reduce `buffer` element-wise across all workers, replacing its contents with the result on every worker.
+///
+/// All workers must call this with buffers of the same length.
+pub fn allreduce<T: CollectiveElement>(buffer: &mut [T], op: ReduceOp) -> XGBResult<()> {
+    xgb_call!(xgboost_sys::XGCommunicatorAllreduce(
+        buffer.as_mut_ptr() as *mut c_void,
+        buffer.len(),
+        T::DATA_TYPE,
+        op.as_c_int()
+    ))
+}
+
+/// Copy `buffer` from the worker with rank `root` to all other workers.
+///
+/// All workers must call this with buffers of the same length.
+pub fn broadcast<T: CollectiveElement>(buffer: &mut [T], root: usize) -> XGBResult<()> {
+    xgb_call!(xgboost_sys::XGCommunicatorBroadcast(
+        buffer.as_mut_ptr() as *mut c_void,
+        std::mem::size_of_val(buffer),
+        root as c_int
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::{self, learning, tree};
+    use crate::{Booster, DMatrix};
+    use std::path::Path;
+    use std::process::Command;
+
+    const WORKER_ARGS_VAR: &str = "XGB_TEST_COLLECTIVE_WORKER_ARGS";
+    const WORKER_OUTPUT_VAR: &str = "XGB_TEST_COLLECTIVE_OUTPUT";
+    const NUM_ROWS: usize = 200;
+    const NUM_COLS: usize = 3;
+
+    #[test]
+    fn tracker_config() {
+        assert!(TrackerConfigBuilder::default().build().is_err());
+        assert!(TrackerConfigBuilder::default().n_workers(0).build().is_err());
+
+        let config = TrackerConfigBuilder::default()
+            .n_workers(3)
+            .timeout(Some(60))
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.to_json(),
+            r#"{"dmlc_communicator":"rabit","n_workers":3,"host_ip":"127.0.0.1","port":0,"timeout":60}"#
+        );
+    }
+
+    #[test]
+    fn single_process() {
+        assert_eq!(rank(), 0);
+        assert_eq!(world_size(), 1);
+        assert!(!is_distributed());
+
+        let mut values = [1.0f32, 2.0];
+        allreduce(&mut values, ReduceOp::Sum).unwrap();
+        assert_eq!(values, [1.0, 2.0]);
+        broadcast(&mut values, 0).unwrap();
+        assert_eq!(values, [1.0, 2.0]);
+    }
+
+    /// Rows of the full dataset, and their labels.
+    fn dataset() -> (Vec<f32>, Vec<f32>) {
+        let x: Vec<f32> = (0..NUM_ROWS * NUM_COLS).map(|i| ((i * 7) % 10) as f32).collect();
+        let y: Vec<f32> = x
+            .chunks(NUM_COLS)
+            .map(|row| row[0] * 2.0 - row[1] + 0.5 * row[2])
+            .collect();
+        (x, y)
+    }
+
+    /// Train on the given rows of the dataset, returning predictions for the full dataset.
+    fn train(rows: &[usize]) -> Vec<f32> {
+        let (x, y) = dataset();
+        let x_shard: Vec<f32> = rows
+            .iter()
+            .flat_map(|row| x[row * NUM_COLS..(row + 1) * NUM_COLS].to_vec())
+            .collect();
+        let y_shard: Vec<f32> = rows.iter().map(|row| y[*row]).collect();
+        let mut dtrain = DMatrix::from_dense(&x_shard, rows.len()).unwrap();
+        dtrain.set_labels(&y_shard).unwrap();
+
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .max_depth(3)
+            .tree_method(tree::TreeMethod::Hist)
+            .build()
+            .unwrap();
+        let learning_params = learning::LearningTaskParametersBuilder::default()
+            .objective(learning::Objective::RegLinear)
+            .base_score(0.5)
+            .build()
+            .unwrap();
+        let booster_params = parameters::BoosterParametersBuilder::default()
+            .booster_type(parameters::BoosterType::Tree(tree_params))
+            .learning_params(learning_params)
+            .verbose(false)
+            .build()
+            .unwrap();
+        let training_params = parameters::TrainingParametersBuilder::default()
+            .dtrain(&dtrain)
+            .booster_params(booster_params)
+            .boost_rounds(10)
+            .build()
+            .unwrap();
+        let booster = Booster::train(&training_params).unwrap();
+        booster.predict(&DMatrix::from_dense(&x, NUM_ROWS).unwrap()).unwrap()
+    }
+
+    /// Entry point of worker processes started by `distributed_training`.
+    fn run_worker(worker_args: &str, output: &Path) {
+        let communicator = init(worker_args).unwrap();
+        assert!(is_distributed());
+        let (rank, world_size) = (rank(), world_size());
+
+        let mut ranks = [rank as u32];
+        allreduce(&mut ranks, ReduceOp::Sum).unwrap();
+        assert_eq!(ranks[0] as usize, (0..world_size).sum::<usize>());
+        let mut root_rank = [rank as u64];
+        broadcast(&mut root_rank, 0).unwrap();
+        assert_eq!(root_rank[0], 0);
+
+        let rows: Vec<usize> = (rank..NUM_ROWS).step_by(world_size).collect();
+        let preds = train(&rows);
+        let preds: Vec<String> = preds.iter().map(|pred| pred.to_string()).collect();
+        std::fs::write(output.join(format!("worker-{}.txt", rank)), preds.join("\n")).unwrap();
+        drop(communicator);
+    }
+
+    #[test]
+    fn distributed_training() {
+        if let Ok(worker_args) = std::env::var(WORKER_ARGS_VAR) {
+            let output = std::env::var(WORKER_OUTPUT_VAR).unwrap();
+            run_worker(&worker_args, Path::new(&output));
+            return;
+        }
+
+        const NUM_WORKERS: u32 = 3;
+        let config = TrackerConfigBuilder::default()
+            .n_workers(NUM_WORKERS)
+            .timeout(Some(120))
+            .build()
+            .unwrap();
+        let tracker = Tracker::new(&config).unwrap();
+        tracker.start().unwrap();
+        let worker_args = tracker.worker_args().unwrap();
+
+        // re-run this test in separate processes acting as workers
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let exe = std::env::current_exe().unwrap();
+        let workers: Vec<_> = (0..NUM_WORKERS)
+            .map(|_| {
+                Command::new(&exe)
+                    .args(["collective::tests::distributed_training", "--exact", "--nocapture"])
+                    .env(WORKER_ARGS_VAR, &worker_args)
+                    .env(WORKER_OUTPUT_VAR, tmp_dir.path())
+                    .spawn()
+                    .unwrap()
+            })
+            .collect();
+        for mut worker in workers {
+            assert!(worker.wait().unwrap().success());
+        }
+        tracker.wait(Some(Duration::from_secs(60))).unwrap();
+
+        let expected = train(&(0..NUM_ROWS).collect::<Vec<_>>());
+        for rank in 0..NUM_WORKERS {
+            let preds = std::fs::read_to_string(tmp_dir.path().join(format!("worker-{}.txt", rank))).unwrap();
+            let preds: Vec<f32> = preds.lines().map(|pred| pred.parse().unwrap()).collect();
+            assert_eq!(preds.len(), expected.len());
+            for (pred, expected) in preds.iter().zip(&expected) {
+                assert!((pred - expected).abs() < 1e-4, "{} != {}", pred, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! Conformal prediction intervals for regression models.
+//!
+//! Conformal prediction turns the predictions of any regression model into intervals that contain the true label
+//! with a requested probability (the coverage), assuming only that calibration and test rows are exchangeable. The
+//! model is calibrated on held-out data that wasn't used for training:
+//!
+//! * [`SplitConformal`](struct.SplitConformal.html) widens point predictions by a quantile of the absolute residuals
+//!   on the calibration data, giving intervals of the same width for every row.
+//! * [`ConformalQuantile`](struct.ConformalQuantile.html) implements conformalized quantile regression
+//!   ([Romano et al., 2019](https://arxiv.org/abs/1905.03222)): it corrects the lower and upper quantiles predicted by
+//!   a model trained with the [`RegQuantileError`](../parameters/learning/enum.Objective.html#variant.RegQuantileError)
+//!   objective, giving intervals that adapt to each row.
+//!
+//! Since all calibration scores are kept, intervals can be computed at any coverage without recalibrating.
+//!
+//! ```no_run
+//! use xgb::parameters::learning::{LearningTaskParametersBuilder, Objective};
+//! use xgb::parameters::{BoosterParametersBuilder, TrainingParametersBuilder};
+//! use xgb::conformal::ConformalQuantile;
+//! use xgb::{Booster, DMatrix};
+//!
+//! let dtrain = DMatrix::load("train.libsvm?format=libsvm").unwrap();
+//! let dcalib = DMatrix::load("calibration.libsvm?format=libsvm").unwrap();
+//! let dtest = DMatrix::load("test.libsvm?format=libsvm").unwrap();
+//!
+//! let learning_params = LearningTaskParametersBuilder::default()
+//!     .objective(Objective::RegQuantileError)
+//!     .quantile_alpha(vec![0.05, 0.1, 0.5, 0.9, 0.95])
+//!     .build()
+//!     .unwrap();
+//! let booster_params = BoosterParametersBuilder::default()
+//!     .learning_params(learning_params)
+//!     .build()
+//!     .unwrap();
+//! let params = TrainingParametersBuilder::default()
+//!     .dtrain(&dtrain)
+//!     .booster_params(booster_params)
+//!     .build()
+//!     .unwrap();
+//! let booster = Booster::train(&params).unwrap();
+//!
+//! let conformal = ConformalQuantile::calibrate(booster, &dcalib).unwrap();
+//! // 90% intervals from the 5% and 95% quantiles
+//! let intervals = conformal.predict_intervals(&dtest, 0.9).unwrap();
+//! for (lower, upper) in intervals.lower.iter().zip(&intervals.upper) {
+//!     println!("[{}, {}]", lower, upper);
+//! }
+//! ```
+use serde_json::Value;
+
+use crate::{Booster, DMatrix, XGBError, XGBResult};
+
+/// Lower and upper bounds of prediction intervals, one per row.
+///
+/// Bounds are infinite when there are too few calibration rows for the requested coverage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Intervals {
+    /// Lower bound of each row.
+    pub lower: Vec<f32>,
+    /// Upper bound of each row.
+    pub upper: Vec<f32>,
+    /// Requested probability of the true label being within the bounds.
+    pub coverage: f64,
+}
+
+impl Intervals {
+    /// Get the width of each interval.
+    pub fn widths(&self) -> Vec<f32> {
+        self.lower
+            .iter()
+            .zip(&self.upper)
+            .map(|(lower, upper)| upper - lower)
+            .collect()
+    }
+
+    /// Get the fraction of `labels` within their interval, to check the coverage on test data.
+    pub fn empirical_coverage(&self, labels: &[f32]) -> XGBResult<f64> {
+        if labels.len() != self.lower.len() {
+            return Err(XGBError::new(format!(
+                "Number of labels ({}) does not match number of intervals ({})",
+                labels.len(),
+                self.lower.len()
+            )));
+        }
+        let covered = (0..labels.len())
+            .filter(|i| self.lower[*i] <= labels[*i] && labels[*i] <= self.upper[*i])
+            .count();
+        Ok(covered as f64 / labels.len().max(1) as f64)
+    }
+}
+
+/// Split conformal prediction around the point predictions of a regression booster.
+pub struct SplitConformal {
+    booster: Booster,
+    scores: Vec<f32>,
+}
+
+impl SplitConformal {
+    /// Calibrate `booster` on the labels of `dcalib`, which should be held out from training.
+    pub fn calibrate(booster: Booster, dcalib: &DMatrix) -> XGBResult<Self> {
+        let predictions = booster.predict(dcalib)?;
+        let labels = dcalib.get_labels()?;
+        check_num_labels(predictions.len(), labels)?;
+        let mut scores: Vec<f32> = predictions
+            .iter()
+            .zip(labels)
+            .map(|(prediction, label)| (label - prediction).abs())
+            .collect();
+        scores.sort_by(f32::total_cmp);
+        Ok(SplitConformal { booster, scores })
+    }
+
+    /// Predict intervals for each row of `dmat` containing the true label with probability `coverage`.
+    pub fn predict_intervals(&self, dmat: &DMatrix, coverage: f64) -> XGBResult<Intervals> {
+        let margin = conformal_quantile(&self.scores, coverage)?;
+        let predictions = self.booster.predict(dmat)?;
+        Ok(Intervals {
+            lower: predictions.iter().map(|p| p - margin).collect(),
+            upper: predictions.iter().map(|p| p + margin).collect(),
+            coverage,
+        })
+    }
+
+    /// Get the sorted absolute residuals on the calibration data.
+    pub fn scores(&self) -> &[f32] {
+        &self.scores
+    }
+
+    /// Get the underlying booster.
+    pub fn booster(&self) -> &Booster {
+        &self.booster
+    }
+
+    /// Take the underlying booster.
+    pub fn into_booster(self) -> Booster {
+        self.booster
+    }
+}
+
+/// Conformalized quantile regression on a booster trained with the
+/// [`RegQuantileError`](../parameters/learning/enum.Objective.html#variant.RegQuantileError) objective.
+///
+/// Intervals at coverage `c` use the predicted `(1 - c) / 2` and `(1 + c) / 2` quantiles, which must both be among
+/// the `quantile_alpha` the booster was trained with.
+pub struct ConformalQuantile {
+    booster: Booster,
+    quantile_alpha: Vec<f32>,
+    predictions: Vec<f32>,
+    labels: Vec<f32>,
+}
+
+impl ConformalQuantile {
+    /// Calibrate `booster` on the labels of `dcalib`, which should be held out from training.
+    ///
+    /// The quantiles predicted are read from the `quantile_alpha` in the booster's configuration.
+    pub fn calibrate(booster: Booster, dcalib: &DMatrix) -> XGBResult<Self> {
+        let quantile_alpha = trained_quantile_alpha(&booster.save_config()?)?;
+        let (predictions, (num_rows, num_quantiles)) = booster.predict_multi_output(dcalib)?;
+        if num_quantiles != quantile_alpha.len() {
+            return Err(XGBError::new(format!(
+                "Booster predicts {} quantiles, but its config has {} quantile_alpha",
+                num_quantiles,
+                quantile_alpha.len()
+            )));
+        }
+        let labels = dcalib.get_labels()?;
+        check_num_labels(num_rows, labels)?;
+        Ok(ConformalQuantile {
+            booster,
+            quantile_alpha,
+            predictions,
+            labels: labels.to_vec(),
+        })
+    }
+
+    /// Predict intervals for each row of `dmat` containing the true label with probability `coverage`.
+    pub fn predict_intervals(&self, dmat: &DMatrix, coverage: f64) -> XGBResult<Intervals> {
+        let (lower, upper) = self.quantile_columns(coverage)?;
+        let num_quantiles = self.quantile_alpha.len();
+
+        // how far the true label is outside the predicted quantiles, negative if inside
+        let mut scores: Vec<f32> = self
+            .predictions
+            .chunks(num_quantiles)
+            .zip(&self.labels)
+            .map(|(row, label)| (row[lower] - label).max(label - row[upper]))
+            .collect();
+        scores.sort_by(f32::total_cmp);
+        let correction = conformal_quantile(&scores, coverage)?;
+
+        let (predictions, _) = self.booster.predict_multi_output(dmat)?;
+        let rows = predictions.chunks(num_quantiles);
+        Ok(Intervals {
+            lower: rows.clone().map(|row| row[lower] - correction).collect(),
+            upper: rows.map(|row| row[upper] + correction).collect(),
+            coverage,
+        })
+    }
+
+    /// Columns of the lower and upper quantiles for `coverage`.
+    fn quantile_columns(&self, coverage: f64) -> XGBResult<(usize, usize)> {
+        check_coverage(coverage)?;
+        let column = |alpha: f64| {
+            self.quantile_alpha
+                .iter()
+                .position(|a| (*a as f64 - alpha).abs() < 1e-6)
+                .ok_or_else(|| {
+                    XGBError::new(format!(
+                        "Coverage {} needs quantile {}, which isn't in quantile_alpha {:?}",
+                        coverage, alpha, self.quantile_alpha
+                    ))
+                })
+        };
+        Ok((column((1.0 - coverage) / 2.0)?, column((1.0 + coverage) / 2.0)?))
+    }
+
+    /// Get the quantiles predicted by the booster.
+    pub fn quantile_alpha(&self) -> &[f32] {
+        &self.quantile_alpha
+    }
+
+    /// Get the underlying booster.
+    pub fn booster(&self) -> &Booster {
+        &self.booster
+    }
+
+    /// Take the underlying booster.
+    pub fn into_booster(self) -> Booster {
+        self.booster
+    }
+}
+
+/// Get the `quantile_alpha` a booster was trained with, from its JSON configuration.
+fn trained_quantile_alpha(config: &str) -> XGBResult<Vec<f32>> {
+    let config: Value =
+        serde_json::from_str(config).map_err(|err| XGBError::new(format!("Invalid booster config: {}", err)))?;
+    let objective = config.pointer("/learner/objective/name").and_then(Value::as_str);
+    if objective != Some("reg:quantileerror") {
+        return Err(XGBError::new(format!(
+            "Expected a booster trained with the reg:quantileerror objective, got {}",
+            objective.unwrap_or_default()
+        )));
+    }
+    let param = config
+        .pointer("/learner/objective/quantile_loss_param/quantile_alpha")
+        .and_then(Value::as_str)
+        .ok_or_else(|| XGBError::new("Booster config is missing quantile_alpha"))?;
+    let quantile_alpha = param
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .map(|s| s.trim().parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|alphas| alphas.iter().all(|alpha| *alpha > 0.0 && *alpha < 1.0))
+        .ok_or_else(|| XGBError::new(format!("Expected quantile_alpha in (0, 1), got '{}'", param)))?;
+    Ok(quantile_alpha)
+}
+
+fn check_num_labels(num_rows: usize, labels: &[f32]) -> XGBResult<()> {
+    if labels.len() != num_rows {
+        return Err(XGBError::new(format!(
+            "Calibration data has {} labels for {} rows",
+            labels.len(),
+            num_rows
+        )));
+    }
+    if num_rows == 0 {
+        return Err(XGBError::new("Can't calibrate without data"));
+    }
+    Ok(())
+}
+
+fn check_coverage(coverage: f64) -> XGBResult<()> {
+    if coverage > 0.0 && coverage < 1.0 {
+        Ok(())
+    } else {
+        Err(XGBError::new(format!("Coverage must be in (0, 1), got {}", coverage)))
+    }
+}
+
+/// The `ceil((n + 1) * coverage)`-th smallest of `n` sorted scores, which bounds the score of a new exchangeable row
+/// with probability at least `coverage`. Infinite when that's more than `n`.
+fn conformal_quantile(sorted_scores: &[f32], coverage: f64) -> XGBResult<f32> {
+    check_coverage(coverage)?;
+    let n = sorted_scores.len();
+    // avoid rounding up exact products, e.g. 0.9 * 10 = 9.000000000000002
+    let rank = ((n + 1) as f64 * coverage - 1e-9).ceil() as usize;
+    Ok(if rank > n { f32::INFINITY } else { sorted_scores[rank.max(1) - 1] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::learning::{LearningTaskParametersBuilder, Objective};
+    use crate::testing;
+
+    #[test]
+    fn quantile_rank() {
+        let scores: Vec<f32> = (1..=9).map(|i| i as f32).collect();
+        assert_eq!(conformal_quantile(&scores, 0.5).unwrap(), 5.0);
+        assert_eq!(conformal_quantile(&scores, 0.9).unwrap(), 9.0);
+        assert_eq!(conformal_quantile(&scores, 0.91).unwrap(), f32::INFINITY);
+        assert_eq!(conformal_quantile(&scores, 0.01).unwrap(), 1.0);
+        assert!(conformal_quantile(&scores, 1.0).is_err());
+        assert!(conformal_quantile(&scores, 0.0).is_err());
+        assert!(conformal_quantile(&scores, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn intervals() {
+        let intervals = Intervals {
+            lower: vec![0.0, 1.0, -1.0],
+            upper: vec![1.0, 3.0, f32::INFINITY],
+            coverage: 0.9,
+        };
+        assert_eq!(intervals.widths(), vec![1.0, 2.0, f32::INFINITY]);
+        assert_eq!(intervals.empirical_coverage(&[0.5, 0.0, 100.0]).unwrap(), 2.0 / 3.0);
+        assert!(intervals.empirical_coverage(&[0.5]).is_err());
+    }
+
+    #[test]
+    fn config_quantile_alpha() {
+        let config = |objective: &str, alpha: &str| {
+            format!(
+                r#"{{"learner": {{"objective": {{"name": "{}", "quantile_loss_param": {{"quantile_alpha": "{}"}}}}}}}}"#,
+                objective, alpha
+            )
+        };
+        let alphas = trained_quantile_alpha(&config("reg:quantileerror", "[5E-2,5E-1,9.5E-1]")).unwrap();
+        assert_eq!(alphas, vec![0.05, 0.5, 0.95]);
+        assert_eq!(
+            trained_quantile_alpha(&config("reg:quantileerror", "0.5")).unwrap(),
+            vec![0.5]
+        );
+        assert!(trained_quantile_alpha(&config("reg:quantileerror", "[0.5,1.5]")).is_err());
+        assert!(trained_quantile_alpha(&config("reg:squarederror", "0.5")).is_err());
+        assert!(trained_quantile_alpha(r#"{"learner": {"objective": {"name": "reg:quantileerror"}}}"#).is_err());
+    }
+
+    fn data(num_rows: usize, offset: usize) -> DMatrix {
+        let x: Vec<f32> = (0..num_rows).map(|i| ((i + offset) % 20) as f32).collect();
+        // noise growing with x, deterministic but uncorrelated with it
+        let labels: Vec<f32> = (0..num_rows)
+            .map(|i| x[i] + x[i] * ((((i + offset) * 37) % 11) as f32 / 10.0 - 0.5))
+            .collect();
+        testing::dmatrix(&x, &labels)
+    }
+
+    #[test]
+    fn split_conformal() {
+        let booster = testing::train(&data(400, 0), Objective::RegLinear, 20);
+        let dcalib = data(400, 400);
+        let dtest = data(1000, 800);
+
+        let conformal = SplitConformal::calibrate(booster, &dcalib).unwrap();
+        assert_eq!(conformal.scores().len(), 400);
+        assert!(conformal.scores().windows(2).all(|w| w[0] <= w[1]));
+        let intervals = conformal.predict_intervals(&dtest, 0.8).unwrap();
+        assert_eq!(intervals.lower.len(), 1000);
+        let widths = intervals.widths();
+        assert!(widths.iter().all(|w| (w - widths[0]).abs() < 1e-3));
+        let coverage = intervals.empirical_coverage(dtest.get_labels().unwrap()).unwrap();
+        assert!(coverage >= 0.75, "coverage = {}", coverage);
+
+        let wider = conformal.predict_intervals(&dtest, 0.95).unwrap();
+        assert!(wider.widths()[0] >= widths[0]);
+    }
+
+    #[test]
+    fn conformal_quantile_regression() {
+        let quantile_alpha = vec![0.05, 0.1, 0.5, 0.9, 0.95];
+        let learning_params = LearningTaskParametersBuilder::default()
+            .objective(Objective::RegQuantileError)
+            .quantile_alpha(quantile_alpha.clone())
+            .build()
+            .unwrap();
+        let booster = testing::train_with(&data(400, 0), learning_params, 20);
+        let dcalib = data(400, 400);
+        let dtest = data(1000, 800);
+
+        let conformal = ConformalQuantile::calibrate(booster, &dcalib).unwrap();
+        assert_eq!(conformal.quantile_alpha(), quantile_alpha);
+        let intervals = conformal.predict_intervals(&dtest, 0.8).unwrap();
+        assert!(intervals.lower.iter().zip(&intervals.upper).all(|(l, u)| l <= u));
+        let coverage = intervals.empirical_coverage(dtest.get_labels().unwrap()).unwrap();
+        assert!(coverage >= 0.75, "coverage = {}", coverage);
+        // intervals adapt to the noise, which grows with the feature
+        let widths = intervals.widths();
+        assert!(widths.iter().cloned().fold(f32::MIN, f32::max) > widths.iter().cloned().fold(f32::MAX, f32::min));
+
+        assert!(conformal.predict_intervals(&dtest, 0.9).is_ok());
+        assert!(conformal.predict_intervals(&dtest, 0.5).is_err());
+
+        let booster = testing::train(&data(400, 0), Objective::RegLinear, 20);
+        let result = ConformalQuantile::calibrate(booster, &dcalib);
+        assert!(result.is_err_and(|err| {
+            err.to_string()
+                .contains("reg:quantileerror objective, got reg:squarederror")
+        }));
+    }
+}
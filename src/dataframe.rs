@@ -0,0 +1,225 @@
+//! Conversions between [Polars](https://docs.rs/polars) data frames and XGBoost data.
+//!
+//! Only available when the `polars` feature is enabled.
+use std::borrow::Cow;
+
+use polars::prelude::{Column, DataFrame, DataType, NamedFrom, PolarsError, Series};
+
+use crate::{DMatrix, FeatureType, Prediction, XGBError, XGBResult};
+
+impl From<PolarsError> for XGBError {
+    fn from(err: PolarsError) -> Self {
+        XGBError::new(err.to_string())
+    }
+}
+
+/// Convert a feature column to `f32` values, with nulls as `NaN`.
+///
+/// Enum columns are converted to their category codes, the index of each value in the enum's categories. Categorical
+/// columns are rejected, as their codes depend on which strings were seen first by a (possibly global) mapping, so
+/// aren't dense and differ between data frames.
+fn feature_values(series: &Series) -> XGBResult<(Vec<f32>, FeatureType)> {
+    let dtype = series.dtype();
+    let (physical, feature_type) = if dtype.is_enum() {
+        (series.to_physical_repr(), FeatureType::Categorical)
+    } else if dtype.is_categorical() {
+        return Err(XGBError::new(format!(
+            "Categorical column '{}' has no fixed category codes, cast it to an Enum with fixed categories instead",
+            series.name()
+        )));
+    } else if dtype.is_primitive_numeric() || dtype.is_bool() {
+        (Cow::Borrowed(series), FeatureType::Quantitative)
+    } else {
+        return Err(XGBError::new(format!(
+            "Unsupported data type {} for column '{}', only numeric, boolean and enum columns are supported",
+            dtype,
+            series.name()
+        )));
+    };
+    let values = physical.cast(&DataType::Float32)?;
+    let values = values.f32()?.into_iter().map(|v| v.unwrap_or(f32::NAN)).collect();
+    Ok((values, feature_type))
+}
+
+/// Convert a label or weight column to `f32` values, which must not contain nulls.
+fn info_values(column: &Column) -> XGBResult<Vec<f32>> {
+    let series = column.as_materialized_series();
+    if series.null_count() > 0 {
+        return Err(XGBError::new(format!(
+            "Column '{}' contains {} null values",
+            series.name(),
+            series.null_count()
+        )));
+    }
+    let values = series.cast(&DataType::Float32)?;
+    Ok(values.f32()?.into_no_null_iter().collect())
+}
+
+impl DMatrix {
+    /// Create a new `DMatrix` from a Polars `DataFrame`, where each row is an instance.
+    ///
+    /// Columns named by `label_column` and `weight_column` (if given) are used as labels and instance weights, all
+    /// other columns are used as features. Feature columns can be numeric, boolean or enum columns. Nulls are treated
+    /// as missing values, and enum columns become categorical features holding the index of each value in the enum's
+    /// categories, so data frames with the same enum type encode categories the same way. Feature names and types
+    /// are set from the columns.
+    ///
+    /// Returns an error for `Categorical` columns, as their category codes aren't fixed, cast them to an `Enum` with
+    /// the categories seen during training first.
+    ///
+    /// ```no_run
+    /// use polars::prelude::*;
+    /// use xgb::DMatrix;
+    ///
+    /// let df = df!(
+    ///     "age" => [Some(31.0f32), None, Some(45.0)],
+    ///     "visits" => [3i64, 1, 7],
+    ///     "churned" => [0.0f32, 1.0, 0.0],
+    /// ).unwrap();
+    /// let dmat = DMatrix::from_dataframe(&df, Some("churned"), None).unwrap();
+    /// assert_eq!(dmat.shape(), (3, 2));
+    /// ```
+    pub fn from_dataframe(df: &DataFrame, label_column: Option<&str>, weight_column: Option<&str>) -> XGBResult<Self> {
+        let mut names = Vec::new();
+        let mut columns = Vec::new();
+        let mut feature_types = Vec::new();
+        for column in df.get_columns() {
+            let name = column.name().as_str();
+            if Some(name) == label_column || Some(name) == weight_column {
+                continue;
+            }
+            let (values, feature_type) = feature_values(column.as_materialized_series())?;
+            names.push(name);
+            columns.push(values);
+            feature_types.push(feature_type);
+        }
+
+        let columns: Vec<&[f32]> = columns.iter().map(Vec::as_slice).collect();
+        let mut dmat = DMatrix::from_columns(&columns)?;
+
+        dmat.set_feature_names(&names)?;
+        if feature_types.contains(&FeatureType::Categorical) {
+            dmat.set_feature_types(&feature_types)?;
+        }
+        if let Some(label_column) = label_column {
+            dmat.set_labels(&info_values(df.column(label_column)?)?)?;
+        }
+        if let Some(weight_column) = weight_column {
+            dmat.set_weights(&info_values(df.column(weight_column)?)?)?;
+        }
+        Ok(dmat)
+    }
+}
+
+impl Prediction {
+    /// Convert a prediction with a single value per row into a `Series` with given name.
+    ///
+    /// Returns an error if there are multiple values per row (e.g. multi-class probabilities), use
+    /// [`to_dataframe`](#method.to_dataframe) for those.
+    pub fn to_series(&self, name: &str) -> XGBResult<Series> {
+        if self.data().len() != self.num_rows() {
+            return Err(XGBError::new(format!(
+                "Prediction with shape {:?} has multiple values per row, can't convert into a Series",
+                self.shape()
+            )));
+        }
+        Ok(Series::new(name.into(), self.data()))
+    }
+
+    /// Convert a prediction into a `DataFrame` with one row per predicted row.
+    ///
+    /// A single value per row becomes a column named `prefix`, multiple values per row become columns named
+    /// `{prefix}_0`, `{prefix}_1`, etc. (flattened if there are more than 2 dimensions).
+    pub fn to_dataframe(&self, prefix: &str) -> XGBResult<DataFrame> {
+        let num_rows = self.num_rows();
+        let row_len = self.data().len().checked_div(num_rows).unwrap_or(0);
+        let columns = if row_len == 1 {
+            vec![Column::from(self.to_series(prefix)?)]
+        } else {
+            (0..row_len)
+                .map(|i| {
+                    let values: Vec<f32> = self.data().iter().skip(i).step_by(row_len).copied().collect();
+                    Column::new(format!("{}_{}", prefix, i).into(), values)
+                })
+                .collect()
+        };
+        Ok(DataFrame::new(columns)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::{Categories, FrozenCategories, df};
+
+    #[test]
+    fn feature_column_values() {
+        let series = Series::new("x".into(), [Some(1i32), None, Some(3)]);
+        let (values, feature_type) = feature_values(&series).unwrap();
+        assert_eq!(values[0], 1.0);
+        assert!(values[1].is_nan());
+        assert_eq!(feature_type, FeatureType::Quantitative);
+
+        let series = Series::new("colour".into(), ["red", "blue", "red"])
+            .cast(&DataType::from_categories(Categories::global()))
+            .unwrap();
+        assert!(feature_values(&series).is_err());
+
+        assert!(feature_values(&Series::new("s".into(), ["a"])).is_err());
+    }
+
+    #[test]
+    fn enum_codes() {
+        // train and test frames built separately from the same enum encode categories the same way
+        let colours = DataType::from_frozen_categories(FrozenCategories::new(["blue", "green", "red"]).unwrap());
+        let train = Series::new("colour".into(), [Some("red"), Some("blue"), None, Some("red")])
+            .cast(&colours)
+            .unwrap();
+        let test = Series::new("colour".into(), ["green", "blue"]).cast(&colours).unwrap();
+
+        let (values, feature_type) = feature_values(&train).unwrap();
+        assert_eq!(values[..2], [2.0, 0.0]);
+        assert!(values[2].is_nan());
+        assert_eq!(values[3], 2.0);
+        assert_eq!(feature_type, FeatureType::Categorical);
+        assert_eq!(feature_values(&test).unwrap().0, vec![1.0, 0.0]);
+
+        let df = DataFrame::new(vec![Column::from(test)]).unwrap();
+        let dmat = DMatrix::from_dataframe(&df, None, None).unwrap();
+        assert_eq!(dmat.get_feature_types().unwrap(), vec![FeatureType::Categorical]);
+        assert_eq!(dmat.get_data_as_csr().unwrap().2, vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn prediction_frames() {
+        let prediction = Prediction::new(vec![0.1, 0.9, 0.7, 0.3], vec![2, 2]);
+        assert!(prediction.to_series("p").is_err());
+        let df = prediction.to_dataframe("p").unwrap();
+        assert_eq!(df.shape(), (2, 2));
+        let p_1: Vec<f32> = df.column("p_1").unwrap().f32().unwrap().into_no_null_iter().collect();
+        assert_eq!(p_1, vec![0.9, 0.3]);
+
+        let prediction = Prediction::new(vec![1.0, 2.0], vec![2]);
+        assert_eq!(prediction.to_series("p").unwrap().len(), 2);
+        assert_eq!(prediction.to_dataframe("p").unwrap().get_column_names(), vec!["p"]);
+    }
+
+    #[test]
+    fn from_dataframe() {
+        let df = df!(
+            "x" => [Some(1.0f64), None, Some(3.0), Some(4.0)],
+            "flag" => [true, false, true, false],
+            "y" => [0.0f32, 1.0, 0.0, 1.0],
+            "w" => [1u32, 2, 1, 2],
+        )
+        .unwrap();
+
+        let dmat = DMatrix::from_dataframe(&df, Some("y"), Some("w")).unwrap();
+        assert_eq!(dmat.shape(), (4, 2));
+        assert_eq!(dmat.get_feature_names().unwrap(), vec!["x", "flag"]);
+        assert_eq!(dmat.get_labels().unwrap(), &[0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(dmat.get_weights().unwrap(), &[1.0, 2.0, 1.0, 2.0]);
+
+        assert!(DMatrix::from_dataframe(&df, Some("missing"), None).is_err());
+    }
+}
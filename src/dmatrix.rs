@@ -1,13 +1,16 @@
 use libc::{c_float, c_uint};
 use std::{ffi, path::Path, ptr, slice};
 
-use super::{XGBError, XGBResult};
+use super::{FeatureType, XGBError, XGBResult};
+use crate::util::json_float;
 
 static KEY_GROUP_PTR: &str = "group_ptr";
 static KEY_GROUP: &str = "group";
 static KEY_LABEL: &str = "label";
 static KEY_WEIGHT: &str = "weight";
 static KEY_BASE_MARGIN: &str = "base_margin";
+static KEY_FEATURE_NAME: &str = "feature_name";
+static KEY_FEATURE_TYPE: &str = "feature_type";
 
 /// Creates a JSON-encoded array interface string for f32 data.
 fn make_array_interface_f32(data: &[f32]) -> String {
@@ -19,17 +22,24 @@ fn make_array_interface_f32(data: &[f32]) -> String {
     )
 }
 
-/// Creates a JSON-encoded array interface string for u64 data.
-///
-/// This function is used for CSR/CSC indices and indptr arrays.
-/// XGBoost's C API expects uint64_t (bst_ulong) for these arrays,
-/// so we use fixed-width u64 to ensure cross-platform compatibility.
-fn make_array_interface_u64(data: &[u64]) -> String {
+/// Creates a JSON-encoded array interface string for a row-major 2-D f32 array.
+fn make_array_interface_f32_2d(data: &[f32], num_rows: usize, num_cols: usize) -> String {
+    let ptr = data.as_ptr() as usize;
+    format!(
+        r#"{{"data":[{},false],"shape":[{},{}],"strides":null,"typestr":"<f4","version":3}}"#,
+        ptr, num_rows, num_cols
+    )
+}
+
+/// Creates a JSON-encoded array interface string for CSR/CSC indices and indptr arrays.
+fn make_array_interface_index<T: SparseIndex>(data: &[T]) -> String {
     let ptr = data.as_ptr() as usize;
     let len = data.len();
     format!(
-        r#"{{"data":[{},false],"shape":[{}],"strides":null,"typestr":"<u8","version":3}}"#,
-        ptr, len
+        r#"{{"data":[{},false],"shape":[{}],"strides":null,"typestr":"{}","version":3}}"#,
+        ptr,
+        len,
+        T::TYPESTR
     )
 }
 
@@ -43,6 +53,438 @@ fn make_array_interface_u32(data: &[u32]) -> String {
     )
 }
 
+/// Creates a JSON-encoded array interface string for a single column of a columnar matrix.
+///
+/// `mask`, if given, points to a validity bitmap of `len` bits in least significant bit order (as used by Arrow),
+/// where an unset bit marks a missing value.
+pub(crate) fn make_column_interface(ptr: usize, len: usize, typestr: &str, mask: Option<usize>) -> String {
+    let mask = match mask {
+        Some(mask) => format!(
+            r#","mask":{{"data":[{},true],"shape":[{}],"typestr":"|t1","version":3}}"#,
+            mask, len
+        ),
+        None => String::new(),
+    };
+    format!(
+        r#"{{"data":[{},true],"shape":[{}],"strides":null,"typestr":"{}","version":3{}}}"#,
+        ptr, len, typestr, mask
+    )
+}
+
+/// Values of a single feature column, see [`FeatureColumn`](struct.FeatureColumn.html).
+#[derive(Clone, Copy, Debug)]
+pub enum ColumnValues<'a> {
+    /// 32-bit floats, `NaN` values are missing.
+    F32(&'a [f32]),
+
+    /// 64-bit floats, `NaN` values are missing.
+    F64(&'a [f64]),
+
+    /// 32-bit signed integers.
+    I32(&'a [i32]),
+
+    /// 64-bit signed integers.
+    I64(&'a [i64]),
+
+    /// Unsigned bytes, e.g. category codes.
+    U8(&'a [u8]),
+
+    /// Booleans, read as 0 or 1.
+    Bool(&'a [bool]),
+}
+
+impl ColumnValues<'_> {
+    /// Number of values in the column.
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnValues::F32(values) => values.len(),
+            ColumnValues::F64(values) => values.len(),
+            ColumnValues::I32(values) => values.len(),
+            ColumnValues::I64(values) => values.len(),
+            ColumnValues::U8(values) => values.len(),
+            ColumnValues::Bool(values) => values.len(),
+        }
+    }
+
+    /// Check whether the column has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pointer to the values and their array interface type string.
+    fn array_data(&self) -> (usize, &'static str) {
+        match self {
+            ColumnValues::F32(values) => (values.as_ptr() as usize, "<f4"),
+            ColumnValues::F64(values) => (values.as_ptr() as usize, "<f8"),
+            ColumnValues::I32(values) => (values.as_ptr() as usize, "<i4"),
+            ColumnValues::I64(values) => (values.as_ptr() as usize, "<i8"),
+            ColumnValues::U8(values) => (values.as_ptr() as usize, "|u1"),
+            // bool is guaranteed to be a single byte holding 0 or 1
+            ColumnValues::Bool(values) => (values.as_ptr() as usize, "|u1"),
+        }
+    }
+}
+
+/// A borrowed feature column used to create a [`DMatrix`](struct.DMatrix.html) with
+/// [`from_typed_columns`](struct.DMatrix.html#method.from_typed_columns), with an optional null bitmap marking
+/// missing values.
+///
+/// ```
+/// use xgb::{ColumnValues, FeatureColumn};
+///
+/// let ages = [31.0f64, 0.0, 45.0];
+/// // second value is null: bits are in least significant bit order, with unset bits marking nulls
+/// let column = FeatureColumn::new(ColumnValues::F64(&ages)).with_validity(&[0b101]);
+/// let counts = FeatureColumn::from(&[3i64, 1, 7][..]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FeatureColumn<'a> {
+    values: ColumnValues<'a>,
+    validity: Option<&'a [u8]>,
+}
+
+impl<'a> FeatureColumn<'a> {
+    /// Create a column without nulls, where only `NaN` values (for float columns) are missing.
+    pub fn new(values: ColumnValues<'a>) -> Self {
+        FeatureColumn { values, validity: None }
+    }
+
+    /// Set a validity bitmap for this column, with one bit per value in least significant bit order (as used by
+    /// Arrow). Values whose bit is unset are treated as missing.
+    pub fn with_validity(mut self, validity: &'a [u8]) -> Self {
+        self.validity = Some(validity);
+        self
+    }
+
+    /// Get the values of this column.
+    pub fn values(&self) -> ColumnValues<'a> {
+        self.values
+    }
+
+    /// Get the validity bitmap of this column, if any.
+    pub fn validity(&self) -> Option<&'a [u8]> {
+        self.validity
+    }
+}
+
+macro_rules! impl_feature_column_from {
+    ($t:ty, $variant:ident) => {
+        impl<'a> From<&'a [$t]> for FeatureColumn<'a> {
+            fn from(values: &'a [$t]) -> Self {
+                FeatureColumn::new(ColumnValues::$variant(values))
+            }
+        }
+    };
+}
+
+impl_feature_column_from!(f32, F32);
+impl_feature_column_from!(f64, F64);
+impl_feature_column_from!(i32, I32);
+impl_feature_column_from!(i64, I64);
+impl_feature_column_from!(u8, U8);
+impl_feature_column_from!(bool, Bool);
+
+mod sealed {
+    pub trait DenseElement {}
+    pub trait SparseIndex {}
+}
+
+/// Element types which can be used to create a [`DMatrix`](struct.DMatrix.html) from dense data with
+/// [`from_dense_with`](struct.DMatrix.html#method.from_dense_with).
+///
+/// Implemented for `f32`, `f64`, `i32`, `i64` and `u8`.
+pub trait DenseElement: Copy + sealed::DenseElement {
+    /// Array interface type string of this type.
+    #[doc(hidden)]
+    const TYPESTR: &'static str;
+}
+
+macro_rules! impl_dense_element {
+    ($t:ty, $typestr:expr) => {
+        impl sealed::DenseElement for $t {}
+        impl DenseElement for $t {
+            const TYPESTR: &'static str = $typestr;
+        }
+    };
+}
+
+impl_dense_element!(f32, "<f4");
+impl_dense_element!(f64, "<f8");
+impl_dense_element!(i32, "<i4");
+impl_dense_element!(i64, "<i8");
+impl_dense_element!(u8, "|u1");
+
+/// Index types which can be used for the `indptr` and `indices` arrays of sparse matrices, see
+/// [`DMatrix::from_csr`](struct.DMatrix.html#method.from_csr).
+///
+/// Implemented for `u32`, `u64`, `usize`, `i32` and `i64`, so indices can be passed without first converting them.
+pub trait SparseIndex: Copy + sealed::SparseIndex {
+    /// Array interface type string of this type.
+    #[doc(hidden)]
+    const TYPESTR: &'static str;
+
+    /// Convert to `usize`, returning `None` for negative values.
+    #[doc(hidden)]
+    fn to_usize(self) -> Option<usize>;
+}
+
+macro_rules! impl_sparse_index {
+    ($t:ty, $typestr:expr) => {
+        impl sealed::SparseIndex for $t {}
+        impl SparseIndex for $t {
+            const TYPESTR: &'static str = $typestr;
+
+            fn to_usize(self) -> Option<usize> {
+                usize::try_from(self).ok()
+            }
+        }
+    };
+}
+
+impl_sparse_index!(u32, "<u4");
+impl_sparse_index!(u64, "<u8");
+impl_sparse_index!(i32, "<i4");
+impl_sparse_index!(i64, "<i8");
+#[cfg(target_pointer_width = "64")]
+impl_sparse_index!(usize, "<u8");
+#[cfg(target_pointer_width = "32")]
+impl_sparse_index!(usize, "<u4");
+
+/// Compressed sparse matrix formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Compressed {
+    Csr,
+    Csc,
+}
+
+impl Compressed {
+    /// Names of the (compressed, indexed) dimensions.
+    fn dims(self) -> (&'static str, &'static str) {
+        match self {
+            Compressed::Csr => ("row", "column"),
+            Compressed::Csc => ("column", "row"),
+        }
+    }
+
+    /// Check that `indptr` and `indices` describe a valid sparse matrix with `data_len` values, and at most
+    /// `num_indexed` rows (for CSC) or columns (for CSR) if given.
+    pub(crate) fn validate<P: SparseIndex, I: SparseIndex>(
+        self,
+        indptr: &[P],
+        indices: &[I],
+        data_len: usize,
+        num_indexed: Option<usize>,
+    ) -> XGBResult<()> {
+        let (compressed, indexed) = self.dims();
+        if indptr.is_empty() {
+            return Err(XGBError::new(format!(
+                "{:?} indptr must have at least one element (number of {}s + 1)",
+                self, compressed
+            )));
+        }
+        let mut prev = 0;
+        for (i, offset) in indptr.iter().enumerate() {
+            let offset = offset
+                .to_usize()
+                .ok_or_else(|| XGBError::new(format!("{:?} indptr[{}] is negative", self, i)))?;
+            if i == 0 && offset != 0 {
+                return Err(XGBError::new(format!(
+                    "{:?} indptr must start at 0, got {}",
+                    self, offset
+                )));
+            }
+            if offset < prev {
+                return Err(XGBError::new(format!(
+                    "{:?} indptr must be non-decreasing, but indptr[{}] = {} is less than indptr[{}] = {}",
+                    self,
+                    i,
+                    offset,
+                    i - 1,
+                    prev
+                )));
+            }
+            prev = offset;
+        }
+        if prev != data_len {
+            return Err(XGBError::new(format!(
+                "{:?} indptr ends at {}, but there are {} values",
+                self, prev, data_len
+            )));
+        }
+        for (i, index) in indices.iter().enumerate() {
+            let index = index
+                .to_usize()
+                .ok_or_else(|| XGBError::new(format!("{:?} indices[{}] is negative", self, i)))?;
+            if let Some(num_indexed) = num_indexed
+                && index >= num_indexed
+            {
+                return Err(XGBError::new(format!(
+                    "{:?} indices[{}] = {} is out of range for {} {}s",
+                    self, i, index, num_indexed, indexed
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Memory layout of dense data passed to [`DMatrix::from_dense_with`](struct.DMatrix.html#method.from_dense_with).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DenseLayout {
+    /// Row-major (C) order, with all values of a row stored next to each other.
+    RowMajor { num_rows: usize },
+
+    /// Column-major (Fortran) order, with all values of a column stored next to each other.
+    ColumnMajor { num_rows: usize },
+
+    /// Arbitrary layout given by the number of elements (not bytes) between consecutive rows and columns, e.g.
+    /// for a view of every other row of a larger row-major array.
+    Strided {
+        num_rows: usize,
+        num_cols: usize,
+        row_stride: usize,
+        col_stride: usize,
+    },
+}
+
+impl DenseLayout {
+    /// Get the (rows, columns, row stride, column stride) of `len` elements in this layout, checking that all
+    /// elements addressed by the layout are within bounds.
+    fn shape_and_strides(&self, len: usize) -> XGBResult<(usize, usize, usize, usize)> {
+        let split = |num_rows: usize| {
+            if num_rows == 0 || !len.is_multiple_of(num_rows) {
+                Err(XGBError::new(format!(
+                    "Data of length {} can't be split into {} rows",
+                    len, num_rows
+                )))
+            } else {
+                Ok((num_rows, len / num_rows))
+            }
+        };
+        let (num_rows, num_cols, row_stride, col_stride) = match *self {
+            DenseLayout::RowMajor { num_rows } => {
+                let (num_rows, num_cols) = split(num_rows)?;
+                (num_rows, num_cols, num_cols, 1)
+            }
+            DenseLayout::ColumnMajor { num_rows } => {
+                let (num_rows, num_cols) = split(num_rows)?;
+                (num_rows, num_cols, 1, num_rows)
+            }
+            DenseLayout::Strided {
+                num_rows,
+                num_cols,
+                row_stride,
+                col_stride,
+            } => (num_rows, num_cols, row_stride, col_stride),
+        };
+        if num_rows > 0 && num_cols > 0 {
+            let last = (num_rows - 1)
+                .checked_mul(row_stride)
+                .zip((num_cols - 1).checked_mul(col_stride))
+                .and_then(|(row_offset, col_offset)| row_offset.checked_add(col_offset));
+            match last {
+                Some(last) if last < len => {}
+                Some(last) => {
+                    return Err(XGBError::new(format!(
+                        "Layout {:?} addresses element {}, but data only has {} elements",
+                        self, last, len
+                    )));
+                }
+                None => {
+                    return Err(XGBError::new(format!(
+                        "Layout {:?} addresses elements beyond the range of usize",
+                        self
+                    )));
+                }
+            }
+        }
+        Ok((num_rows, num_cols, row_stride, col_stride))
+    }
+}
+
+/// Options used when creating a [`DMatrix`](struct.DMatrix.html) from dense data. Create using
+/// [`DenseConfigBuilder`](struct.DenseConfigBuilder.html).
+///
+/// ```
+/// use xgb::DenseConfigBuilder;
+///
+/// // treat -999 as missing, and use 4 threads
+/// let config = DenseConfigBuilder::default().missing(-999.0).nthread(4).build().unwrap();
+/// ```
+#[derive(Builder, Clone, Debug)]
+#[builder(default)]
+pub struct DenseConfig {
+    /// Value treated as missing. `NaN` values are always treated as missing.
+    ///
+    /// *default*: `NaN`
+    missing: f32,
+
+    /// Number of threads used to create the matrix, 0 uses all available threads.
+    ///
+    /// *default*: 0
+    nthread: u32,
+
+    /// How the data is split between workers in distributed or federated training. Only dense data can be split
+    /// by column, matrices created by other constructors are always split by row.
+    ///
+    /// *default*: [`DataSplitMode::Row`](enum.DataSplitMode.html#variant.Row)
+    data_split_mode: DataSplitMode,
+}
+
+impl Default for DenseConfig {
+    fn default() -> Self {
+        DenseConfig {
+            missing: f32::NAN,
+            nthread: 0,
+            data_split_mode: DataSplitMode::Row,
+        }
+    }
+}
+
+impl DenseConfig {
+    /// Get the value treated as missing.
+    pub fn missing(&self) -> f32 {
+        self.missing
+    }
+
+    /// Get the number of threads used to create the matrix.
+    pub fn nthread(&self) -> u32 {
+        self.nthread
+    }
+
+    /// Get how the data is split between workers.
+    pub fn data_split_mode(&self) -> DataSplitMode {
+        self.data_split_mode
+    }
+
+    fn to_json(&self) -> String {
+        match self.data_split_mode {
+            DataSplitMode::Row => format!(
+                r#"{{"missing":{},"nthread":{}}}"#,
+                json_float(self.missing),
+                self.nthread
+            ),
+            DataSplitMode::Column => format!(
+                r#"{{"missing":{},"nthread":{},"data_split_mode":1}}"#,
+                json_float(self.missing),
+                self.nthread
+            ),
+        }
+    }
+}
+
+/// How training data is split between the workers of a distributed or federated training job.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataSplitMode {
+    /// Each worker holds all features for a subset of rows (horizontal split).
+    #[default]
+    Row,
+
+    /// Each worker holds a subset of features for all rows (vertical split). Feature indices are local to each
+    /// worker.
+    Column,
+}
+
 /// Data matrix used throughout XGBoost for training/predicting [`Booster`](struct.Booster.html) models.
 ///
 /// It's used as a container for both features (i.e. a row for every instance), and an optional true label for that
@@ -154,76 +596,259 @@ impl DMatrix {
         DMatrix::new(handle)
     }
 
-    /// Create a new `DMatrix` from a sparse
-    /// [CSR](https://en.wikipedia.org/wiki/Sparse_matrix#Compressed_sparse_row_(CSR,_CRS_or_Yale_format)) matrix.
+    /// Create a new `DMatrix` from dense data of any [`DenseElement`](trait.DenseElement.html) type, stored in
+    /// given layout.
     ///
-    /// Uses standard CSR representation where the column indices for row _i_ are stored in
-    /// `indices[indptr[i]:indptr[i+1]]` and their corresponding values are stored in
-    /// `data[indptr[i]:indptr[i+1]`.
+    /// Data is read by XGBoost in its own type and layout, without first being converted into a row-major `f32`
+    /// buffer. Values equal to the config's `missing` value, as well as `NaN` values, are treated as missing.
     ///
-    /// If `num_cols` is set to None, number of columns will be inferred from given data.
+    /// ```
+    /// use xgb::{DMatrix, DenseConfigBuilder, DenseLayout};
     ///
-    /// Note: For small matrices (< 30000 non-zero elements), single-threaded creation is used
-    /// to avoid thread synchronization overhead. For larger matrices, multi-threaded creation
-    /// is used for better performance.
-    pub fn from_csr(indptr: &[u64], indices: &[u64], data: &[f32], num_cols: Option<usize>) -> XGBResult<Self> {
-        // Threshold below which single-threaded is faster due to thread overhead.
-        // Benchmarking shows crossover point is around 30k non-zeros on typical hardware.
-        const SINGLE_THREAD_THRESHOLD: usize = 30000;
+    /// // 2 rows and 3 columns stored in column-major order, using -999 for missing values
+    /// let data: &[i64] = &[1, 4,
+    ///                      2, -999,
+    ///                      3, 6];
+    /// let config = DenseConfigBuilder::default().missing(-999.0).build().unwrap();
+    /// let dmat = DMatrix::from_dense_with(data, DenseLayout::ColumnMajor { num_rows: 2 }, &config).unwrap();
+    /// assert_eq!(dmat.shape(), (2, 3));
+    /// ```
+    pub fn from_dense_with<T: DenseElement>(data: &[T], layout: DenseLayout, config: &DenseConfig) -> XGBResult<Self> {
+        let (num_rows, num_cols, row_stride, col_stride) = layout.shape_and_strides(data.len())?;
+        let item_size = std::mem::size_of::<T>();
+        let byte_stride = |stride: usize| {
+            stride
+                .checked_mul(item_size)
+                .ok_or_else(|| XGBError::new(format!("Stride {} of layout {:?} is too large", stride, layout)))
+        };
+        let array_interface = format!(
+            r#"{{"data":[{},true],"shape":[{},{}],"strides":[{},{}],"typestr":"{}","version":3}}"#,
+            data.as_ptr() as usize,
+            num_rows,
+            num_cols,
+            byte_stride(row_stride)?,
+            byte_stride(col_stride)?,
+            T::TYPESTR
+        );
+        DMatrix::from_dense_array_interface(&array_interface, config)
+    }
 
-        assert_eq!(indices.len(), data.len());
+    /// Create a new `DMatrix` from a JSON-encoded
+    /// [array interface](https://numpy.org/doc/stable/reference/arrays.interface.html) describing a 2-D dense array.
+    ///
+    /// The array interface may describe arbitrary (non-negative) strides, so both row-major and column-major data
+    /// can be passed without first being copied into a new buffer.
+    pub(crate) fn from_dense_array_interface(array_interface: &str, config: &DenseConfig) -> XGBResult<Self> {
         let mut handle = ptr::null_mut();
-        let num_cols = num_cols.unwrap_or(0) as xgboost_sys::bst_ulong;
-
-        let indptr_interface = make_array_interface_u64(indptr);
-        let indices_interface = make_array_interface_u64(indices);
-        let data_interface = make_array_interface_f32(data);
+        let data_cstr = ffi::CString::new(array_interface).unwrap();
+        let config = ffi::CString::new(config.to_json()).unwrap();
+        xgb_call!(xgboost_sys::XGDMatrixCreateFromDense(
+            data_cstr.as_ptr(),
+            config.as_ptr(),
+            &mut handle
+        ))?;
+        DMatrix::new(handle)
+    }
 
-        let indptr_cstr = ffi::CString::new(indptr_interface).unwrap();
-        let indices_cstr = ffi::CString::new(indices_interface).unwrap();
-        let data_cstr = ffi::CString::new(data_interface).unwrap();
+    /// Create a new `DMatrix` from feature columns of `f32` values, where each slice holds the values of one feature
+    /// for every row.
+    ///
+    /// `NaN` values are treated as missing. Returns an error if the columns don't all have the same length.
+    ///
+    /// ```
+    /// use xgb::DMatrix;
+    ///
+    /// let age = [31.0, 22.0, 45.0];
+    /// let income = [42.0, f32::NAN, 67.5];
+    /// let dmat = DMatrix::from_columns(&[&age, &income]).unwrap();
+    /// assert_eq!(dmat.shape(), (3, 2));
+    /// ```
+    pub fn from_columns(columns: &[&[f32]]) -> XGBResult<Self> {
+        let columns: Vec<FeatureColumn> = columns.iter().map(|column| FeatureColumn::from(*column)).collect();
+        DMatrix::from_typed_columns(&columns)
+    }
 
-        // Use single thread for small matrices to avoid thread synchronization overhead
-        let config = if data.len() < SINGLE_THREAD_THRESHOLD {
-            ffi::CString::new(r#"{"missing": NaN, "nthread": 1}"#).unwrap()
-        } else {
-            ffi::CString::new(r#"{"missing": NaN}"#).unwrap()
-        };
+    /// Create a new `DMatrix` from feature columns which may each have a different type, and a validity bitmap
+    /// marking null values as missing.
+    ///
+    /// Column values are read by XGBoost in their own type, without first being converted into a dense row-major
+    /// buffer. Returns an error if the columns don't all have the same length, or if a validity bitmap has fewer
+    /// bits than its column has values.
+    ///
+    /// ```
+    /// use xgb::{ColumnValues, DMatrix, FeatureColumn};
+    ///
+    /// let visits = [3i64, 1, 7];
+    /// let score = [0.5f64, 0.0, 0.9];
+    /// let member = [true, false, true];
+    /// let dmat = DMatrix::from_typed_columns(&[
+    ///     FeatureColumn::from(&visits[..]),
+    ///     FeatureColumn::new(ColumnValues::F64(&score)).with_validity(&[0b101]),
+    ///     FeatureColumn::from(&member[..]),
+    /// ]).unwrap();
+    /// assert_eq!(dmat.shape(), (3, 3));
+    /// ```
+    pub fn from_typed_columns(columns: &[FeatureColumn]) -> XGBResult<Self> {
+        let num_rows = columns.first().map(|column| column.values.len()).unwrap_or(0);
+        let mut interfaces = Vec::with_capacity(columns.len());
+        for (i, column) in columns.iter().enumerate() {
+            if column.values.len() != num_rows {
+                return Err(XGBError::new(format!(
+                    "Column {} has {} values, expected {} like column 0",
+                    i,
+                    column.values.len(),
+                    num_rows
+                )));
+            }
+            if let Some(validity) = column.validity
+                && validity.len() * 8 < num_rows
+            {
+                return Err(XGBError::new(format!(
+                    "Validity bitmap of column {} has {} bytes, expected at least {} for {} values",
+                    i,
+                    validity.len(),
+                    num_rows.div_ceil(8),
+                    num_rows
+                )));
+            }
+            let (ptr, typestr) = column.values.array_data();
+            let mask = column.validity.map(|validity| validity.as_ptr() as usize);
+            interfaces.push(make_column_interface(ptr, num_rows, typestr, mask));
+        }
+        DMatrix::from_columnar_interface(&format!("[{}]", interfaces.join(",")))
+    }
 
-        xgb_call!(xgboost_sys::XGDMatrixCreateFromCSR(
-            indptr_cstr.as_ptr(),
-            indices_cstr.as_ptr(),
+    /// Create a new `DMatrix` from a JSON-encoded list of 1-D
+    /// [array interfaces](https://numpy.org/doc/stable/reference/arrays.interface.html), one per column.
+    ///
+    /// Each column may have its own numeric type, and an optional `mask` validity bitmap marking missing values.
+    pub(crate) fn from_columnar_interface(columns: &str) -> XGBResult<Self> {
+        let mut handle = ptr::null_mut();
+        let data_cstr = ffi::CString::new(columns).unwrap();
+        let config = ffi::CString::new(r#"{"missing": NaN, "nthread": 0}"#).unwrap();
+        xgb_call!(xgboost_sys::XGDMatrixCreateFromColumnar(
             data_cstr.as_ptr(),
-            num_cols,
             config.as_ptr(),
             &mut handle
         ))?;
         DMatrix::new(handle)
     }
 
+    /// Create a new `DMatrix` from a sparse
+    /// [CSR](https://en.wikipedia.org/wiki/Sparse_matrix#Compressed_sparse_row_(CSR,_CRS_or_Yale_format)) matrix.
+    ///
+    /// Uses standard CSR representation where the column indices for row _i_ are stored in
+    /// `indices[indptr[i]:indptr[i+1]]` and their corresponding values are stored in
+    /// `data[indptr[i]:indptr[i+1]`. Indices can be of any [`SparseIndex`](trait.SparseIndex.html) type.
+    ///
+    /// If `num_cols` is set to None, number of columns will be inferred from given data.
+    ///
+    /// Returns an error if `indptr` doesn't start at 0, isn't non-decreasing or doesn't end at the number of values,
+    /// if `indices` and `data` have different lengths, or if any column index is negative or (when `num_cols` is
+    /// given) out of range. Use [`from_csr_unchecked`](#method.from_csr_unchecked) to skip these checks.
+    ///
+    /// Note: For small matrices (< 30000 non-zero elements), single-threaded creation is used
+    /// to avoid thread synchronization overhead. For larger matrices, multi-threaded creation
+    /// is used for better performance.
+    pub fn from_csr<P: SparseIndex, I: SparseIndex>(
+        indptr: &[P],
+        indices: &[I],
+        data: &[f32],
+        num_cols: Option<usize>,
+    ) -> XGBResult<Self> {
+        Self::check_data_len(indices, data)?;
+        Compressed::Csr.validate(indptr, indices, data.len(), num_cols)?;
+        Self::from_compressed(Compressed::Csr, indptr, indices, data, num_cols)
+    }
+
+    /// Create a new `DMatrix` from a sparse CSR matrix like [`from_csr`](#method.from_csr), without validating
+    /// `indptr` and `indices`.
+    ///
+    /// # Safety
+    ///
+    /// `indptr` must start at 0, be non-decreasing and end at `data.len()`, and all column indices must be
+    /// non-negative (and less than `num_cols` if given). Otherwise XGBoost may read out of bounds.
+    pub unsafe fn from_csr_unchecked<P: SparseIndex, I: SparseIndex>(
+        indptr: &[P],
+        indices: &[I],
+        data: &[f32],
+        num_cols: Option<usize>,
+    ) -> XGBResult<Self> {
+        Self::check_data_len(indices, data)?;
+        Self::from_compressed(Compressed::Csr, indptr, indices, data, num_cols)
+    }
+
     /// Create a new `DMatrix` from a sparse
     /// [CSC](https://en.wikipedia.org/wiki/Sparse_matrix#Compressed_sparse_column_(CSC_or_CCS))) matrix.
     ///
     /// Uses standard CSC representation where the row indices for column _i_ are stored in
     /// `indices[indptr[i]:indptr[i+1]]` and their corresponding values are stored in
-    /// `data[indptr[i]:indptr[i+1]`.
+    /// `data[indptr[i]:indptr[i+1]`. Indices can be of any [`SparseIndex`](trait.SparseIndex.html) type.
     ///
     /// If `num_rows` is set to None, number of rows will be inferred from given data.
     ///
+    /// Returns an error if `indptr` doesn't start at 0, isn't non-decreasing or doesn't end at the number of values,
+    /// if `indices` and `data` have different lengths, or if any row index is negative or (when `num_rows` is
+    /// given) out of range. Use [`from_csc_unchecked`](#method.from_csc_unchecked) to skip these checks.
+    ///
     /// Note: For small matrices (< 30000 non-zero elements), single-threaded creation is used
     /// to avoid thread synchronization overhead. For larger matrices, multi-threaded creation
     /// is used for better performance.
-    pub fn from_csc(indptr: &[u64], indices: &[u64], data: &[f32], num_rows: Option<usize>) -> XGBResult<Self> {
+    pub fn from_csc<P: SparseIndex, I: SparseIndex>(
+        indptr: &[P],
+        indices: &[I],
+        data: &[f32],
+        num_rows: Option<usize>,
+    ) -> XGBResult<Self> {
+        Self::check_data_len(indices, data)?;
+        Compressed::Csc.validate(indptr, indices, data.len(), num_rows)?;
+        Self::from_compressed(Compressed::Csc, indptr, indices, data, num_rows)
+    }
+
+    /// Create a new `DMatrix` from a sparse CSC matrix like [`from_csc`](#method.from_csc), without validating
+    /// `indptr` and `indices`.
+    ///
+    /// # Safety
+    ///
+    /// `indptr` must start at 0, be non-decreasing and end at `data.len()`, and all row indices must be
+    /// non-negative (and less than `num_rows` if given). Otherwise XGBoost may read out of bounds.
+    pub unsafe fn from_csc_unchecked<P: SparseIndex, I: SparseIndex>(
+        indptr: &[P],
+        indices: &[I],
+        data: &[f32],
+        num_rows: Option<usize>,
+    ) -> XGBResult<Self> {
+        Self::check_data_len(indices, data)?;
+        Self::from_compressed(Compressed::Csc, indptr, indices, data, num_rows)
+    }
+
+    fn check_data_len<I>(indices: &[I], data: &[f32]) -> XGBResult<()> {
+        if indices.len() != data.len() {
+            return Err(XGBError::new(format!(
+                "Number of indices ({}) does not match number of values ({})",
+                indices.len(),
+                data.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn from_compressed<P: SparseIndex, I: SparseIndex>(
+        format: Compressed,
+        indptr: &[P],
+        indices: &[I],
+        data: &[f32],
+        num_indexed: Option<usize>,
+    ) -> XGBResult<Self> {
         // Threshold below which single-threaded is faster due to thread overhead.
         // Benchmarking shows crossover point is around 30k non-zeros on typical hardware.
         const SINGLE_THREAD_THRESHOLD: usize = 30000;
 
-        assert_eq!(indices.len(), data.len());
         let mut handle = ptr::null_mut();
-        let num_rows = num_rows.unwrap_or(0) as xgboost_sys::bst_ulong;
+        let num_indexed = num_indexed.unwrap_or(0) as xgboost_sys::bst_ulong;
 
-        let indptr_interface = make_array_interface_u64(indptr);
-        let indices_interface = make_array_interface_u64(indices);
+        let indptr_interface = make_array_interface_index(indptr);
+        let indices_interface = make_array_interface_index(indices);
         let data_interface = make_array_interface_f32(data);
 
         let indptr_cstr = ffi::CString::new(indptr_interface).unwrap();
@@ -237,14 +862,24 @@ impl DMatrix {
             ffi::CString::new(r#"{"missing": NaN}"#).unwrap()
         };
 
-        xgb_call!(xgboost_sys::XGDMatrixCreateFromCSC(
-            indptr_cstr.as_ptr(),
-            indices_cstr.as_ptr(),
-            data_cstr.as_ptr(),
-            num_rows,
-            config.as_ptr(),
-            &mut handle
-        ))?;
+        match format {
+            Compressed::Csr => xgb_call!(xgboost_sys::XGDMatrixCreateFromCSR(
+                indptr_cstr.as_ptr(),
+                indices_cstr.as_ptr(),
+                data_cstr.as_ptr(),
+                num_indexed,
+                config.as_ptr(),
+                &mut handle
+            ))?,
+            Compressed::Csc => xgb_call!(xgboost_sys::XGDMatrixCreateFromCSC(
+                indptr_cstr.as_ptr(),
+                indices_cstr.as_ptr(),
+                data_cstr.as_ptr(),
+                num_indexed,
+                config.as_ptr(),
+                &mut handle
+            ))?,
+        }
         DMatrix::new(handle)
     }
 
@@ -257,6 +892,11 @@ impl DMatrix {
     /// [XGBoost input format](https://xgboost.readthedocs.io/en/latest/tutorials/input_format.html)
     /// documentation.
     ///
+    /// The file is parsed by XGBoost, with the format and options given as a query string (e.g.
+    /// `train.csv?format=csv&label_column=0`). See [`load_csv`](#method.load_csv) and
+    /// [`load_libsvm`](#method.load_libsvm) for readers with typed options and errors reporting the line and column
+    /// of invalid values.
+    ///
     /// # LIBSVM format
     ///
     /// Specified data in a sparse format as:
@@ -341,6 +981,52 @@ impl DMatrix {
         DMatrix::new(out_handle)
     }
 
+    /// Get the values stored in this matrix in CSR format, as `(indptr, indices, data)`.
+    ///
+    /// Missing values aren't stored, so the column indices for row _i_ are stored in
+    /// `indices[indptr[i]:indptr[i+1]]` and their corresponding values in `data[indptr[i]:indptr[i+1]]`.
+    ///
+    /// ```
+    /// use xgb::DMatrix;
+    ///
+    /// let dmat = DMatrix::from_dense(&[1.0, f32::NAN, 0.0, 2.0], 2).unwrap();
+    /// let (indptr, indices, data) = dmat.get_data_as_csr().unwrap();
+    /// assert_eq!(indptr, vec![0, 1, 3]);
+    /// assert_eq!(indices, vec![0, 0, 1]);
+    /// assert_eq!(data, vec![1.0, 0.0, 2.0]);
+    /// ```
+    pub fn get_data_as_csr(&self) -> XGBResult<(Vec<u64>, Vec<u32>, Vec<f32>)> {
+        let mut num_non_missing = 0;
+        xgb_call!(xgboost_sys::XGDMatrixNumNonMissing(self.handle, &mut num_non_missing))?;
+
+        let mut indptr = vec![0; self.num_rows + 1];
+        let mut indices = vec![0; num_non_missing as usize];
+        let mut data = vec![0.0; num_non_missing as usize];
+        let config = ffi::CString::new("{}").unwrap();
+        xgb_call!(xgboost_sys::XGDMatrixGetDataAsCSR(
+            self.handle,
+            config.as_ptr(),
+            indptr.as_mut_ptr(),
+            indices.as_mut_ptr(),
+            data.as_mut_ptr()
+        ))?;
+        Ok((indptr, indices, data))
+    }
+
+    /// Get the values stored in this matrix as a dense row-major array, with `NaN` for missing values.
+    pub(crate) fn get_data_as_dense(&self) -> XGBResult<Vec<f32>> {
+        let (indptr, indices, data) = self.get_data_as_csr()?;
+        let num_cols = self.num_cols();
+        let mut dense = vec![f32::NAN; self.num_rows() * num_cols];
+        for (row, bounds) in indptr.windows(2).enumerate() {
+            let (start, end) = (bounds[0] as usize, bounds[1] as usize);
+            for (col, value) in indices[start..end].iter().zip(&data[start..end]) {
+                dense[row * num_cols + *col as usize] = *value;
+            }
+        }
+        Ok(dense)
+    }
+
     /// Get ground truth labels for each row of this matrix.
     pub fn get_labels(&self) -> XGBResult<&[f32]> {
         self.get_float_info(KEY_LABEL)
@@ -351,6 +1037,32 @@ impl DMatrix {
         self.set_float_info(KEY_LABEL, array)
     }
 
+    /// Set ground truth labels for a multi-target task, with `num_targets` labels per row.
+    ///
+    /// `labels` is a row-major array of shape (number of rows, `num_targets`), i.e. the labels for row _i_ are
+    /// stored in `labels[i * num_targets..(i + 1) * num_targets]`.
+    ///
+    /// ```
+    /// use xgb::DMatrix;
+    ///
+    /// let mut dmat = DMatrix::from_dense(&[1.0, 2.0, 3.0, 4.0], 2).unwrap();
+    /// dmat.set_labels_2d(&[0.1, 0.2,
+    ///                      0.3, 0.4], 2).unwrap();
+    /// assert_eq!(dmat.get_labels().unwrap().len(), 4);
+    /// ```
+    pub fn set_labels_2d(&mut self, labels: &[f32], num_targets: usize) -> XGBResult<()> {
+        if num_targets == 0 || labels.len() != self.num_rows * num_targets {
+            return Err(XGBError::new(format!(
+                "Label array of length {} does not match shape ({}, {})",
+                labels.len(),
+                self.num_rows,
+                num_targets
+            )));
+        }
+        let array_interface = make_array_interface_f32_2d(labels, self.num_rows, num_targets);
+        self.set_info_from_interface(KEY_LABEL, &array_interface)
+    }
+
     /// Get weights of each instance.
     pub fn get_weights(&self) -> XGBResult<&[f32]> {
         self.get_float_info(KEY_WEIGHT)
@@ -392,6 +1104,77 @@ impl DMatrix {
         self.get_uint_info(KEY_GROUP_PTR)
     }
 
+    /// Get names of each feature (column) of this matrix.
+    ///
+    /// Returns an empty list if no feature names have been set.
+    pub fn get_feature_names(&self) -> XGBResult<Vec<String>> {
+        self.get_str_feature_info(KEY_FEATURE_NAME)
+    }
+
+    /// Set names of each feature (column) of this matrix.
+    ///
+    /// Feature names are stored in models trained on this matrix, and are used to resolve constraints given by
+    /// feature name.
+    pub fn set_feature_names(&mut self, names: &[&str]) -> XGBResult<()> {
+        self.set_str_feature_info(KEY_FEATURE_NAME, names)
+    }
+
+    /// Get types of each feature (column) of this matrix.
+    ///
+    /// Returns an empty list if no feature types have been set.
+    pub fn get_feature_types(&self) -> XGBResult<Vec<FeatureType>> {
+        self.get_str_feature_info(KEY_FEATURE_TYPE)?
+            .iter()
+            .map(|s| s.parse().map_err(XGBError::new))
+            .collect()
+    }
+
+    /// Set types of each feature (column) of this matrix.
+    ///
+    /// Columns marked as [`Categorical`](enum.FeatureType.html#variant.Categorical) must hold non-negative
+    /// integer category codes, and are split on by category rather than by value when training with a tree method
+    /// supporting categorical data (e.g. `Hist`).
+    pub fn set_feature_types(&mut self, types: &[FeatureType]) -> XGBResult<()> {
+        let types: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+        let types: Vec<&str> = types.iter().map(String::as_str).collect();
+        self.set_str_feature_info(KEY_FEATURE_TYPE, &types)
+    }
+
+    fn get_str_feature_info(&self, field: &str) -> XGBResult<Vec<String>> {
+        let field = ffi::CString::new(field).unwrap();
+        let mut out_len = 0;
+        let mut out = ptr::null_mut();
+        xgb_call!(xgboost_sys::XGDMatrixGetStrFeatureInfo(
+            self.handle,
+            field.as_ptr(),
+            &mut out_len,
+            &mut out
+        ))?;
+        if out_len > 0 {
+            let out_ptr_slice = unsafe { slice::from_raw_parts(out, out_len as usize) };
+            let out_vec = out_ptr_slice
+                .iter()
+                .map(|str_ptr| unsafe { ffi::CStr::from_ptr(*str_ptr).to_str().unwrap().to_owned() })
+                .collect();
+            Ok(out_vec)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn set_str_feature_info(&mut self, field: &str, values: &[&str]) -> XGBResult<()> {
+        let field = ffi::CString::new(field).unwrap();
+        // keep owned C strings alive until the call returns, XGBoost copies them
+        let c_values: Vec<ffi::CString> = values.iter().map(|s| ffi::CString::new(*s).unwrap()).collect();
+        let mut c_ptrs: Vec<*const libc::c_char> = c_values.iter().map(|s| s.as_ptr()).collect();
+        xgb_call!(xgboost_sys::XGDMatrixSetStrFeatureInfo(
+            self.handle,
+            field.as_ptr(),
+            c_ptrs.as_mut_ptr(),
+            c_ptrs.len() as xgboost_sys::bst_ulong
+        ))
+    }
+
     fn get_float_info(&self, field: &str) -> XGBResult<&[f32]> {
         let field = ffi::CString::new(field).unwrap();
         let mut out_len = 0;
@@ -439,8 +1222,12 @@ impl DMatrix {
     }
 
     fn set_uint_info(&mut self, field: &str, array: &[u32]) -> XGBResult<()> {
-        let field = ffi::CString::new(field).unwrap();
         let array_interface = make_array_interface_u32(array);
+        self.set_info_from_interface(field, &array_interface)
+    }
+
+    fn set_info_from_interface(&mut self, field: &str, array_interface: &str) -> XGBResult<()> {
+        let field = ffi::CString::new(field).unwrap();
         let data_cstr = ffi::CString::new(array_interface).unwrap();
         xgb_call!(xgboost_sys::XGDMatrixSetInfoFromInterface(
             self.handle,
@@ -456,6 +1243,144 @@ impl Drop for DMatrix {
     }
 }
 
+/// Values and info of a `DMatrix`, in CSR format.
+pub(crate) struct RowData {
+    indptr: Vec<u64>,
+    indices: Vec<u32>,
+    data: Vec<f32>,
+    num_cols: usize,
+    labels: Vec<f32>,
+    weights: Vec<f32>,
+    base_margin: Vec<f32>,
+    group: Vec<u32>,
+    feature_names: Vec<String>,
+    feature_types: Vec<FeatureType>,
+}
+
+impl RowData {
+    pub(crate) fn new(dmat: &DMatrix) -> XGBResult<Self> {
+        let (indptr, indices, data) = dmat.get_data_as_csr()?;
+        Ok(RowData {
+            indptr,
+            indices,
+            data,
+            num_cols: dmat.num_cols(),
+            labels: dmat.get_labels()?.to_vec(),
+            weights: dmat.get_weights()?.to_vec(),
+            base_margin: dmat.get_base_margin()?.to_vec(),
+            // stored as group boundaries, set as group sizes
+            group: dmat.get_group()?.windows(2).map(|w| w[1] - w[0]).collect(),
+            feature_names: dmat.get_feature_names()?,
+            feature_types: dmat.get_feature_types()?,
+        })
+    }
+
+    pub(crate) fn num_rows(&self) -> usize {
+        self.indptr.len() - 1
+    }
+
+    pub(crate) fn has_groups(&self) -> bool {
+        !self.group.is_empty()
+    }
+
+    /// Copy of the given rows, without groups.
+    pub(crate) fn subset(&self, rows: &[usize]) -> RowData {
+        let mut indptr = Vec::with_capacity(rows.len() + 1);
+        indptr.push(0);
+        let mut indices = Vec::new();
+        let mut data = Vec::new();
+        for row in rows {
+            let (start, end) = (self.indptr[*row] as usize, self.indptr[row + 1] as usize);
+            indices.extend_from_slice(&self.indices[start..end]);
+            data.extend_from_slice(&self.data[start..end]);
+            indptr.push(data.len() as u64);
+        }
+        let take = |values: &[f32]| {
+            let width = values.len() / self.num_rows();
+            rows.iter()
+                .flat_map(|row| &values[row * width..(row + 1) * width])
+                .copied()
+                .collect()
+        };
+        RowData {
+            indptr,
+            indices,
+            data,
+            num_cols: self.num_cols,
+            labels: take(&self.labels),
+            weights: take(&self.weights),
+            base_margin: take(&self.base_margin),
+            group: Vec::new(),
+            feature_names: self.feature_names.clone(),
+            feature_types: self.feature_types.clone(),
+        }
+    }
+
+    pub(crate) fn to_dmatrix(&self) -> XGBResult<DMatrix> {
+        let mut dmat = DMatrix::from_csr(&self.indptr, &self.indices, &self.data, Some(self.num_cols))?;
+        let num_rows = self.num_rows().max(1);
+        match self.labels.len() / num_rows {
+            0 => {}
+            1 => dmat.set_labels(&self.labels)?,
+            num_targets => dmat.set_labels_2d(&self.labels, num_targets)?,
+        }
+        if !self.weights.is_empty() {
+            dmat.set_weights(&self.weights)?;
+        }
+        if !self.base_margin.is_empty() {
+            dmat.set_base_margin(&self.base_margin)?;
+        }
+        if !self.group.is_empty() {
+            dmat.set_group(&self.group)?;
+        }
+        if !self.feature_names.is_empty() {
+            let names: Vec<&str> = self.feature_names.iter().map(String::as_str).collect();
+            dmat.set_feature_names(&names)?;
+        }
+        if !self.feature_types.is_empty() {
+            dmat.set_feature_types(&self.feature_types)?;
+        }
+        Ok(dmat)
+    }
+}
+
+/// Feature names, types and base margin of a `DMatrix`, to create dense matrices holding copies of its rows with
+/// other values.
+pub(crate) struct DenseTemplate {
+    num_rows: usize,
+    base_margin: Vec<f32>,
+    feature_names: Vec<String>,
+    feature_types: Vec<FeatureType>,
+}
+
+impl DenseTemplate {
+    pub(crate) fn new(dmat: &DMatrix) -> XGBResult<Self> {
+        Ok(DenseTemplate {
+            num_rows: dmat.num_rows(),
+            base_margin: dmat.get_base_margin()?.to_vec(),
+            feature_names: dmat.get_feature_names()?,
+            feature_types: dmat.get_feature_types()?,
+        })
+    }
+
+    /// Create a `DMatrix` from dense row-major `values` holding `copies` copies of the rows, with the base margin
+    /// repeated for each copy.
+    pub(crate) fn to_dmatrix(&self, values: &[f32], copies: usize) -> XGBResult<DMatrix> {
+        let mut dmat = DMatrix::from_dense(values, self.num_rows * copies)?;
+        if !self.base_margin.is_empty() {
+            dmat.set_base_margin(&self.base_margin.repeat(copies))?;
+        }
+        if !self.feature_names.is_empty() {
+            let names: Vec<&str> = self.feature_names.iter().map(String::as_str).collect();
+            dmat.set_feature_names(&names)?;
+        }
+        if !self.feature_types.is_empty() {
+            dmat.set_feature_types(&self.feature_types)?;
+        }
+        Ok(dmat)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -512,6 +1437,19 @@ mod tests {
         assert_eq!(dmat.get_labels().unwrap(), labels);
     }
 
+    #[test]
+    fn get_set_labels_2d() {
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let mut dmat = DMatrix::from_dense(&data, 3).unwrap();
+
+        assert!(dmat.set_labels_2d(&[1.0, 2.0, 3.0], 2).is_err());
+        assert!(dmat.set_labels_2d(&[1.0, 2.0, 3.0], 0).is_err());
+
+        let labels = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        assert!(dmat.set_labels_2d(&labels, 2).is_ok());
+        assert_eq!(dmat.get_labels().unwrap(), labels);
+    }
+
     #[test]
     fn get_set_weights() {
         let mut dmat = read_train_matrix().unwrap();
@@ -544,6 +1482,192 @@ mod tests {
         assert_eq!(dmat.get_group().unwrap(), &[0, 1]);
     }
 
+    #[test]
+    fn get_set_feature_names() {
+        let mut dmat = DMatrix::from_dense(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2).unwrap();
+        assert!(dmat.get_feature_names().unwrap().is_empty());
+
+        dmat.set_feature_names(&["age", "income", "tenure"]).unwrap();
+        assert_eq!(dmat.get_feature_names().unwrap(), vec!["age", "income", "tenure"]);
+    }
+
+    #[test]
+    fn dense_layouts() {
+        assert_eq!(
+            DenseLayout::RowMajor { num_rows: 2 }.shape_and_strides(6).unwrap(),
+            (2, 3, 3, 1)
+        );
+        assert_eq!(
+            DenseLayout::ColumnMajor { num_rows: 2 }.shape_and_strides(6).unwrap(),
+            (2, 3, 1, 2)
+        );
+        assert!(DenseLayout::RowMajor { num_rows: 4 }.shape_and_strides(6).is_err());
+        assert!(DenseLayout::RowMajor { num_rows: 0 }.shape_and_strides(6).is_err());
+
+        // every other row of a 4x3 row-major array
+        let strided = DenseLayout::Strided {
+            num_rows: 2,
+            num_cols: 3,
+            row_stride: 6,
+            col_stride: 1,
+        };
+        assert_eq!(strided.shape_and_strides(12).unwrap(), (2, 3, 6, 1));
+        assert!(strided.shape_and_strides(8).is_err());
+
+        // offsets that would wrap around to within bounds are rejected
+        let overflowing = DenseLayout::Strided {
+            num_rows: 2,
+            num_cols: 2,
+            row_stride: usize::MAX,
+            col_stride: 1,
+        };
+        assert!(overflowing.shape_and_strides(4).is_err());
+
+        let config = DenseConfigBuilder::default()
+            .missing(-999.0)
+            .nthread(2)
+            .build()
+            .unwrap();
+        assert_eq!(config.to_json(), r#"{"missing":-999,"nthread":2}"#);
+        assert_eq!(DenseConfig::default().to_json(), r#"{"missing":NaN,"nthread":0}"#);
+        let config = DenseConfigBuilder::default()
+            .data_split_mode(DataSplitMode::Column)
+            .build()
+            .unwrap();
+        assert_eq!(config.to_json(), r#"{"missing":NaN,"nthread":0,"data_split_mode":1}"#);
+    }
+
+    #[test]
+    fn from_dense_with() {
+        let config = DenseConfig::default();
+        let data = [1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let dmat = DMatrix::from_dense_with(&data, DenseLayout::RowMajor { num_rows: 2 }, &config).unwrap();
+        assert_eq!(dmat.shape(), (2, 3));
+        assert_eq!(
+            dmat.get_data_as_csr().unwrap(),
+            (
+                vec![0, 3, 6],
+                vec![0, 1, 2, 0, 1, 2],
+                vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+            )
+        );
+        let dmat = DMatrix::from_dense_with(&data, DenseLayout::ColumnMajor { num_rows: 2 }, &config).unwrap();
+        assert_eq!(dmat.shape(), (2, 3));
+        assert_eq!(
+            dmat.get_data_as_csr().unwrap(),
+            (
+                vec![0, 3, 6],
+                vec![0, 1, 2, 0, 1, 2],
+                vec![1.0, 3.0, 5.0, 2.0, 4.0, 6.0]
+            )
+        );
+
+        // -999 marks missing values, the second row is entirely missing but still counted
+        let data = [1i32, -999, -999, -999, 5, 6];
+        let config = DenseConfigBuilder::default().missing(-999.0).build().unwrap();
+        let dmat = DMatrix::from_dense_with(&data, DenseLayout::RowMajor { num_rows: 3 }, &config).unwrap();
+        assert_eq!(dmat.shape(), (3, 2));
+        assert_eq!(
+            dmat.get_data_as_csr().unwrap(),
+            (vec![0, 1, 1, 3], vec![0, 0, 1], vec![1.0, 5.0, 6.0])
+        );
+
+        let data = [0u8, 1, 2, 3, 4, 5, 6, 7];
+        let layout = DenseLayout::Strided {
+            num_rows: 2,
+            num_cols: 2,
+            row_stride: 4,
+            col_stride: 2,
+        };
+        let dmat = DMatrix::from_dense_with(&data, layout, &DenseConfig::default()).unwrap();
+        assert_eq!(dmat.shape(), (2, 2));
+        assert_eq!(
+            dmat.get_data_as_csr().unwrap(),
+            (vec![0, 2, 4], vec![0, 1, 0, 1], vec![0.0, 2.0, 4.0, 6.0])
+        );
+
+        // a single row never steps by its row stride, but its size in bytes still mustn't overflow
+        let layout = DenseLayout::Strided {
+            num_rows: 1,
+            num_cols: 2,
+            row_stride: usize::MAX,
+            col_stride: 1,
+        };
+        assert!(DMatrix::from_dense_with(&[1.0f64, 2.0], layout, &DenseConfig::default()).is_err());
+    }
+
+    #[test]
+    fn column_interfaces() {
+        let values = [1i64, 2, 3];
+        let column = FeatureColumn::from(&values[..]);
+        let (ptr, typestr) = column.values().array_data();
+        assert_eq!(typestr, "<i8");
+        assert_eq!(
+            make_column_interface(ptr, 3, typestr, None),
+            format!(
+                r#"{{"data":[{},true],"shape":[3],"strides":null,"typestr":"<i8","version":3}}"#,
+                values.as_ptr() as usize
+            )
+        );
+        assert!(
+            make_column_interface(ptr, 3, typestr, Some(42))
+                .ends_with(r#","mask":{"data":[42,true],"shape":[3],"typestr":"|t1","version":3}}"#)
+        );
+        assert_eq!(FeatureColumn::from(&[true][..]).values().array_data().1, "|u1");
+
+        // mismatched lengths and short bitmaps are rejected before reaching XGBoost
+        assert!(DMatrix::from_columns(&[&[1.0, 2.0], &[1.0]]).is_err());
+        let long = [0.0f32; 9];
+        let column = FeatureColumn::from(&long[..]).with_validity(&[0xff]);
+        assert!(DMatrix::from_typed_columns(&[column]).is_err());
+    }
+
+    #[test]
+    fn from_columns() {
+        let dmat = DMatrix::from_columns(&[&[1.0, 2.0, 3.0], &[f32::NAN, 5.0, 6.0]]).unwrap();
+        assert_eq!(dmat.shape(), (3, 2));
+        assert_eq!(
+            dmat.get_data_as_csr().unwrap(),
+            (vec![0, 1, 3, 5], vec![0, 0, 1, 0, 1], vec![1.0, 2.0, 5.0, 3.0, 6.0])
+        );
+
+        let ints = [1i32, 0, 3, 4];
+        let floats = [0.5f64, 1.5, f64::NAN, 2.5];
+        let bytes = [0u8, 1, 2, 3];
+        let bools = [true, false, false, true];
+        let dmat = DMatrix::from_typed_columns(&[
+            FeatureColumn::from(&ints[..]).with_validity(&[0b1101]),
+            FeatureColumn::new(ColumnValues::F64(&floats)),
+            FeatureColumn::from(&bytes[..]),
+            FeatureColumn::from(&bools[..]),
+        ])
+        .unwrap();
+        assert_eq!(dmat.shape(), (4, 4));
+
+        // the null int and NaN float are missing, zeros and false are kept
+        let (indptr, indices, data) = dmat.get_data_as_csr().unwrap();
+        assert_eq!(indptr, vec![0, 4, 7, 10, 14]);
+        assert_eq!(indices, vec![0, 1, 2, 3, 1, 2, 3, 0, 2, 3, 0, 1, 2, 3]);
+        assert_eq!(
+            data,
+            vec![1.0, 0.5, 0.0, 1.0, 1.5, 1.0, 0.0, 3.0, 2.0, 0.0, 4.0, 2.5, 3.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn get_set_feature_types() {
+        let mut dmat = DMatrix::from_dense(&[1.0, 0.0, 3.0, 4.0, 2.0, 6.0], 2).unwrap();
+        assert!(dmat.get_feature_types().unwrap().is_empty());
+
+        let types = [
+            FeatureType::Quantitative,
+            FeatureType::Categorical,
+            FeatureType::Integer,
+        ];
+        dmat.set_feature_types(&types).unwrap();
+        assert_eq!(dmat.get_feature_types().unwrap(), types);
+    }
+
     #[test]
     fn from_csr() {
         let indptr: [u64; 5] = [0, 2, 3, 6, 8];
@@ -559,6 +1683,45 @@ mod tests {
         assert_eq!(dmat.num_cols(), 10);
     }
 
+    #[test]
+    fn csr_validation() {
+        let data = [1.0, 2.0, 3.0];
+        let csr = Compressed::Csr;
+        assert!(csr.validate(&[0u64, 1, 3], &[0u64, 1, 2], 3, Some(3)).is_ok());
+        assert!(csr.validate(&[0u64, 1, 3], &[0u64, 1, 2], 3, None).is_ok());
+
+        let err = csr.validate(&[0u32, 2, 1, 3], &[0u32, 1, 2], 3, None).unwrap_err();
+        assert!(err.to_string().contains("indptr[2] = 1 is less than indptr[1] = 2"));
+        assert!(csr.validate(&[0u32, 1, 2], &[0u32, 1, 2], 3, None).is_err());
+        assert!(csr.validate(&[1u32, 2, 3], &[0u32, 1, 2], 3, None).is_err());
+        assert!(csr.validate::<u32, u32>(&[], &[], 0, None).is_err());
+        assert!(csr.validate(&[0i32, -1, 3], &[0i32, 1, 2], 3, None).is_err());
+        assert!(csr.validate(&[0i32, 1, 3], &[0i32, -1, 2], 3, None).is_err());
+
+        let err = csr.validate(&[0usize, 1, 3], &[0usize, 1, 5], 3, Some(4)).unwrap_err();
+        assert!(err.to_string().contains("indices[2] = 5 is out of range for 4 columns"));
+        let err = Compressed::Csc
+            .validate(&[0usize, 1, 3], &[0usize, 1, 5], 3, Some(4))
+            .unwrap_err();
+        assert!(err.to_string().contains("out of range for 4 rows"));
+
+        assert!(DMatrix::from_csr(&[0u64, 1, 3], &[0u64, 1], &data, None).is_err());
+        assert!(DMatrix::from_csc(&[0u64, 1, 4], &[0u64, 1, 2], &data, None).is_err());
+    }
+
+    #[test]
+    fn from_csr_index_types() {
+        let data = [1.0, 2.0, 3.0];
+        let dmat = DMatrix::from_csr(&[0u32, 1, 3], &[0u32, 1, 2], &data, Some(3)).unwrap();
+        assert_eq!(dmat.shape(), (2, 3));
+        let dmat = DMatrix::from_csr(&[0i32, 1, 3], &[0i32, 1, 2], &data, Some(3)).unwrap();
+        assert_eq!(dmat.shape(), (2, 3));
+        let dmat = DMatrix::from_csc(&[0usize, 1, 3], &[0usize, 1, 2], &data, Some(3)).unwrap();
+        assert_eq!(dmat.shape(), (3, 2));
+        let dmat = unsafe { DMatrix::from_csr_unchecked(&[0u64, 1, 3], &[0u32, 1, 2], &data, Some(3)) }.unwrap();
+        assert_eq!(dmat.shape(), (2, 3));
+    }
+
     #[test]
     fn from_csc() {
         let indptr: [u64; 5] = [0, 2, 3, 6, 8];
@@ -621,4 +1784,22 @@ mod tests {
         assert_eq!(dmat.slice(&[0, 1, 2]).unwrap().shape(), (3, 3));
         assert_eq!(dmat.slice(&[3, 2, 1]).unwrap().shape(), (3, 3));
     }
+
+    #[test]
+    fn row_data() {
+        let mut dmat = DMatrix::from_dense(&[1.0, f32::NAN, 0.0, 2.0, 3.0, 4.0], 3).unwrap();
+        dmat.set_labels(&[1.0, 2.0, 3.0]).unwrap();
+        dmat.set_weights(&[0.5, 1.0, 1.5]).unwrap();
+        let data = RowData::new(&dmat).unwrap();
+        assert_eq!(data.num_rows(), 3);
+
+        let subset = data.subset(&[0, 2]).to_dmatrix().unwrap();
+        assert_eq!(subset.shape(), (2, 2));
+        assert_eq!(subset.get_labels().unwrap(), &[1.0, 3.0]);
+        assert_eq!(subset.get_weights().unwrap(), &[0.5, 1.5]);
+        assert_eq!(
+            subset.get_data_as_csr().unwrap(),
+            (vec![0, 1, 3], vec![0, 0, 1], vec![1.0, 3.0, 4.0])
+        );
+    }
 }
@@ -44,6 +44,12 @@ impl XGBError {
 
 impl Error for XGBError {}
 
+impl From<std::io::Error> for XGBError {
+    fn from(err: std::io::Error) -> Self {
+        XGBError::new(err.to_string())
+    }
+}
+
 impl Display for XGBError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "XGBoost error: {}", &self.desc)
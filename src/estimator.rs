@@ -0,0 +1,671 @@
+//! High level estimators in the style of scikit-learn, wrapping parameter setup, training and prediction.
+//!
+//! Estimators are configured with [`BoosterParameters`](../parameters/struct.BoosterParameters.html) and a number
+//! of boosting rounds, trained with `fit`, and can be saved and loaded along with their parameters and any extra
+//! metadata (such as the class labels of a classifier), which are stored as attributes of the underlying
+//! [`Booster`](../struct.Booster.html).
+//!
+//! ```no_run
+//! use xgb::{DMatrix, XGBClassifier};
+//!
+//! let x = &[1.0, 0.0,
+//!           0.0, 1.0,
+//!           1.0, 1.0];
+//! let dtrain = DMatrix::from_dense(x, 3).unwrap();
+//!
+//! let mut clf = XGBClassifier::default();
+//! clf.fit(&dtrain, &["cat", "dog", "cat"]).unwrap();
+//! let labels: Vec<&str> = clf.predict(&dtrain).unwrap();
+//! println!("{:?}, accuracy={}", labels, clf.score(&dtrain, &["cat", "dog", "cat"]).unwrap());
+//! clf.save("clf.json").unwrap();
+//! ```
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::parameters::BoosterParameters;
+use crate::parameters::learning::Objective;
+use crate::{Booster, DMatrix, XGBError, XGBResult};
+
+/// Booster attribute storing the kind of estimator a model was trained by.
+const ATTR_ESTIMATOR_TYPE: &str = "estimator_type";
+/// Booster attribute storing the number of classes of a classifier.
+const ATTR_NUM_CLASSES: &str = "num_classes";
+/// Booster attribute storing the parameters a model was trained with, as `key=value` lines.
+const ATTR_PARAMS: &str = "params";
+/// Booster attribute storing the number of rounds a model was trained for.
+const ATTR_BOOST_ROUNDS: &str = "boost_rounds";
+
+/// Number of boosting rounds used by estimators created with `default()`, and by `XGBParams`.
+pub(crate) const DEFAULT_BOOST_ROUNDS: u32 = 100;
+
+/// Parameters an estimator trains with.
+#[derive(Clone)]
+enum Params {
+    /// Parameters given when creating the estimator.
+    Booster(Box<BoosterParameters>),
+
+    /// Parameters restored from a saved model, as passed to XGBoost.
+    Saved(Vec<(String, String)>),
+}
+
+impl Params {
+    /// Parameters to pass to XGBoost when training on `dtrain`, with the objective replaced if given.
+    fn resolve(&self, dtrain: &DMatrix, objective: Option<Objective>) -> XGBResult<Vec<(String, String)>> {
+        let mut pairs = match *self {
+            Params::Booster(ref params) => {
+                let mut pairs = params.as_string_pairs();
+                pairs.extend(params.booster_type().resolve_constraints(Some(dtrain))?);
+                pairs
+            }
+            Params::Saved(ref pairs) => pairs.clone(),
+        };
+        if let Some(objective) = objective {
+            pairs.retain(|(key, _)| key != "objective" && key != "num_class");
+            if let Objective::MultiSoftprob(num_class) = objective {
+                pairs.push(("num_class".to_owned(), num_class.to_string()));
+            }
+            pairs.push(("objective".to_owned(), objective.to_string()));
+        }
+        Ok(pairs)
+    }
+
+    /// Objective set in the parameters given when creating the estimator, if any.
+    fn objective(&self) -> Option<Objective> {
+        match *self {
+            Params::Booster(ref params) => Some(*params.learning_params().objective()),
+            Params::Saved(_) => None,
+        }
+    }
+}
+
+/// Train a booster for `boost_rounds` rounds on a copy of `x` with labels `y` (and query groups `group` if given),
+/// leaving `x` unchanged. The parameters and number of rounds are stored as attributes, to be restored by
+/// `load_booster`.
+fn train(
+    params: &[(String, String)],
+    boost_rounds: u32,
+    estimator_type: &str,
+    x: &DMatrix,
+    y: &[f32],
+    group: Option<&[u32]>,
+) -> XGBResult<Booster> {
+    // slicing all rows copies the values and info, such as feature names and types, except query groups
+    let all_rows: Vec<usize> = (0..x.num_rows()).collect();
+    let mut dtrain = x.slice(&all_rows)?;
+    dtrain.set_labels(y)?;
+    if let Some(group) = group {
+        dtrain.set_group(group)?;
+    }
+
+    let mut booster = Booster::new_with_cached_dmats(&BoosterParameters::default(), &[&dtrain])?;
+    for (key, value) in params {
+        debug!("Setting parameter: {}={}", key, value);
+        booster.set_param(key, value)?;
+    }
+    for i in 0..boost_rounds {
+        booster.update(&dtrain, i as i32)?;
+    }
+
+    let params: Vec<String> = params.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+    booster.set_attribute(ATTR_ESTIMATOR_TYPE, estimator_type)?;
+    booster.set_attribute(ATTR_PARAMS, &params.join("\n"))?;
+    booster.set_attribute(ATTR_BOOST_ROUNDS, &boost_rounds.to_string())?;
+    Ok(booster)
+}
+
+fn missing_attribute(key: &str) -> XGBError {
+    XGBError::new(format!("Model is missing the '{}' attribute", key))
+}
+
+/// Load a booster saved by an estimator, checking that it was saved by the expected type of estimator, along with
+/// the parameters and number of rounds it was trained with.
+fn load_booster<P: AsRef<Path>>(path: P, estimator_type: &str) -> XGBResult<(Booster, Params, u32)> {
+    let booster = Booster::load(path)?;
+    match booster.get_attribute(ATTR_ESTIMATOR_TYPE)? {
+        Some(ref saved_type) if saved_type == estimator_type => {}
+        Some(saved_type) => {
+            return Err(XGBError::new(format!(
+                "Model was saved by a {} estimator, expected {}",
+                saved_type, estimator_type
+            )));
+        }
+        None => {
+            return Err(XGBError::new(format!(
+                "Model has no '{}' attribute, it was not saved by an estimator",
+                ATTR_ESTIMATOR_TYPE
+            )));
+        }
+    }
+
+    let params = booster
+        .get_attribute(ATTR_PARAMS)?
+        .ok_or_else(|| missing_attribute(ATTR_PARAMS))?;
+    let params = params
+        .lines()
+        .map(|line| {
+            line.split_once('=')
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .ok_or_else(|| XGBError::new(format!("Invalid saved parameter '{}'", line)))
+        })
+        .collect::<XGBResult<Vec<_>>>()?;
+    let boost_rounds = booster
+        .get_attribute(ATTR_BOOST_ROUNDS)?
+        .ok_or_else(|| missing_attribute(ATTR_BOOST_ROUNDS))?;
+    let boost_rounds = boost_rounds
+        .parse()
+        .map_err(|_| XGBError::new(format!("Invalid number of boosting rounds '{}'", boost_rounds)))?;
+    Ok((booster, Params::Saved(params), boost_rounds))
+}
+
+fn fitted(booster: &Option<Booster>) -> XGBResult<&Booster> {
+    booster
+        .as_ref()
+        .ok_or_else(|| XGBError::new("Estimator is not fitted yet, call fit() first"))
+}
+
+fn check_num_labels(dmat: &DMatrix, num_labels: usize) -> XGBResult<()> {
+    if dmat.num_rows() != num_labels {
+        return Err(XGBError::new(format!(
+            "Number of labels ({}) does not match number of rows ({})",
+            num_labels,
+            dmat.num_rows()
+        )));
+    }
+    Ok(())
+}
+
+fn check_score_input(dmat: &DMatrix, num_labels: usize) -> XGBResult<()> {
+    check_num_labels(dmat, num_labels)?;
+    if num_labels == 0 {
+        return Err(XGBError::new("Can't score predictions for an empty matrix"));
+    }
+    Ok(())
+}
+
+/// Coefficient of determination (R²) of predictions against true values.
+fn r2_score(y_true: &[f32], y_pred: &[f32]) -> f32 {
+    let mean = y_true.iter().map(|y| *y as f64).sum::<f64>() / y_true.len() as f64;
+    let ss_res: f64 = y_true
+        .iter()
+        .zip(y_pred)
+        .map(|(y, pred)| (*y as f64 - *pred as f64).powi(2))
+        .sum();
+    let ss_tot: f64 = y_true.iter().map(|y| (*y as f64 - mean).powi(2)).sum();
+    if ss_tot == 0.0 {
+        return if ss_res == 0.0 { 1.0 } else { 0.0 };
+    }
+    (1.0 - ss_res / ss_tot) as f32
+}
+
+/// Mean NDCG over all query groups, using exponential gain (`2^relevance - 1`) and the full length of each group.
+///
+/// Groups without any relevant documents are scored as 1.0, matching XGBoost's `ndcg` metric.
+fn mean_ndcg(y_true: &[f32], scores: &[f32], group: &[u32]) -> f32 {
+    fn dcg(relevances: impl Iterator<Item = f32>) -> f64 {
+        relevances
+            .enumerate()
+            .map(|(rank, rel)| (2f64.powf(rel as f64) - 1.0) / (rank as f64 + 2.0).log2())
+            .sum()
+    }
+
+    let mut total = 0.0;
+    let mut start = 0;
+    for size in group {
+        let end = start + *size as usize;
+        let labels = &y_true[start..end];
+
+        let mut by_score: Vec<usize> = (0..labels.len()).collect();
+        by_score.sort_by(|a, b| scores[start + *b].total_cmp(&scores[start + *a]));
+        let mut ideal = labels.to_vec();
+        ideal.sort_by(|a, b| b.total_cmp(a));
+
+        let idcg = dcg(ideal.into_iter());
+        total += if idcg == 0.0 { 1.0 } else { dcg(by_score.iter().map(|i| labels[*i])) / idcg };
+        start = end;
+    }
+    (total / group.len() as f64) as f32
+}
+
+/// Gradient boosted regression model.
+///
+/// Uses the objective set in its booster parameters, i.e. squared error unless otherwise configured.
+pub struct XGBRegressor {
+    params: Params,
+    boost_rounds: u32,
+    booster: Option<Booster>,
+}
+
+impl Default for XGBRegressor {
+    fn default() -> Self {
+        XGBRegressor::new(BoosterParameters::default(), DEFAULT_BOOST_ROUNDS)
+    }
+}
+
+impl XGBRegressor {
+    const ESTIMATOR_TYPE: &'static str = "regressor";
+
+    /// Create an unfitted regressor, which will train for `boost_rounds` rounds using given parameters.
+    pub fn new(booster_params: BoosterParameters, boost_rounds: u32) -> Self {
+        XGBRegressor {
+            params: Params::Booster(Box::new(booster_params)),
+            boost_rounds,
+            booster: None,
+        }
+    }
+
+    /// Train the model on `x` with labels `y`, replacing any previous model.
+    ///
+    /// `x` isn't modified, the model is trained on a copy of it with labels set to `y`.
+    pub fn fit(&mut self, x: &DMatrix, y: &[f32]) -> XGBResult<()> {
+        check_num_labels(x, y.len())?;
+        let params = self.params.resolve(x, None)?;
+        self.booster = Some(train(&params, self.boost_rounds, Self::ESTIMATOR_TYPE, x, y, None)?);
+        Ok(())
+    }
+
+    /// Predict a value for each row of `x`.
+    pub fn predict(&self, x: &DMatrix) -> XGBResult<Vec<f32>> {
+        fitted(&self.booster)?.predict(x)
+    }
+
+    /// Coefficient of determination (R²) of the predictions for `x` against the true values `y`.
+    pub fn score(&self, x: &DMatrix, y: &[f32]) -> XGBResult<f32> {
+        check_score_input(x, y.len())?;
+        Ok(r2_score(y, &self.predict(x)?))
+    }
+
+    /// Get the trained booster, if fitted.
+    pub fn booster(&self) -> Option<&Booster> {
+        self.booster.as_ref()
+    }
+
+    /// Save the fitted model to a file, in a format determined by the file extension as in
+    /// [`Booster::save`](../struct.Booster.html#method.save).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        fitted(&self.booster)?.save(path)
+    }
+
+    /// Load a model saved by [`save`](#method.save), along with the parameters and number of rounds it was
+    /// trained with, which are used for any further training.
+    pub fn load<P: AsRef<Path>>(path: P) -> XGBResult<Self> {
+        let (booster, params, boost_rounds) = load_booster(path, Self::ESTIMATOR_TYPE)?;
+        Ok(XGBRegressor {
+            params,
+            boost_rounds,
+            booster: Some(booster),
+        })
+    }
+}
+
+/// Gradient boosted classification model, for labels of any type.
+///
+/// Labels are encoded as class indices in sorted order before training, and decoded again when predicting. Uses
+/// the `BinaryLogistic` objective for two classes, and `MultiSoftprob` for more.
+///
+/// Class labels are stored as booster attributes when saving, so the label type must be convertible to and from a
+/// string (e.g. integers, `String`, or `&'static str` when loading isn't needed).
+pub struct XGBClassifier<L> {
+    params: Params,
+    boost_rounds: u32,
+    booster: Option<Booster>,
+    classes: Vec<L>,
+}
+
+impl<L: Clone + Ord + Display> Default for XGBClassifier<L> {
+    fn default() -> Self {
+        XGBClassifier::new(BoosterParameters::default(), DEFAULT_BOOST_ROUNDS)
+    }
+}
+
+impl<L: Clone + Ord + Display> XGBClassifier<L> {
+    const ESTIMATOR_TYPE: &'static str = "classifier";
+
+    /// Create an unfitted classifier, which will train for `boost_rounds` rounds using given parameters.
+    ///
+    /// The objective set in the parameters is replaced based on the number of classes seen by `fit`.
+    pub fn new(booster_params: BoosterParameters, boost_rounds: u32) -> Self {
+        XGBClassifier {
+            params: Params::Booster(Box::new(booster_params)),
+            boost_rounds,
+            booster: None,
+            classes: Vec::new(),
+        }
+    }
+
+    /// Train the model on `x` with class labels `y`, replacing any previous model.
+    ///
+    /// `x` isn't modified, the model is trained on a copy of it with labels set to the class indices of `y`.
+    /// Returns an error if `y` contains fewer than two distinct labels.
+    pub fn fit(&mut self, x: &DMatrix, y: &[L]) -> XGBResult<()> {
+        check_num_labels(x, y.len())?;
+        let mut classes = y.to_vec();
+        classes.sort();
+        classes.dedup();
+        if classes.len() < 2 {
+            return Err(XGBError::new(format!(
+                "Classifier needs at least 2 classes to train, got {}",
+                classes.len()
+            )));
+        }
+
+        let encoded: Vec<f32> = y
+            .iter()
+            .map(|label| classes.binary_search(label).unwrap() as f32)
+            .collect();
+
+        let objective = if classes.len() == 2 {
+            Objective::BinaryLogistic
+        } else {
+            Objective::MultiSoftprob(classes.len() as u32)
+        };
+        let params = self.params.resolve(x, Some(objective))?;
+        let mut booster = train(&params, self.boost_rounds, Self::ESTIMATOR_TYPE, x, &encoded, None)?;
+        booster.set_attribute(ATTR_NUM_CLASSES, &classes.len().to_string())?;
+        for (i, class) in classes.iter().enumerate() {
+            booster.set_attribute(&format!("class_{}", i), &class.to_string())?;
+        }
+        self.booster = Some(booster);
+        self.classes = classes;
+        Ok(())
+    }
+
+    /// Get the probability of each class for each row of `x`.
+    ///
+    /// Returns a flat array along with its shape (number of rows, number of classes), columns are in the same
+    /// order as [`classes`](#method.classes).
+    pub fn predict_proba(&self, x: &DMatrix) -> XGBResult<(Vec<f32>, (usize, usize))> {
+        let booster = fitted(&self.booster)?;
+        if self.classes.len() == 2 {
+            let preds = booster.predict(x)?;
+            let proba = preds.iter().flat_map(|p| [1.0 - *p, *p]).collect();
+            Ok((proba, (preds.len(), 2)))
+        } else {
+            booster.predict_multi_output(x)
+        }
+    }
+
+    /// Predict the most likely class label for each row of `x`.
+    pub fn predict(&self, x: &DMatrix) -> XGBResult<Vec<L>> {
+        let (proba, (_, num_classes)) = self.predict_proba(x)?;
+        Ok(proba
+            .chunks(num_classes)
+            .map(|row| {
+                let best = row
+                    .iter()
+                    .enumerate()
+                    .fold(0, |best, (i, p)| if *p > row[best] { i } else { best });
+                self.classes[best].clone()
+            })
+            .collect())
+    }
+
+    /// Accuracy of the predicted labels for `x` against the true labels `y`.
+    pub fn score(&self, x: &DMatrix, y: &[L]) -> XGBResult<f32> {
+        check_score_input(x, y.len())?;
+        let preds = self.predict(x)?;
+        let correct = preds.iter().zip(y).filter(|(pred, label)| pred == label).count();
+        Ok(correct as f32 / y.len() as f32)
+    }
+
+    /// Get the class labels seen during training, in sorted order.
+    pub fn classes(&self) -> &[L] {
+        &self.classes
+    }
+
+    /// Get the trained booster, if fitted.
+    pub fn booster(&self) -> Option<&Booster> {
+        self.booster.as_ref()
+    }
+
+    /// Save the fitted model along with its class labels to a file, in a format determined by the file extension
+    /// as in [`Booster::save`](../struct.Booster.html#method.save).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        fitted(&self.booster)?.save(path)
+    }
+}
+
+impl<L: Clone + Ord + Display + FromStr> XGBClassifier<L> {
+    /// Load a model saved by [`save`](#method.save), along with the parameters and number of rounds it was
+    /// trained with, which are used for any further training.
+    pub fn load<P: AsRef<Path>>(path: P) -> XGBResult<Self> {
+        let (booster, params, boost_rounds) = load_booster(path, Self::ESTIMATOR_TYPE)?;
+
+        let num_classes = booster
+            .get_attribute(ATTR_NUM_CLASSES)?
+            .ok_or_else(|| missing_attribute(ATTR_NUM_CLASSES))?;
+        let num_classes: usize = num_classes
+            .parse()
+            .map_err(|_| XGBError::new(format!("Invalid number of classes '{}'", num_classes)))?;
+        let mut classes = Vec::with_capacity(num_classes);
+        for i in 0..num_classes {
+            let key = format!("class_{}", i);
+            let value = booster.get_attribute(&key)?.ok_or_else(|| missing_attribute(&key))?;
+            let class = L::from_str(&value).map_err(|_| XGBError::new(format!("Invalid class label '{}'", value)))?;
+            classes.push(class);
+        }
+
+        Ok(XGBClassifier {
+            params,
+            boost_rounds,
+            booster: Some(booster),
+            classes,
+        })
+    }
+}
+
+/// Gradient boosted learning to rank model.
+///
+/// Rows of the training data are split into query groups, given as the number of consecutive rows belonging to
+/// each group. Uses the `RankPairwise` objective unless a different objective is set in its booster parameters.
+pub struct XGBRanker {
+    params: Params,
+    boost_rounds: u32,
+    booster: Option<Booster>,
+}
+
+impl Default for XGBRanker {
+    fn default() -> Self {
+        XGBRanker::new(BoosterParameters::default(), DEFAULT_BOOST_ROUNDS)
+    }
+}
+
+impl XGBRanker {
+    const ESTIMATOR_TYPE: &'static str = "ranker";
+
+    /// Create an unfitted ranker, which will train for `boost_rounds` rounds using given parameters.
+    pub fn new(booster_params: BoosterParameters, boost_rounds: u32) -> Self {
+        XGBRanker {
+            params: Params::Booster(Box::new(booster_params)),
+            boost_rounds,
+            booster: None,
+        }
+    }
+
+    /// Train the model on `x` with relevance `y` and query groups `group`, replacing any previous model.
+    ///
+    /// `y` holds the relevance of each row, and `group` the size of each query group, which must sum to the number
+    /// of rows. `x` isn't modified, the model is trained on a copy of it with labels and groups set to `y` and
+    /// `group`, so `x` itself mustn't have query groups set.
+    pub fn fit(&mut self, x: &DMatrix, y: &[f32], group: &[u32]) -> XGBResult<()> {
+        check_num_labels(x, y.len())?;
+        Self::check_group(x, group)?;
+
+        let objective = match self.params.objective() {
+            Some(Objective::RegLinear) => Some(Objective::RankPairwise),
+            _ => None,
+        };
+        let params = self.params.resolve(x, objective)?;
+        self.booster = Some(train(
+            &params,
+            self.boost_rounds,
+            Self::ESTIMATOR_TYPE,
+            x,
+            y,
+            Some(group),
+        )?);
+        Ok(())
+    }
+
+    /// Predict a ranking score for each row of `x`, higher scores are ranked first within each group.
+    pub fn predict(&self, x: &DMatrix) -> XGBResult<Vec<f32>> {
+        fitted(&self.booster)?.predict(x)
+    }
+
+    /// Mean NDCG over the query groups of `x`, given true relevance `y` and group sizes `group`.
+    pub fn score(&self, x: &DMatrix, y: &[f32], group: &[u32]) -> XGBResult<f32> {
+        check_score_input(x, y.len())?;
+        Self::check_group(x, group)?;
+        Ok(mean_ndcg(y, &self.predict(x)?, group))
+    }
+
+    /// Get the trained booster, if fitted.
+    pub fn booster(&self) -> Option<&Booster> {
+        self.booster.as_ref()
+    }
+
+    /// Save the fitted model to a file, in a format determined by the file extension as in
+    /// [`Booster::save`](../struct.Booster.html#method.save).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        fitted(&self.booster)?.save(path)
+    }
+
+    /// Load a model saved by [`save`](#method.save), along with the parameters and number of rounds it was
+    /// trained with, which are used for any further training.
+    pub fn load<P: AsRef<Path>>(path: P) -> XGBResult<Self> {
+        let (booster, params, boost_rounds) = load_booster(path, Self::ESTIMATOR_TYPE)?;
+        Ok(XGBRanker {
+            params,
+            boost_rounds,
+            booster: Some(booster),
+        })
+    }
+
+    fn check_group(x: &DMatrix, group: &[u32]) -> XGBResult<()> {
+        let total: usize = group.iter().map(|size| *size as usize).sum();
+        if total != x.num_rows() {
+            return Err(XGBError::new(format!(
+                "Group sizes sum to {}, but there are {} rows",
+                total,
+                x.num_rows()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::{self, tree};
+
+    fn small_params() -> BoosterParameters {
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .max_depth(3)
+            .build()
+            .unwrap();
+        parameters::BoosterParametersBuilder::default()
+            .booster_type(parameters::BoosterType::Tree(tree_params))
+            .verbose(false)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn scores() {
+        assert_eq!(r2_score(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]), 1.0);
+        assert_eq!(r2_score(&[1.0, 2.0, 3.0], &[2.0, 2.0, 2.0]), 0.0);
+
+        // perfect ordering in the first group, reversed in the second, no relevant documents in the third
+        let y = [2.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        let scores = [3.0, 2.0, 1.0, 1.0, 0.0, 0.5, 0.1];
+        let ndcg = mean_ndcg(&y, &scores, &[3, 2, 2]);
+        let reversed = 1.0 / 3f64.log2();
+        assert!((ndcg as f64 - (1.0 + reversed + 1.0) / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn regressor() {
+        let x: Vec<f32> = (0..50).map(|i| i as f32).collect();
+        let y: Vec<f32> = x.iter().map(|v| v * 2.0).collect();
+        let dtrain = DMatrix::from_dense(&x, 50).unwrap();
+
+        let mut reg = XGBRegressor::new(small_params(), 20);
+        assert!(reg.predict(&dtrain).is_err());
+        reg.fit(&dtrain, &y).unwrap();
+        assert!(reg.score(&dtrain, &y).unwrap() > 0.9);
+        assert!(reg.fit(&dtrain, &y[1..]).is_err());
+        // trained on a copy, so labels of the input aren't set
+        assert!(dtrain.get_labels().unwrap().is_empty());
+        let empty = dtrain.slice(&[]).unwrap();
+        assert!(reg.score(&empty, &[]).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reg.json");
+        reg.save(&path).unwrap();
+        let mut loaded = XGBRegressor::load(&path).unwrap();
+        assert_eq!(loaded.predict(&dtrain).unwrap(), reg.predict(&dtrain).unwrap());
+
+        // refitting uses the saved parameters and number of rounds
+        assert_eq!(loaded.boost_rounds, 20);
+        loaded.fit(&dtrain, &y).unwrap();
+        assert_eq!(loaded.predict(&dtrain).unwrap(), reg.predict(&dtrain).unwrap());
+        assert!(XGBRanker::load(&path).is_err());
+    }
+
+    #[test]
+    fn classifier() {
+        let x: Vec<f32> = (0..60).map(|i| (i % 3) as f32).collect();
+        let y: Vec<String> = x
+            .iter()
+            .map(|v| ["apple", "banana", "cherry"][*v as usize].to_owned())
+            .collect();
+        let dtrain = DMatrix::from_dense(&x, 60).unwrap();
+
+        let mut clf = XGBClassifier::new(small_params(), 10);
+        clf.fit(&dtrain, &y).unwrap();
+        assert_eq!(clf.classes(), &["apple", "banana", "cherry"]);
+        let (proba, shape) = clf.predict_proba(&dtrain).unwrap();
+        assert_eq!(shape, (60, 3));
+        assert!((proba[..3].iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert_eq!(clf.predict(&dtrain).unwrap(), y);
+        assert_eq!(clf.score(&dtrain, &y).unwrap(), 1.0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clf.json");
+        clf.save(&path).unwrap();
+        let mut loaded = XGBClassifier::<String>::load(&path).unwrap();
+        assert_eq!(loaded.classes(), clf.classes());
+        assert_eq!(loaded.predict(&dtrain).unwrap(), y);
+        loaded.fit(&dtrain, &y).unwrap();
+        assert_eq!(
+            loaded.predict_proba(&dtrain).unwrap(),
+            clf.predict_proba(&dtrain).unwrap()
+        );
+
+        // binary classification with integer labels
+        let y_binary: Vec<i32> = x.iter().map(|v| if *v > 0.0 { 7 } else { -1 }).collect();
+        let mut clf = XGBClassifier::new(small_params(), 10);
+        clf.fit(&dtrain, &y_binary).unwrap();
+        assert_eq!(clf.predict_proba(&dtrain).unwrap().1, (60, 2));
+        assert_eq!(clf.predict(&dtrain).unwrap(), y_binary);
+        assert!(dtrain.get_labels().unwrap().is_empty());
+
+        assert!(clf.fit(&dtrain, &[1; 60]).is_err());
+    }
+
+    #[test]
+    fn ranker() {
+        let x: Vec<f32> = (0..40).map(|i| (i % 4) as f32).collect();
+        let y: Vec<f32> = x.clone();
+        let group = [4; 10];
+        let dtrain = DMatrix::from_dense(&x, 40).unwrap();
+
+        let mut ranker = XGBRanker::new(small_params(), 10);
+        assert!(ranker.fit(&dtrain, &y, &[4, 4]).is_err());
+        ranker.fit(&dtrain, &y, &group).unwrap();
+        assert!((ranker.score(&dtrain, &y, &group).unwrap() - 1.0).abs() < 1e-6);
+        assert!(dtrain.get_labels().unwrap().is_empty());
+        assert!(dtrain.get_group().unwrap().is_empty());
+    }
+}
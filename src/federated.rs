@@ -0,0 +1,483 @@
+//! Federated learning, where several parties jointly train a model without sharing their data.
+//!
+//! A [`FederatedServer`](struct.FederatedServer.html) relays the aggregated statistics between the parties. Each
+//! party runs as a separate process holding its own partition of the data, connects to the server with
+//! [`init`](fn.init.html) and calls [`Booster::train`](../struct.Booster.html#method.train) as usual:
+//!
+//! * for horizontal federated learning, each party holds all features for some of the rows, and creates its
+//!   `DMatrix` as usual.
+//! * for vertical federated learning, each party holds some of the features for all rows, and creates its `DMatrix`
+//!   with [`DataSplitMode::Column`](../enum.DataSplitMode.html#variant.Column) (see
+//!   [`DenseConfigBuilder`](../struct.DenseConfigBuilder.html)).
+//!
+//! Requires XGBoost to be built with the federated plugin, which the `federated` feature enables when building
+//! XGBoost locally (it's not part of the prebuilt library). Otherwise creating a server or connecting to one returns
+//! an error.
+//!
+//! Only dense data created with a [`DenseConfig`](../struct.DenseConfig.html) can be split by column, other
+//! constructors (such as [`DMatrix::from_csr`](../struct.DMatrix.html#method.from_csr),
+//! [`DMatrix::from_columns`](../struct.DMatrix.html#method.from_columns) and
+//! [`DMatrix::load`](../struct.DMatrix.html#method.load)) always create row split data.
+//!
+//! ```no_run
+//! use xgb::federated::{self, FederatedServer, FederatedServerConfigBuilder, FederatedWorkerConfigBuilder};
+//!
+//! // in the server process
+//! let config = FederatedServerConfigBuilder::default().n_workers(2).port(9091).build().unwrap();
+//! let server = FederatedServer::start(&config).unwrap();
+//! server.wait(None).unwrap();
+//!
+//! // in each party's process
+//! let config = FederatedWorkerConfigBuilder::default()
+//!     .server_address("localhost:9091".to_owned())
+//!     .world_size(2)
+//!     .rank(0)
+//!     .build()
+//!     .unwrap();
+//! let communicator = federated::init(&config).unwrap();
+//! // ... load this party's data and call Booster::train ...
+//! drop(communicator);
+//! ```
+use std::time::Duration;
+
+use crate::XGBResult;
+use crate::collective::{self, Communicator, Tracker};
+
+/// Quote and escape a string for use in a JSON config.
+fn json_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Options used when starting a [`FederatedServer`](struct.FederatedServer.html). Create using
+/// [`FederatedServerConfigBuilder`](struct.FederatedServerConfigBuilder.html).
+///
+/// The server uses TLS if all of `server_key_path`, `server_cert_path` and `client_cert_path` are set.
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct FederatedServerConfig {
+    /// Number of parties that will connect to the server.
+    n_workers: u32,
+
+    /// Port the server listens on.
+    port: u16,
+
+    /// Maximum time in seconds to wait for parties to connect, `None` waits forever.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    timeout: Option<u32>,
+
+    /// Path to the server's private key.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    server_key_path: Option<String>,
+
+    /// Path to the server's certificate.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    server_cert_path: Option<String>,
+
+    /// Path to the certificate used to verify clients.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    client_cert_path: Option<String>,
+}
+
+impl FederatedServerConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if self.n_workers == Some(0) {
+            return Err("Invalid value for 'n_workers' parameter, must be at least 1.".to_owned());
+        }
+        let tls_paths = [&self.server_key_path, &self.server_cert_path, &self.client_cert_path];
+        let num_set = tls_paths.iter().filter(|path| matches!(path, Some(Some(_)))).count();
+        if num_set != 0 && num_set != tls_paths.len() {
+            return Err(
+                "'server_key_path', 'server_cert_path' and 'client_cert_path' must either all be set or none"
+                    .to_owned(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl FederatedServerConfig {
+    /// Get the number of parties.
+    pub fn n_workers(&self) -> u32 {
+        self.n_workers
+    }
+
+    /// Get the port the server listens on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Get the timeout in seconds.
+    pub fn timeout(&self) -> Option<u32> {
+        self.timeout
+    }
+
+    /// Whether the server uses TLS.
+    pub fn is_secure(&self) -> bool {
+        self.server_key_path.is_some()
+    }
+
+    fn to_json(&self) -> String {
+        let mut json = format!(
+            r#"{{"dmlc_communicator":"federated","n_workers":{},"port":{},"federated_secure":{}"#,
+            self.n_workers,
+            self.port,
+            self.is_secure()
+        );
+        if let Some(timeout) = self.timeout {
+            json.push_str(&format!(r#","timeout":{}"#, timeout));
+        }
+        for (key, path) in [
+            ("server_key_path", &self.server_key_path),
+            ("server_cert_path", &self.server_cert_path),
+            ("client_cert_path", &self.client_cert_path),
+        ] {
+            if let Some(path) = path {
+                json.push_str(&format!(r#","{}":{}"#, key, json_string(path)));
+            }
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// Federated server running in the background of the process that started it.
+pub struct FederatedServer {
+    tracker: Tracker,
+}
+
+impl FederatedServer {
+    /// Start a federated server accepting connections from parties.
+    pub fn start(config: &FederatedServerConfig) -> XGBResult<Self> {
+        let tracker = Tracker::from_json(&config.to_json())?;
+        tracker.start()?;
+        Ok(FederatedServer { tracker })
+    }
+
+    /// Block until all parties have finished, or until `timeout` has passed.
+    pub fn wait(&self, timeout: Option<Duration>) -> XGBResult<()> {
+        self.tracker.wait(timeout)
+    }
+}
+
+/// Options used when connecting a party to a [`FederatedServer`](struct.FederatedServer.html). Create using
+/// [`FederatedWorkerConfigBuilder`](struct.FederatedWorkerConfigBuilder.html).
+///
+/// The connection uses TLS if all of `server_cert_path`, `client_key_path` and `client_cert_path` are set.
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct FederatedWorkerConfig {
+    /// Address of the server, e.g. `"localhost:9091"`.
+    server_address: String,
+
+    /// Number of parties.
+    world_size: u32,
+
+    /// Rank of this party, from 0 to `world_size - 1`.
+    rank: u32,
+
+    /// Path to the certificate used to verify the server.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    server_cert_path: Option<String>,
+
+    /// Path to this party's private key.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    client_key_path: Option<String>,
+
+    /// Path to this party's certificate.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    client_cert_path: Option<String>,
+}
+
+impl FederatedWorkerConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if let (Some(rank), Some(world_size)) = (self.rank, self.world_size)
+            && rank >= world_size
+        {
+            return Err(format!(
+                "Invalid value for 'rank' parameter, {} is not less than world size {}.",
+                rank, world_size
+            ));
+        }
+        let tls_paths = [&self.server_cert_path, &self.client_key_path, &self.client_cert_path];
+        let num_set = tls_paths.iter().filter(|path| matches!(path, Some(Some(_)))).count();
+        if num_set != 0 && num_set != tls_paths.len() {
+            return Err(
+                "'server_cert_path', 'client_key_path' and 'client_cert_path' must either all be set or none"
+                    .to_owned(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl FederatedWorkerConfig {
+    /// Get the address of the server.
+    pub fn server_address(&self) -> &str {
+        &self.server_address
+    }
+
+    /// Get the number of parties.
+    pub fn world_size(&self) -> u32 {
+        self.world_size
+    }
+
+    /// Get the rank of this party.
+    pub fn rank(&self) -> u32 {
+        self.rank
+    }
+
+    fn to_json(&self) -> String {
+        let mut json = format!(
+            r#"{{"dmlc_communicator":"federated","federated_server_address":{},"federated_world_size":{},"federated_rank":{}"#,
+            json_string(&self.server_address),
+            self.world_size,
+            self.rank
+        );
+        for (key, path) in [
+            ("federated_server_cert_path", &self.server_cert_path),
+            ("federated_client_key_path", &self.client_key_path),
+            ("federated_client_cert_path", &self.client_cert_path),
+        ] {
+            if let Some(path) = path {
+                json.push_str(&format!(r#","{}":{}"#, key, json_string(path)));
+            }
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// Connect this party to a federated server. Blocks until all parties have connected.
+///
+/// Training is federated until the returned [`Communicator`](../collective/struct.Communicator.html) is dropped.
+pub fn init(config: &FederatedWorkerConfig) -> XGBResult<Communicator> {
+    collective::init(&config.to_json())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_config() {
+        let config = FederatedServerConfigBuilder::default()
+            .n_workers(2)
+            .port(9091)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.to_json(),
+            r#"{"dmlc_communicator":"federated","n_workers":2,"port":9091,"federated_secure":false}"#
+        );
+        assert!(FederatedServerConfigBuilder::default().n_workers(2).build().is_err());
+        assert!(
+            FederatedServerConfigBuilder::default()
+                .n_workers(2)
+                .port(9091)
+                .server_key_path(Some("key.pem".to_owned()))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn worker_config() {
+        let config = FederatedWorkerConfigBuilder::default()
+            .server_address("localhost:9091".to_owned())
+            .world_size(2)
+            .rank(1)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.to_json(),
+            r#"{"dmlc_communicator":"federated","federated_server_address":"localhost:9091","federated_world_size":2,"federated_rank":1}"#
+        );
+        assert!(
+            FederatedWorkerConfigBuilder::default()
+                .server_address("localhost:9091".to_owned())
+                .world_size(2)
+                .rank(2)
+                .build()
+                .is_err()
+        );
+    }
+
+    /// End-to-end training between separate party processes, which needs the federated plugin.
+    #[cfg(feature = "federated")]
+    mod training {
+        use super::*;
+        use crate::parameters::{self, learning, tree};
+        use crate::{Booster, DMatrix, DataSplitMode, DenseConfigBuilder, DenseLayout};
+        use std::net::TcpListener;
+        use std::path::Path;
+        use std::process::Command;
+
+        const WORKER_VAR: &str = "XGB_TEST_FEDERATED_WORKER";
+        const NUM_WORKERS: u32 = 2;
+        const NUM_ROWS: usize = 100;
+        const NUM_COLS: usize = 4;
+
+        /// Full dataset in row-major order, and its labels.
+        fn dataset() -> (Vec<f32>, Vec<f32>) {
+            let x: Vec<f32> = (0..NUM_ROWS * NUM_COLS).map(|i| ((i * 7) % 11) as f32).collect();
+            let y: Vec<f32> = x.chunks(NUM_COLS).map(|row| row[0] - 2.0 * row[1] + row[3]).collect();
+            (x, y)
+        }
+
+        fn train(dtrain: &DMatrix) -> Booster {
+            let tree_params = tree::TreeBoosterParametersBuilder::default()
+                .max_depth(3)
+                .tree_method(tree::TreeMethod::Hist)
+                .build()
+                .unwrap();
+            let learning_params = learning::LearningTaskParametersBuilder::default()
+                .objective(learning::Objective::RegLinear)
+                .base_score(0.5)
+                .build()
+                .unwrap();
+            let booster_params = parameters::BoosterParametersBuilder::default()
+                .booster_type(parameters::BoosterType::Tree(tree_params))
+                .learning_params(learning_params)
+                .verbose(false)
+                .build()
+                .unwrap();
+            let training_params = parameters::TrainingParametersBuilder::default()
+                .dtrain(dtrain)
+                .booster_params(booster_params)
+                .boost_rounds(5)
+                .build()
+                .unwrap();
+            Booster::train(&training_params).unwrap()
+        }
+
+        /// Entry point of party processes started by `federated_training`, training on this party's partition of the
+        /// dataset and writing predictions on its training data to `output`.
+        fn run_worker(args: &str) {
+            let args: Vec<&str> = args.split(';').collect();
+            let (port, rank, split, output) = (args[0], args[1].parse().unwrap(), args[2], Path::new(args[3]));
+            let config = FederatedWorkerConfigBuilder::default()
+                .server_address(format!("localhost:{}", port))
+                .world_size(NUM_WORKERS)
+                .rank(rank)
+                .build()
+                .unwrap();
+            let communicator = init(&config).unwrap();
+
+            let (x, y) = dataset();
+            let rank = rank as usize;
+            let dtrain = if split == "row" {
+                let rows: Vec<usize> = (rank..NUM_ROWS).step_by(NUM_WORKERS as usize).collect();
+                let x: Vec<f32> = rows
+                    .iter()
+                    .flat_map(|row| x[row * NUM_COLS..(row + 1) * NUM_COLS].to_vec())
+                    .collect();
+                let mut dtrain = DMatrix::from_dense(&x, rows.len()).unwrap();
+                dtrain
+                    .set_labels(&rows.iter().map(|row| y[*row]).collect::<Vec<_>>())
+                    .unwrap();
+                dtrain
+            } else {
+                // each party holds half of the columns of every row
+                let cols_per_party = NUM_COLS / NUM_WORKERS as usize;
+                let cols = rank * cols_per_party..(rank + 1) * cols_per_party;
+                let x: Vec<f32> = x.chunks(NUM_COLS).flat_map(|row| row[cols.clone()].to_vec()).collect();
+                let config = DenseConfigBuilder::default()
+                    .data_split_mode(DataSplitMode::Column)
+                    .build()
+                    .unwrap();
+                let mut dtrain =
+                    DMatrix::from_dense_with(&x, DenseLayout::RowMajor { num_rows: NUM_ROWS }, &config).unwrap();
+                dtrain.set_labels(&y).unwrap();
+                dtrain
+            };
+
+            let preds = train(&dtrain).predict(&dtrain).unwrap();
+            let preds: Vec<String> = preds.iter().map(|pred| pred.to_string()).collect();
+            std::fs::write(output.join(format!("{}-{}.txt", split, rank)), preds.join("\n")).unwrap();
+            drop(communicator);
+        }
+
+        #[test]
+        fn federated_training() {
+            if let Ok(args) = std::env::var(WORKER_VAR) {
+                run_worker(&args);
+                return;
+            }
+
+            let (x, y) = dataset();
+            let mut dtrain = DMatrix::from_dense(&x, NUM_ROWS).unwrap();
+            dtrain.set_labels(&y).unwrap();
+            let expected = train(&dtrain).predict(&dtrain).unwrap();
+
+            let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+            let exe = std::env::current_exe().unwrap();
+            for split in ["row", "column"] {
+                let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+                let config = FederatedServerConfigBuilder::default()
+                    .n_workers(NUM_WORKERS)
+                    .port(port)
+                    .timeout(Some(120))
+                    .build()
+                    .unwrap();
+                let server = FederatedServer::start(&config).unwrap();
+
+                // re-run this test in separate processes acting as parties
+                let workers: Vec<_> = (0..NUM_WORKERS)
+                    .map(|rank| {
+                        let args = format!("{};{};{};{}", port, rank, split, tmp_dir.path().display());
+                        Command::new(&exe)
+                            .args(["federated::tests::training::federated_training", "--exact"])
+                            .env(WORKER_VAR, args)
+                            .spawn()
+                            .unwrap()
+                    })
+                    .collect();
+                for mut worker in workers {
+                    assert!(worker.wait().unwrap().success());
+                }
+                server.wait(Some(Duration::from_secs(60))).unwrap();
+
+                for rank in 0..NUM_WORKERS as usize {
+                    let preds =
+                        std::fs::read_to_string(tmp_dir.path().join(format!("{}-{}.txt", split, rank))).unwrap();
+                    let preds: Vec<f32> = preds.lines().map(|pred| pred.parse().unwrap()).collect();
+                    // with a row split, each party predicts its own rows
+                    let expected: Vec<f32> = if split == "row" {
+                        expected
+                            .iter()
+                            .skip(rank)
+                            .step_by(NUM_WORKERS as usize)
+                            .copied()
+                            .collect()
+                    } else {
+                        expected.clone()
+                    };
+                    assert_eq!(preds.len(), expected.len());
+                    for (pred, expected) in preds.iter().zip(&expected) {
+                        assert!(
+                            (pred - expected).abs() < 1e-4,
+                            "{} split: {} != {}",
+                            split,
+                            pred,
+                            expected
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Permutation feature importance.
+//!
+//! The importance of a feature is how much a model's score gets worse when the feature's values are shuffled
+//! between rows, breaking their relationship with the labels. Unlike gain-based importance it isn't biased towards
+//! features with many distinct values, and can be measured on held-out data with any metric.
+//!
+//! Each feature is shuffled several times, and features are scored in parallel threads.
+//!
+//! ```no_run
+//! use xgb::importance::{self, PermutationConfigBuilder};
+//! use xgb::parameters::learning::EvaluationMetric;
+//! use xgb::{Booster, DMatrix};
+//!
+//! let booster = Booster::load("model.json").unwrap();
+//! let dtest = DMatrix::load("test.libsvm?format=libsvm").unwrap();
+//!
+//! let config = PermutationConfigBuilder::default().repeats(10).seed(42).build().unwrap();
+//! let importance = importance::permutation_importance(&booster, &dtest, &EvaluationMetric::AUC, &config).unwrap();
+//! println!("{}", importance);
+//!
+//! // or score with a closure taking predictions and labels
+//! let accuracy = |predictions: &[f32], labels: &[f32]| {
+//!     let correct = predictions.iter().zip(labels).filter(|(p, y)| (**p > 0.5) == (**y > 0.5)).count();
+//!     correct as f32 / labels.len() as f32
+//! };
+//! let importance = importance::permutation_importance_with(&booster, &dtest, accuracy, true, &config).unwrap();
+//! ```
+use std::fmt::{self, Display};
+
+use crate::dmatrix::DenseTemplate;
+use crate::metrics::{self, EvalData};
+use crate::parameters::learning::EvaluationMetric;
+use crate::util::{Rng, default_threads, is_maximized, run_jobs};
+use crate::{Booster, DMatrix, XGBError, XGBResult};
+
+/// Settings for permutation importance. Create using
+/// [`PermutationConfigBuilder`](struct.PermutationConfigBuilder.html).
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct PermutationConfig {
+    /// Number of times each feature is shuffled, averaging the resulting drops in score.
+    ///
+    /// *default*: 5
+    #[builder(default = "5")]
+    repeats: usize,
+
+    /// Seed for shuffling. Results are reproducible for a given seed, regardless of the number of threads.
+    ///
+    /// *default*: 0
+    #[builder(default)]
+    seed: u64,
+
+    /// Number of features scored in parallel. XGBoost's threads are shared evenly between them.
+    ///
+    /// *default*: number of available CPUs
+    #[builder(default = "default_threads()")]
+    threads: usize,
+}
+
+impl PermutationConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if self.repeats == Some(0) {
+            return Err("Invalid value for 'repeats' parameter, must be at least 1.".to_owned());
+        }
+        if self.threads == Some(0) {
+            return Err("Invalid value for 'threads' parameter, must be at least 1.".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl PermutationConfig {
+    /// Get the number of times each feature is shuffled.
+    pub fn repeats(&self) -> usize {
+        self.repeats
+    }
+
+    /// Get the seed for shuffling.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Get the number of features scored in parallel.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+}
+
+/// Importance of a single feature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeatureImportance {
+    /// Name of the feature, or `f0`, `f1`, ... if the `DMatrix` has no feature names.
+    pub feature: String,
+    /// Mean drop in score over all repeats. Positive if shuffling the feature makes the score worse.
+    pub mean: f32,
+    /// Standard deviation of the drop in score over all repeats.
+    pub std: f32,
+    /// Drop in score of each repeat.
+    pub drops: Vec<f32>,
+}
+
+/// Result of [`permutation_importance`](fn.permutation_importance.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermutationImportance {
+    /// Score of the model on unshuffled data.
+    pub baseline: f32,
+    /// Whether higher scores are better.
+    pub maximize: bool,
+    /// Importance of each feature, in column order.
+    pub features: Vec<FeatureImportance>,
+}
+
+impl PermutationImportance {
+    /// Get the importance of each feature, most important first.
+    pub fn sorted(&self) -> Vec<&FeatureImportance> {
+        let mut features: Vec<&FeatureImportance> = self.features.iter().collect();
+        features.sort_by(|a, b| b.mean.total_cmp(&a.mean));
+        features
+    }
+}
+
+impl Display for PermutationImportance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "baseline score: {}", self.baseline)?;
+        writeln!(f, "{:>12}  {:>12}  feature", "mean drop", "std")?;
+        for feature in self.sorted() {
+            writeln!(f, "{:>12.6}  {:>12.6}  {}", feature.mean, feature.std, feature.feature)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compute the permutation importance of each feature of `dmat` for `booster`, scoring its predictions against the
+/// labels (and weights and query groups, if set) of `dmat` with `metric`, see [`metrics::evaluate`](
+/// ../metrics/fn.evaluate.html).
+pub fn permutation_importance(
+    booster: &Booster,
+    dmat: &DMatrix,
+    metric: &EvaluationMetric,
+    config: &PermutationConfig,
+) -> XGBResult<PermutationImportance> {
+    let labels = dmat.get_labels()?;
+    let weights = dmat.get_weights()?;
+    let group_ptr = dmat.get_group()?;
+    let data = EvalData::new(labels).with_weights(weights).with_group_ptr(group_ptr);
+    let score = |predictions: &[f32]| metrics::evaluate(metric, predictions, &data);
+    permute(booster, dmat, &score, is_maximized(&metric.to_string()), config)
+}
+
+/// Compute the permutation importance of each feature of `dmat` for `booster`, scoring its predictions against the
+/// labels of `dmat` with `score(predictions, labels)`, where higher scores are better if `maximize` is set.
+pub fn permutation_importance_with<F>(
+    booster: &Booster,
+    dmat: &DMatrix,
+    score: F,
+    maximize: bool,
+    config: &PermutationConfig,
+) -> XGBResult<PermutationImportance>
+where
+    F: Fn(&[f32], &[f32]) -> f32 + Sync,
+{
+    let labels = dmat.get_labels()?;
+    let score = |predictions: &[f32]| Ok(score(predictions, labels));
+    permute(booster, dmat, &score, maximize, config)
+}
+
+fn permute(
+    booster: &Booster,
+    dmat: &DMatrix,
+    score: &(dyn Fn(&[f32]) -> XGBResult<f32> + Sync),
+    maximize: bool,
+    config: &PermutationConfig,
+) -> XGBResult<PermutationImportance> {
+    let (num_rows, num_cols) = dmat.shape();
+    if num_rows == 0 || num_cols == 0 {
+        return Err(XGBError::new("Can't compute permutation importance on an empty matrix"));
+    }
+    let baseline = score(&booster.predict(dmat)?)?;
+
+    // each thread loads its own copy of the model and creates matrices from a copy of the data
+    let model = booster.save_buffer(true)?;
+    let values = dmat.get_data_as_dense()?;
+    let template = DenseTemplate::new(dmat)?;
+    let feature_names = dmat.get_feature_names()?;
+    let xgb_threads = (default_threads() / config.threads).max(1).to_string();
+
+    let score_permuted = |booster: &Booster, column: usize, repeat: usize| -> XGBResult<f32> {
+        let mut rng = Rng::new(config.seed.wrapping_add((column * config.repeats + repeat) as u64));
+        let mut permuted = values.clone();
+        for i in (1..num_rows).rev() {
+            let j = rng.below(i + 1);
+            permuted.swap(i * num_cols + column, j * num_cols + column);
+        }
+        let permuted_score = score(&booster.predict(&template.to_dmatrix(&permuted, 1)?)?)?;
+        Ok(if maximize { baseline - permuted_score } else { permuted_score - baseline })
+    };
+
+    let init = || {
+        Booster::load_buffer(&model).and_then(|mut booster| {
+            booster.set_param("nthread", &xgb_threads)?;
+            Ok(booster)
+        })
+    };
+    let results = run_jobs(num_cols, config.threads, init, |booster, column| {
+        let booster = booster.as_ref().map_err(|err| XGBError::new(err.to_string()))?;
+        (0..config.repeats)
+            .map(|repeat| score_permuted(booster, column, repeat))
+            .collect::<XGBResult<Vec<f32>>>()
+    })?;
+    let features = results
+        .into_iter()
+        .enumerate()
+        .map(|(column, drops)| {
+            let (mean, std) = mean_std(&drops);
+            FeatureImportance {
+                feature: feature_names
+                    .get(column)
+                    .cloned()
+                    .unwrap_or_else(|| format!("f{}", column)),
+                mean,
+                std,
+                drops,
+            }
+        })
+        .collect();
+    Ok(PermutationImportance {
+        baseline,
+        maximize,
+        features,
+    })
+}
+
+/// Mean and population standard deviation.
+fn mean_std(values: &[f32]) -> (f32, f32) {
+    let n = values.len() as f64;
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+    let variance = values.iter().map(|v| (*v as f64 - mean).powi(2)).sum::<f64>() / n;
+    (mean as f32, variance.sqrt() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::learning::Objective;
+    use crate::testing;
+
+    #[test]
+    fn config_validation() {
+        let config = PermutationConfigBuilder::default().build().unwrap();
+        assert_eq!(config.repeats(), 5);
+        assert_eq!(config.seed(), 0);
+        assert!(config.threads() >= 1);
+        assert!(PermutationConfigBuilder::default().repeats(0).build().is_err());
+        assert!(PermutationConfigBuilder::default().threads(0).build().is_err());
+    }
+
+    #[test]
+    fn summary() {
+        assert_eq!(mean_std(&[1.0, 3.0]), (2.0, 1.0));
+        assert_eq!(mean_std(&[0.5]), (0.5, 0.0));
+
+        let feature = |name: &str, mean| FeatureImportance {
+            feature: name.to_owned(),
+            mean,
+            std: 0.0,
+            drops: vec![mean],
+        };
+        let importance = PermutationImportance {
+            baseline: 0.9,
+            maximize: true,
+            features: vec![feature("a", 0.1), feature("b", 0.3), feature("c", -0.01)],
+        };
+        let names: Vec<&str> = importance.sorted().iter().map(|f| f.feature.as_str()).collect();
+        assert_eq!(names, vec!["b", "a", "c"]);
+        let table = importance.to_string();
+        assert!(table.starts_with("baseline score: 0.9\n"));
+        assert!(table.lines().nth(2).unwrap().ends_with("  b"));
+    }
+
+    fn train(objective: Objective, x: &[f32], labels: &[f32]) -> (Booster, DMatrix) {
+        let mut dtrain = testing::dmatrix(x, labels);
+        dtrain.set_feature_names(&["signal", "weak", "noise"]).unwrap();
+        (testing::train(&dtrain, objective, 20), dtrain)
+    }
+
+    fn data() -> (Vec<f32>, Vec<f32>) {
+        let x: Vec<f32> = (0..900).map(|i| ((i * 7 + i / 3) % 17) as f32).collect();
+        let labels = x.chunks(3).map(|row| 3.0 * row[0] + 0.5 * row[1]).collect();
+        (x, labels)
+    }
+
+    #[test]
+    fn metric() {
+        let (x, labels) = data();
+        let (booster, dmat) = train(Objective::RegLinear, &x, &labels);
+        let config = PermutationConfigBuilder::default()
+            .repeats(3)
+            .threads(2)
+            .build()
+            .unwrap();
+
+        let importance = permutation_importance(&booster, &dmat, &EvaluationMetric::RMSE, &config).unwrap();
+        assert!(!importance.maximize);
+        let names: Vec<&str> = importance.sorted().iter().map(|f| f.feature.as_str()).collect();
+        assert_eq!(names[..2], ["signal", "weak"]);
+        assert!(importance.features[0].mean > 0.0);
+        assert!(importance.features.iter().all(|f| f.drops.len() == 3));
+
+        // reproducible regardless of the number of threads
+        let config_1 = PermutationConfigBuilder::default()
+            .repeats(3)
+            .threads(1)
+            .build()
+            .unwrap();
+        let again = permutation_importance(&booster, &dmat, &EvaluationMetric::RMSE, &config_1).unwrap();
+        assert_eq!(importance, again);
+    }
+
+    #[test]
+    fn closure() {
+        let (x, labels) = data();
+        let labels: Vec<f32> = labels.iter().map(|y| (*y > 25.0) as u8 as f32).collect();
+        let (booster, dmat) = train(Objective::BinaryLogistic, &x, &labels);
+        let config = PermutationConfigBuilder::default().repeats(2).build().unwrap();
+
+        let accuracy = |predictions: &[f32], labels: &[f32]| {
+            let correct = predictions
+                .iter()
+                .zip(labels)
+                .filter(|(p, y)| (**p > 0.5) == (**y > 0.5))
+                .count();
+            correct as f32 / labels.len() as f32
+        };
+        let importance = permutation_importance_with(&booster, &dmat, accuracy, true, &config).unwrap();
+        assert!(importance.maximize);
+        assert!(importance.baseline > 0.9);
+        assert_eq!(importance.sorted()[0].feature, "signal");
+
+        let auc = permutation_importance(&booster, &dmat, &EvaluationMetric::AUC, &config).unwrap();
+        assert!(auc.maximize);
+        assert_eq!(auc.sorted()[0].feature, "signal");
+    }
+
+    #[test]
+    fn ranking_metrics() {
+        let (x, labels) = data();
+        let labels: Vec<f32> = labels.iter().map(|y| (*y / 15.0).floor()).collect();
+        let (booster, mut dmat) = train(Objective::RankPairwise, &x, &labels);
+        dmat.set_group(&[30; 10]).unwrap();
+        let config = PermutationConfigBuilder::default().repeats(2).build().unwrap();
+
+        for metric in [
+            EvaluationMetric::NDCG,
+            EvaluationMetric::NDCGNegative,
+            EvaluationMetric::MAPNegative,
+            EvaluationMetric::NDCGCutNegative(5),
+        ] {
+            let importance = permutation_importance(&booster, &dmat, &metric, &config).unwrap();
+            assert!(importance.maximize, "{}", metric);
+            assert_eq!(importance.sorted()[0].feature, "signal", "{}", metric);
+            assert!(importance.features[0].mean > 0.0, "{}", metric);
+        }
+    }
+}
@@ -0,0 +1,677 @@
+//! Partial dependence and individual conditional expectation (ICE) curves, showing how predictions change as
+//! features are varied.
+//!
+//! Each row of a `DMatrix` is predicted with a feature replaced by each point of a [`Grid`](enum.Grid.html), by
+//! default quantiles of the feature's values. ICE curves give the predictions of every row, and partial dependence
+//! their average over all rows. Results can be written as CSV for plotting elsewhere.
+//!
+//! ```no_run
+//! use xgb::inspection::{self, Grid};
+//! use xgb::{Booster, DMatrix};
+//!
+//! let booster = Booster::load("model.json").unwrap();
+//! let dmat = DMatrix::load("test.libsvm?format=libsvm").unwrap();
+//!
+//! let pd = inspection::partial_dependence(&booster, &dmat, "f0", &Grid::quantiles(20)).unwrap();
+//! for (value, prediction) in pd.grid.iter().zip(&pd.average) {
+//!     println!("f0={}: {}", value, prediction);
+//! }
+//!
+//! let ice = inspection::ice_curves(&booster, &dmat, 0, &Grid::Values(vec![0.0, 0.5, 1.0])).unwrap();
+//! ice.centered().save_csv("ice.csv").unwrap();
+//!
+//! let pd = inspection::partial_dependence_2d(&booster, &dmat, ("f0", "f1"), (&Grid::default(), &Grid::default()));
+//! pd.unwrap().save_csv("pd_f0_f1.csv").unwrap();
+//! ```
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::dmatrix::DenseTemplate;
+use crate::{Booster, DMatrix, FeatureType, XGBError, XGBResult};
+
+/// Maximum number of values in each batch of modified data predicted at once.
+const MAX_BATCH_VALUES: usize = 1 << 24;
+
+/// A feature, given by column index or by name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Feature {
+    /// Column index.
+    Index(usize),
+
+    /// Feature name, resolved against the feature names of the `DMatrix`, or `f0`, `f1`, ... if it has none.
+    Name(String),
+}
+
+impl From<usize> for Feature {
+    fn from(index: usize) -> Self {
+        Feature::Index(index)
+    }
+}
+
+impl From<&str> for Feature {
+    fn from(name: &str) -> Self {
+        Feature::Name(name.to_owned())
+    }
+}
+
+impl From<String> for Feature {
+    fn from(name: String) -> Self {
+        Feature::Name(name)
+    }
+}
+
+/// Values a feature is set to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Grid {
+    /// Quantiles of the feature's non-missing values, at `num_points` evenly spaced probabilities from `lower` to
+    /// `upper`. Duplicate quantiles are removed, and all distinct values are used if there are at most `num_points`.
+    ///
+    /// Categorical features use all their distinct values instead, as interpolated quantiles aren't categories.
+    Quantiles { num_points: usize, lower: f64, upper: f64 },
+
+    /// Given values.
+    Values(Vec<f32>),
+}
+
+impl Grid {
+    /// Quantiles from the 5th to the 95th percentile, leaving out extreme values where data is sparse.
+    pub fn quantiles(num_points: usize) -> Self {
+        Grid::Quantiles {
+            num_points,
+            lower: 0.05,
+            upper: 0.95,
+        }
+    }
+
+    /// Grid points for a feature with given values, `NaN` for missing.
+    fn points(&self, values: impl Iterator<Item = f32>, categorical: bool) -> XGBResult<Vec<f32>> {
+        match self {
+            Grid::Values(points) => {
+                if points.is_empty() || points.iter().any(|p| p.is_nan()) {
+                    return Err(XGBError::new(format!("Invalid grid values {:?}", points)));
+                }
+                Ok(points.clone())
+            }
+            Grid::Quantiles {
+                num_points,
+                lower,
+                upper,
+            } => {
+                if *num_points == 0 || !(0.0 <= *lower && lower <= upper && *upper <= 1.0) {
+                    return Err(XGBError::new(format!(
+                        "Invalid quantile grid of {} points from {} to {}",
+                        num_points, lower, upper
+                    )));
+                }
+                let mut values: Vec<f32> = values.filter(|v| !v.is_nan()).collect();
+                if values.is_empty() {
+                    return Err(XGBError::new("Feature has no values to compute quantiles of"));
+                }
+                values.sort_by(f32::total_cmp);
+                let mut distinct = values.clone();
+                distinct.dedup();
+                if categorical || distinct.len() <= *num_points {
+                    return Ok(distinct);
+                }
+
+                let mut points: Vec<f32> = (0..*num_points)
+                    .map(|i| {
+                        let p = if *num_points == 1 {
+                            (lower + upper) / 2.0
+                        } else {
+                            lower + (upper - lower) * i as f64 / (*num_points - 1) as f64
+                        };
+                        let position = p * (values.len() - 1) as f64;
+                        let below = position.floor() as usize;
+                        let above = position.ceil() as usize;
+                        let t = (position - below as f64) as f32;
+                        values[below] + t * (values[above] - values[below])
+                    })
+                    .collect();
+                points.dedup();
+                Ok(points)
+            }
+        }
+    }
+}
+
+impl Default for Grid {
+    /// 20 quantiles from the 5th to the 95th percentile.
+    fn default() -> Self {
+        Grid::quantiles(20)
+    }
+}
+
+/// Average predictions over all rows with a feature set to each grid point, see
+/// [`partial_dependence`](fn.partial_dependence.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialDependence {
+    /// Name of the feature.
+    pub feature: String,
+    /// Values the feature was set to.
+    pub grid: Vec<f32>,
+    /// Average prediction for each grid point and model output, shaped (grid points, outputs).
+    pub average: Vec<f32>,
+    /// Number of outputs of the model (e.g. one per class for multiclass models).
+    pub num_outputs: usize,
+}
+
+impl PartialDependence {
+    /// Write as CSV, with a header row and one row per grid point holding the feature value and the average
+    /// prediction of each output.
+    pub fn write_csv<W: Write>(&self, writer: W) -> XGBResult<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{},{}", self.feature, prediction_header(self.num_outputs))?;
+        for (value, average) in self.grid.iter().zip(self.average.chunks(self.num_outputs)) {
+            writeln!(writer, "{},{}", value, join(average))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write as CSV to a file at given path, see [`write_csv`](#method.write_csv).
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        self.write_csv(File::create(path)?)
+    }
+}
+
+/// Predictions of every row with a feature set to each grid point, see [`ice_curves`](fn.ice_curves.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct IceCurves {
+    /// Name of the feature.
+    pub feature: String,
+    /// Values the feature was set to.
+    pub grid: Vec<f32>,
+    /// Prediction for each row, grid point and model output, shaped (rows, grid points, outputs).
+    pub predictions: Vec<f32>,
+    /// Number of rows.
+    pub num_rows: usize,
+    /// Number of outputs of the model (e.g. one per class for multiclass models).
+    pub num_outputs: usize,
+}
+
+impl IceCurves {
+    /// Get the predictions of a row for each grid point and output, shaped (grid points, outputs).
+    pub fn curve(&self, row: usize) -> &[f32] {
+        let len = self.grid.len() * self.num_outputs;
+        &self.predictions[row * len..(row + 1) * len]
+    }
+
+    /// Average the curves of all rows into partial dependence.
+    pub fn average(&self) -> PartialDependence {
+        let len = self.grid.len() * self.num_outputs;
+        let mut sums = vec![0.0f64; len];
+        for curve in self.predictions.chunks(len) {
+            for (sum, prediction) in sums.iter_mut().zip(curve) {
+                *sum += *prediction as f64;
+            }
+        }
+        PartialDependence {
+            feature: self.feature.clone(),
+            grid: self.grid.clone(),
+            average: sums
+                .iter()
+                .map(|sum| (sum / self.num_rows.max(1) as f64) as f32)
+                .collect(),
+            num_outputs: self.num_outputs,
+        }
+    }
+
+    /// Get centered ICE curves, with the prediction at the first grid point subtracted from each curve, so curves
+    /// all start at 0 and differences in their shape are easier to see.
+    pub fn centered(&self) -> IceCurves {
+        let len = self.grid.len() * self.num_outputs;
+        let mut predictions = self.predictions.clone();
+        for curve in predictions.chunks_mut(len) {
+            let start = curve[..self.num_outputs].to_vec();
+            for point in curve.chunks_mut(self.num_outputs) {
+                for (prediction, start) in point.iter_mut().zip(&start) {
+                    *prediction -= start;
+                }
+            }
+        }
+        IceCurves {
+            predictions,
+            ..self.clone()
+        }
+    }
+
+    /// Write as CSV, with a header row and one row per row of data and grid point holding the row index, the
+    /// feature value and the prediction of each output.
+    pub fn write_csv<W: Write>(&self, writer: W) -> XGBResult<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "row,{},{}", self.feature, prediction_header(self.num_outputs))?;
+        for row in 0..self.num_rows {
+            for (value, predictions) in self.grid.iter().zip(self.curve(row).chunks(self.num_outputs)) {
+                writeln!(writer, "{},{},{}", row, value, join(predictions))?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write as CSV to a file at given path, see [`write_csv`](#method.write_csv).
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        self.write_csv(File::create(path)?)
+    }
+}
+
+/// Average predictions over all rows with two features set to each pair of grid points, see
+/// [`partial_dependence_2d`](fn.partial_dependence_2d.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialDependence2d {
+    /// Names of the features.
+    pub features: (String, String),
+    /// Values each feature was set to.
+    pub grids: (Vec<f32>, Vec<f32>),
+    /// Average prediction for each pair of grid points and model output, shaped (first grid points, second grid
+    /// points, outputs).
+    pub average: Vec<f32>,
+    /// Number of outputs of the model (e.g. one per class for multiclass models).
+    pub num_outputs: usize,
+}
+
+impl PartialDependence2d {
+    /// Get the average prediction of each output with the features set to the `i`-th and `j`-th points of their
+    /// grids.
+    pub fn get(&self, i: usize, j: usize) -> &[f32] {
+        let start = (i * self.grids.1.len() + j) * self.num_outputs;
+        &self.average[start..start + self.num_outputs]
+    }
+
+    /// Write as CSV, with a header row and one row per pair of grid points holding both feature values and the
+    /// average prediction of each output.
+    pub fn write_csv<W: Write>(&self, writer: W) -> XGBResult<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(
+            writer,
+            "{},{},{}",
+            self.features.0,
+            self.features.1,
+            prediction_header(self.num_outputs)
+        )?;
+        for (i, first) in self.grids.0.iter().enumerate() {
+            for (j, second) in self.grids.1.iter().enumerate() {
+                writeln!(writer, "{},{},{}", first, second, join(self.get(i, j)))?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write as CSV to a file at given path, see [`write_csv`](#method.write_csv).
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        self.write_csv(File::create(path)?)
+    }
+}
+
+/// Compute partial dependence of `booster`'s predictions on `feature` over the rows of `dmat`.
+///
+/// Predictions are in the model's output space, e.g. probabilities for classifiers.
+pub fn partial_dependence<F: Into<Feature>>(
+    booster: &Booster,
+    dmat: &DMatrix,
+    feature: F,
+    grid: &Grid,
+) -> XGBResult<PartialDependence> {
+    Ok(ice_curves(booster, dmat, feature, grid)?.average())
+}
+
+/// Compute ICE curves of `booster`'s predictions for `feature`, one for each row of `dmat`.
+pub fn ice_curves<F: Into<Feature>>(
+    booster: &Booster,
+    dmat: &DMatrix,
+    feature: F,
+    grid: &Grid,
+) -> XGBResult<IceCurves> {
+    let data = Data::new(dmat)?;
+    let column = data.resolve(feature.into())?;
+    let grid = data.grid_points(grid, column)?;
+    let points: Vec<Vec<f32>> = grid.iter().map(|value| vec![*value]).collect();
+    let (predictions, num_outputs) = data.sweep(booster, dmat, &[column], &points)?;
+
+    // reorder from (grid points, rows, outputs) to (rows, grid points, outputs)
+    let mut curves = vec![0.0; predictions.len()];
+    for (point, point_predictions) in predictions.chunks(data.num_rows * num_outputs).enumerate() {
+        for (row, row_predictions) in point_predictions.chunks(num_outputs).enumerate() {
+            let start = (row * grid.len() + point) * num_outputs;
+            curves[start..start + num_outputs].copy_from_slice(row_predictions);
+        }
+    }
+    Ok(IceCurves {
+        feature: data.names[column].clone(),
+        grid,
+        predictions: curves,
+        num_rows: data.num_rows,
+        num_outputs,
+    })
+}
+
+/// Compute partial dependence of `booster`'s predictions on two features jointly over the rows of `dmat`, to show
+/// how they interact.
+pub fn partial_dependence_2d<F: Into<Feature>, G: Into<Feature>>(
+    booster: &Booster,
+    dmat: &DMatrix,
+    features: (F, G),
+    grids: (&Grid, &Grid),
+) -> XGBResult<PartialDependence2d> {
+    let data = Data::new(dmat)?;
+    let columns = [data.resolve(features.0.into())?, data.resolve(features.1.into())?];
+    if columns[0] == columns[1] {
+        return Err(XGBError::new("Expected two different features"));
+    }
+    let first = data.grid_points(grids.0, columns[0])?;
+    let second = data.grid_points(grids.1, columns[1])?;
+    let points: Vec<Vec<f32>> = first
+        .iter()
+        .flat_map(|a| second.iter().map(move |b| vec![*a, *b]))
+        .collect();
+    let (predictions, num_outputs) = data.sweep(booster, dmat, &columns, &points)?;
+
+    let average = predictions
+        .chunks(data.num_rows * num_outputs)
+        .flat_map(|point_predictions| {
+            let mut sums = vec![0.0f64; num_outputs];
+            for row in point_predictions.chunks(num_outputs) {
+                for (sum, prediction) in sums.iter_mut().zip(row) {
+                    *sum += *prediction as f64;
+                }
+            }
+            sums.into_iter().map(|sum| (sum / data.num_rows as f64) as f32)
+        })
+        .collect();
+    Ok(PartialDependence2d {
+        features: (data.names[columns[0]].clone(), data.names[columns[1]].clone()),
+        grids: (first, second),
+        average,
+        num_outputs,
+    })
+}
+
+/// Dense copy of the data in a `DMatrix`.
+struct Data {
+    values: Vec<f32>,
+    num_rows: usize,
+    num_cols: usize,
+    names: Vec<String>,
+    categorical: Vec<bool>,
+}
+
+impl Data {
+    fn new(dmat: &DMatrix) -> XGBResult<Self> {
+        let (num_rows, num_cols) = dmat.shape();
+        if num_rows == 0 || num_cols == 0 {
+            return Err(XGBError::new("Can't inspect predictions on an empty matrix"));
+        }
+        let names = dmat.get_feature_names()?;
+        let names = (0..num_cols)
+            .map(|i| names.get(i).cloned().unwrap_or_else(|| format!("f{}", i)))
+            .collect();
+        let feature_types = dmat.get_feature_types()?;
+        let categorical = (0..num_cols)
+            .map(|i| feature_types.get(i) == Some(&FeatureType::Categorical))
+            .collect();
+        Ok(Data {
+            values: dmat.get_data_as_dense()?,
+            num_rows,
+            num_cols,
+            names,
+            categorical,
+        })
+    }
+
+    fn resolve(&self, feature: Feature) -> XGBResult<usize> {
+        match feature {
+            Feature::Index(index) if index < self.num_cols => Ok(index),
+            Feature::Index(index) => Err(XGBError::new(format!(
+                "Feature index {} out of range for {} columns",
+                index, self.num_cols
+            ))),
+            Feature::Name(name) => self
+                .names
+                .iter()
+                .position(|n| *n == name)
+                .ok_or_else(|| XGBError::new(format!("Unknown feature '{}'", name))),
+        }
+    }
+
+    fn column(&self, column: usize) -> impl Iterator<Item = f32> + '_ {
+        self.values.iter().skip(column).step_by(self.num_cols).copied()
+    }
+
+    fn grid_points(&self, grid: &Grid, column: usize) -> XGBResult<Vec<f32>> {
+        grid.points(self.column(column), self.categorical[column])
+    }
+
+    /// Predict every row with `columns` set to the values of each of `points`, in batches of copies of the data.
+    ///
+    /// Returns predictions shaped (points, rows, outputs) and the number of outputs.
+    fn sweep(
+        &self,
+        booster: &Booster,
+        dmat: &DMatrix,
+        columns: &[usize],
+        points: &[Vec<f32>],
+    ) -> XGBResult<(Vec<f32>, usize)> {
+        let template = DenseTemplate::new(dmat)?;
+
+        let points_per_batch = (MAX_BATCH_VALUES / self.values.len()).max(1);
+        let mut predictions = Vec::new();
+        let mut num_outputs = 0;
+        for batch in points.chunks(points_per_batch) {
+            let mut values = Vec::with_capacity(batch.len() * self.values.len());
+            for point in batch {
+                let start = values.len();
+                values.extend_from_slice(&self.values);
+                for row in values[start..].chunks_mut(self.num_cols) {
+                    for (column, value) in columns.iter().zip(point) {
+                        row[*column] = *value;
+                    }
+                }
+            }
+            let batch_dmat = template.to_dmatrix(&values, batch.len())?;
+            let (batch_predictions, (_, outputs)) = booster.predict_multi_output(&batch_dmat)?;
+            predictions.extend(batch_predictions);
+            num_outputs = outputs;
+        }
+        Ok((predictions, num_outputs))
+    }
+}
+
+fn prediction_header(num_outputs: usize) -> String {
+    if num_outputs == 1 {
+        "prediction".to_owned()
+    } else {
+        (0..num_outputs)
+            .map(|i| format!("prediction_{}", i))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn join(values: &[f32]) -> String {
+    values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::learning::Objective;
+    use crate::testing;
+
+    #[test]
+    fn grid_points() {
+        let values = (0..101).map(|i| i as f32);
+        assert_eq!(
+            Grid::quantiles(5).points(values.clone(), false).unwrap(),
+            vec![5.0, 27.5, 50.0, 72.5, 95.0]
+        );
+        let full = Grid::Quantiles {
+            num_points: 3,
+            lower: 0.0,
+            upper: 1.0,
+        };
+        assert_eq!(full.points(values.clone(), false).unwrap(), vec![0.0, 50.0, 100.0]);
+
+        // few distinct values are all used, missing values are ignored
+        let values = [3.0, 1.0, f32::NAN, 1.0, 2.0];
+        assert_eq!(
+            Grid::quantiles(5).points(values.into_iter(), false).unwrap(),
+            vec![1.0, 2.0, 3.0]
+        );
+        assert_eq!(
+            Grid::Values(vec![0.5]).points(values.into_iter(), false).unwrap(),
+            vec![0.5]
+        );
+
+        assert!(Grid::quantiles(5).points([f32::NAN].into_iter(), false).is_err());
+        assert!(Grid::quantiles(0).points(values.into_iter(), false).is_err());
+        let reversed = Grid::Quantiles {
+            num_points: 3,
+            lower: 0.9,
+            upper: 0.1,
+        };
+        assert!(reversed.points(values.into_iter(), false).is_err());
+        assert!(Grid::Values(vec![]).points(values.into_iter(), false).is_err());
+        assert!(Grid::Values(vec![f32::NAN]).points(values.into_iter(), false).is_err());
+
+        // categories are all used rather than interpolated
+        let categories = (0..100).map(|i| (i % 7) as f32);
+        assert_eq!(
+            Grid::quantiles(3).points(categories.clone(), true).unwrap(),
+            (0..7).map(|i| i as f32).collect::<Vec<_>>()
+        );
+        assert_eq!(Grid::quantiles(3).points(categories, false).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn curves() {
+        let ice = IceCurves {
+            feature: "x".to_owned(),
+            grid: vec![0.0, 1.0],
+            predictions: vec![1.0, 2.0, 3.0, 6.0],
+            num_rows: 2,
+            num_outputs: 1,
+        };
+        assert_eq!(ice.curve(1), &[3.0, 6.0]);
+        assert_eq!(ice.average().average, vec![2.0, 4.0]);
+        assert_eq!(ice.centered().predictions, vec![0.0, 1.0, 0.0, 3.0]);
+
+        let mut csv = Vec::new();
+        ice.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "row,x,prediction\n0,0,1\n0,1,2\n1,0,3\n1,1,6\n"
+        );
+
+        let mut csv = Vec::new();
+        ice.average().write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "x,prediction\n0,2\n1,4\n");
+
+        let pd = PartialDependence2d {
+            features: ("x".to_owned(), "y".to_owned()),
+            grids: (vec![0.0, 1.0], vec![5.0]),
+            average: vec![0.25, 0.75, 0.5, 0.5],
+            num_outputs: 2,
+        };
+        assert_eq!(pd.get(1, 0), &[0.5, 0.5]);
+        let mut csv = Vec::new();
+        pd.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "x,y,prediction_0,prediction_1\n0,5,0.25,0.75\n1,5,0.5,0.5\n"
+        );
+    }
+
+    fn train(objective: Objective, x: &[f32], labels: &[f32]) -> (Booster, DMatrix) {
+        let mut dtrain = testing::dmatrix(x, labels);
+        dtrain.set_feature_names(&["a", "b"]).unwrap();
+        (testing::train(&dtrain, objective, 20), dtrain)
+    }
+
+    #[test]
+    fn regression() {
+        let x: Vec<f32> = (0..400).map(|i| ((i * 7) % 23) as f32).collect();
+        let labels: Vec<f32> = x
+            .chunks(2)
+            .map(|row| 2.0 * row[0] + (row[1] > 10.0) as u8 as f32)
+            .collect();
+        let (booster, dmat) = train(Objective::RegLinear, &x, &labels);
+
+        let grid = Grid::quantiles(10);
+        let pd = partial_dependence(&booster, &dmat, "a", &grid).unwrap();
+        assert_eq!(pd.feature, "a");
+        assert_eq!(pd.num_outputs, 1);
+        assert_eq!(pd.average.len(), pd.grid.len());
+        // increases with a, by about twice as much
+        assert!(pd.average.windows(2).all(|w| w[0] <= w[1] + 1e-3));
+        let slope = (pd.average[pd.grid.len() - 1] - pd.average[0]) / (pd.grid[pd.grid.len() - 1] - pd.grid[0]);
+        assert!((slope - 2.0).abs() < 0.5, "slope = {}", slope);
+
+        let ice = ice_curves(&booster, &dmat, 0, &grid).unwrap();
+        assert_eq!(ice.num_rows, 200);
+        let average = ice.average();
+        for (a, b) in average.average.iter().zip(&pd.average) {
+            assert!((a - b).abs() < 1e-4);
+        }
+        // a model with no interactions moves all curves together
+        let centered = ice.centered();
+        for row in 1..ice.num_rows {
+            for (a, b) in centered.curve(row).iter().zip(centered.curve(0)) {
+                assert!((a - b).abs() < 0.5);
+            }
+        }
+
+        let pd = partial_dependence_2d(&booster, &dmat, ("a", 1), (&grid, &Grid::Values(vec![0.0, 20.0]))).unwrap();
+        assert_eq!(pd.features, ("a".to_owned(), "b".to_owned()));
+        assert_eq!(pd.average.len(), pd.grids.0.len() * 2);
+        assert!(pd.get(0, 1)[0] > pd.get(0, 0)[0]);
+
+        assert!(partial_dependence(&booster, &dmat, "c", &grid).is_err());
+        assert!(partial_dependence(&booster, &dmat, 2, &grid).is_err());
+        assert!(partial_dependence_2d(&booster, &dmat, ("a", 0), (&grid, &grid)).is_err());
+    }
+
+    #[test]
+    fn base_margin() {
+        let x: Vec<f32> = (0..400).map(|i| ((i * 7) % 23) as f32).collect();
+        let labels: Vec<f32> = x.chunks(2).map(|row| row[0] - row[1]).collect();
+        let (booster, mut dmat) = train(Objective::RegLinear, &x, &labels);
+        let margin: Vec<f32> = (0..200).map(|i| (i % 7) as f32 * 10.0).collect();
+        dmat.set_base_margin(&margin).unwrap();
+
+        // each row keeps its own margin at every grid point
+        let grid = Grid::Values(vec![1.0, 15.0]);
+        let ice = ice_curves(&booster, &dmat, 0, &grid).unwrap();
+        for (i, point) in [1.0, 15.0].into_iter().enumerate() {
+            let mut values = x.clone();
+            values.iter_mut().step_by(2).for_each(|v| *v = point);
+            let mut expected_dmat = DMatrix::from_dense(&values, 200).unwrap();
+            expected_dmat.set_base_margin(&margin).unwrap();
+            let expected = booster.predict(&expected_dmat).unwrap();
+            for (row, expected) in expected.iter().enumerate() {
+                assert!((ice.curve(row)[i] - expected).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn multiclass() {
+        let x = testing::features(300);
+        let labels: Vec<f32> = x.chunks(2).map(|row| (row[0] as u32 / 5) as f32).collect();
+        let (booster, dmat) = train(Objective::MultiSoftprob(3), &x, &labels);
+
+        let pd = partial_dependence(&booster, &dmat, "a", &Grid::default()).unwrap();
+        assert_eq!(pd.num_outputs, 3);
+        assert_eq!(pd.average.len(), pd.grid.len() * 3);
+        assert!(pd.average.chunks(3).all(|p| (p.iter().sum::<f32>() - 1.0).abs() < 1e-4));
+
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp_dir.path().join("pd.csv");
+        pd.save_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("a,prediction_0,prediction_1,prediction_2\n"));
+        assert_eq!(csv.lines().count(), pd.grid.len() + 1);
+    }
+}
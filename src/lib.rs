@@ -75,11 +75,66 @@ mod error;
 pub use error::{XGBError, XGBResult};
 
 mod dmatrix;
-pub use dmatrix::DMatrix;
+pub use dmatrix::{
+    ColumnValues, DMatrix, DataSplitMode, DenseConfig, DenseConfigBuilder, DenseElement, DenseLayout, FeatureColumn,
+    SparseIndex,
+};
 
 mod booster;
-pub use booster::{Booster, FeatureMap, FeatureType, PredictConfig, PredictType};
+pub use booster::{Booster, FeatureMap, FeatureType};
+
+mod predict;
+pub use predict::{PredictConfig, PredictConfigBuilder, PredictType, Prediction, PredictionView};
+
+mod util;
+
 pub mod parameters;
+
+pub mod text;
+
+pub mod collective;
+
+pub mod federated;
+
+pub mod tuning;
+
+pub mod metrics;
+
+#[cfg(test)]
+mod testing;
+
+pub mod calibration;
+
+pub mod conformal;
+
+pub mod shap;
+
+pub mod inspection;
+
+pub mod importance;
+
+mod checkpoint;
+pub use checkpoint::{CheckpointConfig, CheckpointConfigBuilder};
+
+mod estimator;
+pub use estimator::{XGBClassifier, XGBRanker, XGBRegressor};
+
+#[cfg(feature = "ndarray")]
+mod array;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+
+#[cfg(feature = "polars")]
+mod dataframe;
+
+#[cfg(any(feature = "sprs", feature = "nalgebra-sparse"))]
+mod sparse;
+
+#[cfg(feature = "linfa")]
+mod linfa_impl;
+#[cfg(feature = "linfa")]
+pub use linfa_impl::{XGBModel, XGBParams};
 use std::{ffi, path::Path};
 
 #[cfg(not(target_os = "windows"))]
@@ -0,0 +1,174 @@
+//! Integration with the [linfa](https://docs.rs/linfa) machine learning framework.
+//!
+//! Only available when the `linfa` feature is enabled.
+use linfa::dataset::{AsSingleTargets, DatasetBase};
+use linfa::traits::{Fit, PredictInplace};
+use ndarray::{Array1, ArrayBase, Data, Ix2};
+
+use crate::estimator::DEFAULT_BOOST_ROUNDS;
+use crate::parameters::{BoosterParameters, TrainingParametersBuilder};
+use crate::{Booster, DMatrix, XGBError, XGBResult};
+
+impl From<linfa::Error> for XGBError {
+    fn from(err: linfa::Error) -> Self {
+        XGBError::new(err.to_string())
+    }
+}
+
+/// Hyperparameters of a gradient boosted model trained through linfa's [`Fit`] trait.
+///
+/// Records are converted into a [`DMatrix`](struct.DMatrix.html) without copying where possible, with targets
+/// used as labels and dataset weights (if any) as instance weights.
+///
+/// ```no_run
+/// use linfa::prelude::*;
+/// use ndarray::{array, Array1};
+/// use xgb::XGBParams;
+///
+/// let dataset = Dataset::new(array![[1.0f32, 0.0], [0.0, 1.0], [1.0, 1.0]], array![1.0f32, 0.0, 1.0])
+///     .with_weights(Array1::from(vec![1.0, 2.0, 1.0]));
+/// let model = XGBParams::default().boost_rounds(20).fit(&dataset).unwrap();
+/// let preds: Array1<f32> = model.predict(dataset.records());
+/// ```
+#[derive(Clone)]
+pub struct XGBParams {
+    booster_params: BoosterParameters,
+    boost_rounds: u32,
+}
+
+impl Default for XGBParams {
+    fn default() -> Self {
+        XGBParams::new(BoosterParameters::default())
+    }
+}
+
+impl XGBParams {
+    /// Create hyperparameters using given booster parameters, training for 100 rounds like the estimators.
+    pub fn new(booster_params: BoosterParameters) -> Self {
+        XGBParams {
+            booster_params,
+            boost_rounds: DEFAULT_BOOST_ROUNDS,
+        }
+    }
+
+    /// Set the number of boosting rounds to train for.
+    pub fn boost_rounds(mut self, boost_rounds: u32) -> Self {
+        self.boost_rounds = boost_rounds;
+        self
+    }
+
+    /// Get the parameters of the booster.
+    pub fn booster_params(&self) -> &BoosterParameters {
+        &self.booster_params
+    }
+}
+
+/// Gradient boosted model trained by [`XGBParams`](struct.XGBParams.html), predicting one value per row through
+/// linfa's [`Predict`](https://docs.rs/linfa/latest/linfa/traits/trait.Predict.html) trait.
+pub struct XGBModel {
+    booster: Booster,
+}
+
+impl XGBModel {
+    /// Get the trained booster.
+    pub fn booster(&self) -> &Booster {
+        &self.booster
+    }
+
+    /// Consume this model, returning the trained booster.
+    pub fn into_booster(self) -> Booster {
+        self.booster
+    }
+}
+
+impl From<Booster> for XGBModel {
+    fn from(booster: Booster) -> Self {
+        XGBModel { booster }
+    }
+}
+
+impl<D, T> Fit<ArrayBase<D, Ix2>, T, XGBError> for XGBParams
+where
+    D: Data<Elem = f32>,
+    T: AsSingleTargets<Elem = f32>,
+{
+    type Object = XGBModel;
+
+    fn fit(&self, dataset: &DatasetBase<ArrayBase<D, Ix2>, T>) -> XGBResult<XGBModel> {
+        let mut dtrain = DMatrix::from_array_view(dataset.records().view())?;
+        let labels = dataset.targets().as_single_targets();
+        match labels.as_slice() {
+            Some(labels) => dtrain.set_labels(labels)?,
+            None => dtrain.set_labels(&labels.to_vec())?,
+        }
+        if let Some(weights) = dataset.weights() {
+            dtrain.set_weights(weights)?;
+        }
+
+        let training_params = TrainingParametersBuilder::default()
+            .dtrain(&dtrain)
+            .booster_params(self.booster_params.clone())
+            .boost_rounds(self.boost_rounds)
+            .build()
+            .map_err(|err| XGBError::new(err.to_string()))?;
+        Ok(XGBModel {
+            booster: Booster::train(&training_params)?,
+        })
+    }
+}
+
+impl<D: Data<Elem = f32>> PredictInplace<ArrayBase<D, Ix2>, Array1<f32>> for XGBModel {
+    /// Predict one value per row of `x`.
+    ///
+    /// Panics if `y` doesn't have one entry per row of `x`, or if XGBoost fails to predict, as linfa's prediction
+    /// traits don't support returning errors. Use [`booster`](#method.booster) to handle errors instead.
+    fn predict_inplace(&self, x: &ArrayBase<D, Ix2>, y: &mut Array1<f32>) {
+        assert_eq!(
+            x.nrows(),
+            y.len(),
+            "The number of data points must match the number of output targets."
+        );
+        let dmat = DMatrix::from_array_view(x.view()).expect("failed to create DMatrix");
+        let preds = self.booster.predict(&dmat).expect("failed to predict");
+        y.assign(&Array1::from(preds));
+    }
+
+    fn default_target(&self, x: &ArrayBase<D, Ix2>) -> Array1<f32> {
+        Array1::zeros(x.nrows())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::{self, tree};
+    use linfa::prelude::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn fit_predict() {
+        let records = Array2::from_shape_fn((40, 2), |(i, j)| ((i + j) % 4) as f32);
+        let targets = records.column(0).mapv(|v| v * 3.0);
+        let dataset = Dataset::new(records, targets).with_weights(Array1::from_elem(40, 1.0));
+
+        let tree_params = tree::TreeBoosterParametersBuilder::default()
+            .max_depth(3)
+            .build()
+            .unwrap();
+        let booster_params = parameters::BoosterParametersBuilder::default()
+            .booster_type(parameters::BoosterType::Tree(tree_params))
+            .verbose(false)
+            .build()
+            .unwrap();
+        let model = XGBParams::new(booster_params).boost_rounds(30).fit(&dataset).unwrap();
+
+        let preds: Array1<f32> = model.predict(dataset.records());
+        assert_eq!(preds.len(), 40);
+        for (pred, target) in preds.iter().zip(dataset.targets()) {
+            assert!((pred - target).abs() < 0.1);
+        }
+
+        let dmat = DMatrix::from_array_view(dataset.records().view()).unwrap();
+        assert_eq!(preds.to_vec(), model.booster().predict(&dmat).unwrap());
+    }
+}
@@ -0,0 +1,959 @@
+//! Pure Rust implementations of XGBoost's evaluation metrics.
+//!
+//! Computes the same values as [`Booster::evaluate`](../struct.Booster.html#method.evaluate) from predictions and
+//! labels, without needing a `DMatrix` or the native library, e.g. for predictions made elsewhere or read back from
+//! logs.
+//!
+//! Predictions are expected in the form returned by [`Booster::predict`](../struct.Booster.html#method.predict),
+//! i.e. probabilities for classification objectives, and a row-major array of shape (rows, classes) for
+//! multiclass metrics.
+//!
+//! ```
+//! use xgb::metrics::{self, EvalData};
+//! use xgb::parameters::learning::EvaluationMetric;
+//!
+//! let labels = [1.0, 0.0, 1.0, 0.0];
+//! let predictions = [0.9, 0.2, 0.4, 0.6];
+//! let data = EvalData::new(&labels);
+//! assert_eq!(metrics::evaluate(&EvaluationMetric::AUC, &predictions, &data).unwrap(), 0.75);
+//! assert_eq!(metrics::evaluate(&EvaluationMetric::BinaryError, &predictions, &data).unwrap(), 0.5);
+//! ```
+use std::f64::consts::PI;
+
+use crate::parameters::learning::EvaluationMetric;
+use crate::{XGBError, XGBResult};
+
+/// Labels, and optionally weights and query groups, that predictions are evaluated against.
+///
+/// Empty slices are treated as not set.
+#[derive(Clone, Copy, Debug)]
+pub struct EvalData<'a> {
+    labels: &'a [f32],
+    weights: &'a [f32],
+    group_ptr: &'a [u32],
+    tweedie_variance_power: f64,
+}
+
+impl<'a> EvalData<'a> {
+    /// Create data with one label per row.
+    pub fn new(labels: &'a [f32]) -> Self {
+        EvalData {
+            labels,
+            weights: &[],
+            group_ptr: &[],
+            tweedie_variance_power: 1.5,
+        }
+    }
+
+    /// Set weights, one per row, or one per query group for ranking metrics when groups are set.
+    pub fn with_weights(mut self, weights: &'a [f32]) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Set query group boundaries, where rows `group_ptr[i]..group_ptr[i + 1]` belong to group _i_ (as returned
+    /// by [`DMatrix::get_group`](../struct.DMatrix.html#method.get_group)).
+    ///
+    /// Ranking metrics and `AUC`/`AUCPR` are averaged over groups when set.
+    pub fn with_group_ptr(mut self, group_ptr: &'a [u32]) -> Self {
+        self.group_ptr = group_ptr;
+        self
+    }
+
+    /// Set the variance power used by `TweedieLogLoss`.
+    ///
+    /// *default*: 1.5
+    pub fn with_tweedie_variance_power(mut self, tweedie_variance_power: f64) -> Self {
+        self.tweedie_variance_power = tweedie_variance_power;
+        self
+    }
+
+    /// Get the labels of each row.
+    pub fn labels(&self) -> &'a [f32] {
+        self.labels
+    }
+
+    /// Get the weights of each row or query group.
+    pub fn weights(&self) -> &'a [f32] {
+        self.weights
+    }
+
+    /// Get the query group boundaries.
+    pub fn group_ptr(&self) -> &'a [u32] {
+        self.group_ptr
+    }
+
+    /// Get the variance power used by `TweedieLogLoss`.
+    pub fn tweedie_variance_power(&self) -> f64 {
+        self.tweedie_variance_power
+    }
+
+    fn num_rows(&self) -> usize {
+        self.labels.len()
+    }
+
+    fn row_weight(&self, row: usize) -> f64 {
+        self.weights.get(row).map_or(1.0, |w| *w as f64)
+    }
+
+    /// Row ranges of each query group, or a single group of all rows if groups aren't set.
+    fn groups(&self) -> XGBResult<Vec<(usize, usize)>> {
+        let num_rows = self.num_rows();
+        if self.group_ptr.is_empty() {
+            return Ok(vec![(0, num_rows)]);
+        }
+        let valid = self.group_ptr[0] == 0
+            && self.group_ptr.windows(2).all(|bounds| bounds[0] <= bounds[1])
+            && self.group_ptr[self.group_ptr.len() - 1] as usize == num_rows;
+        if !valid {
+            return Err(XGBError::new(format!(
+                "Group boundaries must start at 0, be non-decreasing and end at the number of rows ({})",
+                num_rows
+            )));
+        }
+        Ok(self
+            .group_ptr
+            .windows(2)
+            .map(|bounds| (bounds[0] as usize, bounds[1] as usize))
+            .collect())
+    }
+
+    /// Weight of each query group, checking there's one weight per group. Row weights are ignored without groups,
+    /// as all rows form a single group.
+    fn group_weights(&self, num_groups: usize) -> XGBResult<Vec<f64>> {
+        if self.weights.is_empty() || self.group_ptr.is_empty() {
+            return Ok(vec![1.0; num_groups]);
+        }
+        if self.weights.len() != num_groups {
+            return Err(XGBError::new(format!(
+                "Expected one weight per query group ({} groups), got {} weights",
+                num_groups,
+                self.weights.len()
+            )));
+        }
+        Ok(self.weights.iter().map(|w| *w as f64).collect())
+    }
+
+    fn check_row_weights(&self) -> XGBResult<()> {
+        if !self.weights.is_empty() && self.weights.len() != self.num_rows() {
+            return Err(XGBError::new(format!(
+                "Expected one weight per row ({} rows), got {} weights",
+                self.num_rows(),
+                self.weights.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Evaluate `predictions` against `data` with `metric`, computed the same way as by XGBoost.
+///
+/// Returns an error if the number of predictions, weights or groups doesn't match the number of labels, or if
+/// multiclass labels aren't class indices. `AUC` and `AUCPR` are NaN when only one class is present.
+pub fn evaluate(metric: &EvaluationMetric, predictions: &[f32], data: &EvalData) -> XGBResult<f32> {
+    if data.num_rows() == 0 {
+        return Err(XGBError::new("Can't evaluate metric without labels"));
+    }
+    let score = match *metric {
+        EvaluationMetric::RMSE => elementwise(predictions, data, |y, p| (y - p) * (y - p))?.sqrt(),
+        EvaluationMetric::MAE => elementwise(predictions, data, |y, p| (y - p).abs())?,
+        EvaluationMetric::LogLoss => elementwise(predictions, data, log_loss)?,
+        EvaluationMetric::BinaryError => elementwise(predictions, data, |y, p| error(y, p, 0.5))?,
+        EvaluationMetric::BinaryErrorRate(t) => elementwise(predictions, data, |y, p| error(y, p, t as f64))?,
+        EvaluationMetric::PoissonLogLoss => elementwise(predictions, data, |y, p| {
+            let p = p.max(1e-16);
+            ln_gamma(y + 1.0) + p - p.ln() * y
+        })?,
+        // negative log likelihood of a gamma distribution with shape 1
+        EvaluationMetric::GammaLogLoss => elementwise(predictions, data, |y, p| y / p + p.ln())?,
+        EvaluationMetric::GammaDeviance => {
+            let ratio = |y: f64, p: f64| y / (p + 1e-9);
+            2.0 * elementwise(predictions, data, |y, p| ratio(y, p) - ratio(y, p).ln() - 1.0)?
+        }
+        EvaluationMetric::TweedieLogLoss => {
+            let rho = data.tweedie_variance_power;
+            elementwise(predictions, data, |y, p| {
+                -y * ((1.0 - rho) * p.ln()).exp() / (1.0 - rho) + ((2.0 - rho) * p.ln()).exp() / (2.0 - rho)
+            })?
+        }
+        EvaluationMetric::MultiClassErrorRate => multiclass_error(predictions, data)?,
+        EvaluationMetric::MultiClassLogLoss => multiclass_log_loss(predictions, data)?,
+        EvaluationMetric::AUC => area_under_curve(Curve::Roc, predictions, data)?,
+        EvaluationMetric::AUCPR => area_under_curve(Curve::PrecisionRecall, predictions, data)?,
+        EvaluationMetric::NDCG => ranking(Ranking::Ndcg, None, false, predictions, data)?,
+        EvaluationMetric::NDCGCut(k) => ranking(Ranking::Ndcg, Some(k), false, predictions, data)?,
+        EvaluationMetric::NDCGNegative => ranking(Ranking::Ndcg, None, true, predictions, data)?,
+        EvaluationMetric::NDCGCutNegative(k) => ranking(Ranking::Ndcg, Some(k), true, predictions, data)?,
+        EvaluationMetric::MAP => ranking(Ranking::Map, None, false, predictions, data)?,
+        EvaluationMetric::MAPCut(k) => ranking(Ranking::Map, Some(k), false, predictions, data)?,
+        EvaluationMetric::MAPNegative => ranking(Ranking::Map, None, true, predictions, data)?,
+        EvaluationMetric::MAPCutNegative(k) => ranking(Ranking::Map, Some(k), true, predictions, data)?,
+        EvaluationMetric::CoxLogLoss => cox_log_loss(predictions, data)?,
+    };
+    Ok(score as f32)
+}
+
+fn check_num_predictions(predictions: &[f32], expected: usize) -> XGBResult<()> {
+    if predictions.len() != expected {
+        return Err(XGBError::new(format!(
+            "Expected {} predictions, got {}",
+            expected,
+            predictions.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Weighted mean of `loss(label, prediction)` over all rows.
+fn elementwise<F: Fn(f64, f64) -> f64>(predictions: &[f32], data: &EvalData, loss: F) -> XGBResult<f64> {
+    check_num_predictions(predictions, data.num_rows())?;
+    data.check_row_weights()?;
+    let (mut sum, mut weight_sum) = (0.0, 0.0);
+    for (row, (y, p)) in data.labels.iter().zip(predictions).enumerate() {
+        let w = data.row_weight(row);
+        sum += w * loss(*y as f64, *p as f64);
+        weight_sum += w;
+    }
+    Ok(sum / weight_sum)
+}
+
+fn log_loss(y: f64, p: f64) -> f64 {
+    let eps = 1e-16;
+    let p = p.clamp(eps, 1.0 - eps);
+    -y * p.ln() - (1.0 - y) * (1.0 - p).ln()
+}
+
+fn error(y: f64, p: f64, threshold: f64) -> f64 {
+    if p > threshold { 1.0 - y } else { y }
+}
+
+/// Check multiclass predictions have shape (rows, classes), or hold one predicted class per row, returning the
+/// number of classes (1 for predicted classes) and the class index of each label.
+fn classes(predictions: &[f32], data: &EvalData) -> XGBResult<(usize, Vec<usize>)> {
+    let num_rows = data.num_rows();
+    let num_classes = predictions.len() / num_rows;
+    if num_classes == 0 || !predictions.len().is_multiple_of(num_rows) {
+        return Err(XGBError::new(format!(
+            "Expected predictions of shape ({}, number of classes), got {} predictions",
+            num_rows,
+            predictions.len()
+        )));
+    }
+    data.check_row_weights()?;
+    let max_class = if num_classes == 1 { u32::MAX as usize } else { num_classes };
+    let labels = data
+        .labels
+        .iter()
+        .map(|label| {
+            if label.fract() == 0.0 && *label >= 0.0 && (*label as usize) < max_class {
+                Ok(*label as usize)
+            } else {
+                Err(XGBError::new(format!(
+                    "Label {} is not a class index in [0, {})",
+                    label, max_class
+                )))
+            }
+        })
+        .collect::<XGBResult<Vec<_>>>()?;
+    Ok((num_classes, labels))
+}
+
+/// Weighted fraction of rows whose most probable class isn't the label. Also accepts one predicted class per row,
+/// as output by the `multi:softmax` objective.
+fn multiclass_error(predictions: &[f32], data: &EvalData) -> XGBResult<f64> {
+    let (num_classes, labels) = classes(predictions, data)?;
+    let (mut sum, mut weight_sum) = (0.0, 0.0);
+    for (row, label) in labels.iter().enumerate() {
+        let probs = &predictions[row * num_classes..(row + 1) * num_classes];
+        let predicted = if num_classes == 1 {
+            probs[0] as usize
+        } else {
+            // first class with the highest probability
+            (0..num_classes).fold(0, |best, class| if probs[class] > probs[best] { class } else { best })
+        };
+        let w = data.row_weight(row);
+        sum += w * (predicted != *label) as u8 as f64;
+        weight_sum += w;
+    }
+    Ok(sum / weight_sum)
+}
+
+fn multiclass_log_loss(predictions: &[f32], data: &EvalData) -> XGBResult<f64> {
+    let (num_classes, labels) = classes(predictions, data)?;
+    if num_classes == 1 {
+        return Err(XGBError::new(
+            "Multiclass log loss needs predicted probabilities of each class",
+        ));
+    }
+    let (mut sum, mut weight_sum) = (0.0, 0.0);
+    for (row, label) in labels.iter().enumerate() {
+        let p = predictions[row * num_classes + label] as f64;
+        let w = data.row_weight(row);
+        sum -= w * p.max(1e-16).ln();
+        weight_sum += w;
+    }
+    Ok(sum / weight_sum)
+}
+
+#[derive(Clone, Copy)]
+enum Curve {
+    Roc,
+    PrecisionRecall,
+}
+
+/// Area under the ROC or precision-recall curve: per class (one-vs-rest, weighted by the weight of each class)
+/// for multiclass predictions, averaged over query groups when set, and for all rows otherwise.
+fn area_under_curve(curve: Curve, predictions: &[f32], data: &EvalData) -> XGBResult<f64> {
+    let num_rows = data.num_rows();
+    if !data.group_ptr.is_empty() {
+        check_num_predictions(predictions, num_rows)?;
+        let groups = data.groups()?;
+        let weights = data.group_weights(groups.len())?;
+        let (mut sum, mut weight_sum) = (0.0, 0.0);
+        for ((start, end), w) in groups.iter().zip(weights) {
+            let area = match curve {
+                Curve::Roc => pairwise_auc(&predictions[*start..*end], &data.labels[*start..*end]),
+                Curve::PrecisionRecall => binary_area(
+                    curve,
+                    (*start..*end)
+                        .map(|row| (predictions[row], data.labels[row] as f64, 1.0 - data.labels[row] as f64)),
+                ),
+            };
+            // groups with a single label value are skipped
+            if let Some(area) = area {
+                sum += w * area;
+                weight_sum += w;
+            }
+        }
+        return Ok(if weight_sum > 0.0 { sum / weight_sum } else { f64::NAN });
+    }
+
+    if predictions.len() == num_rows {
+        data.check_row_weights()?;
+        let points = (0..num_rows).map(|row| {
+            let (y, w) = (data.labels[row] as f64, data.row_weight(row));
+            (predictions[row], y * w, (1.0 - y) * w)
+        });
+        return Ok(binary_area(curve, points).unwrap_or(f64::NAN));
+    }
+
+    let (num_classes, labels) = classes(predictions, data)?;
+    let (mut sum, mut weight_sum) = (0.0, 0.0);
+    for class in 0..num_classes {
+        let points = (0..num_rows).map(|row| {
+            let w = data.row_weight(row);
+            let positive = labels[row] == class;
+            (
+                predictions[row * num_classes + class],
+                if positive { w } else { 0.0 },
+                if positive { 0.0 } else { w },
+            )
+        });
+        let Some(area) = binary_area(curve, points) else {
+            return Ok(f64::NAN);
+        };
+        let class_weight: f64 = (0..num_rows)
+            .filter(|row| labels[*row] == class)
+            .map(|row| data.row_weight(row))
+            .sum();
+        sum += area * class_weight;
+        weight_sum += class_weight;
+    }
+    Ok(sum / weight_sum)
+}
+
+/// Area under the curve of `(score, positive weight, negative weight)` points, or `None` if either class has no
+/// weight. Tied scores are treated as a single threshold.
+fn binary_area<I: Iterator<Item = (f32, f64, f64)>>(curve: Curve, points: I) -> Option<f64> {
+    let mut points: Vec<(f32, f64, f64)> = points.collect();
+    points.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let total_pos: f64 = points.iter().map(|p| p.1).sum();
+    let total_neg: f64 = points.iter().map(|p| p.2).sum();
+    if total_pos <= 0.0 || total_neg <= 0.0 {
+        return None;
+    }
+
+    let (mut tp, mut fp, mut area) = (0.0, 0.0, 0.0);
+    let mut i = 0;
+    while i < points.len() {
+        let (tp_prev, fp_prev) = (tp, fp);
+        let score = points[i].0;
+        while i < points.len() && points[i].0 == score {
+            tp += points[i].1;
+            fp += points[i].2;
+            i += 1;
+        }
+        area += match curve {
+            Curve::Roc => (fp - fp_prev) * (tp + tp_prev) / 2.0,
+            Curve::PrecisionRecall => delta_pr_area(fp_prev, fp, tp_prev, tp, total_pos),
+        };
+    }
+    Some(match curve {
+        Curve::Roc => area / (total_pos * total_neg),
+        Curve::PrecisionRecall => area,
+    })
+}
+
+/// Area under the precision-recall curve between two thresholds, interpolating precision non-linearly between
+/// them (Davis and Goadrich, 2006) as XGBoost does.
+fn delta_pr_area(fp_prev: f64, fp: f64, tp_prev: f64, tp: f64, total_pos: f64) -> f64 {
+    if tp == tp_prev {
+        return 0.0;
+    }
+    let h = (fp - fp_prev) / (tp - tp_prev);
+    let a = 1.0 + h;
+    let b = (fp_prev - h * tp_prev) / total_pos;
+    if b != 0.0 {
+        (tp / total_pos
+            - tp_prev / total_pos
+            - b / a * ((a * tp / total_pos + b).ln() - (a * tp_prev / total_pos + b).ln()))
+            / a
+    } else {
+        (tp - tp_prev) / total_pos / a
+    }
+}
+
+/// Fraction of pairs of rows with different labels that are ordered correctly, counting ties as half, or `None` if
+/// all labels are equal.
+fn pairwise_auc(predictions: &[f32], labels: &[f32]) -> Option<f64> {
+    let (mut correct, mut pairs) = (0.0, 0.0);
+    for i in 0..labels.len() {
+        for j in 0..labels.len() {
+            if labels[i] > labels[j] {
+                pairs += 1.0;
+                if predictions[i] > predictions[j] {
+                    correct += 1.0;
+                } else if predictions[i] == predictions[j] {
+                    correct += 0.5;
+                }
+            }
+        }
+    }
+    (pairs > 0.0).then(|| correct / pairs)
+}
+
+#[derive(Clone, Copy)]
+enum Ranking {
+    Ndcg,
+    Map,
+}
+
+/// Ranking metric averaged over query groups (weighted by group weights), considering the top `cut` rows of each
+/// group by prediction. Groups without any relevant rows score 0 if `minus`, and 1 otherwise.
+fn ranking(metric: Ranking, cut: Option<u32>, minus: bool, predictions: &[f32], data: &EvalData) -> XGBResult<f64> {
+    check_num_predictions(predictions, data.num_rows())?;
+    let groups = data.groups()?;
+    let weights = data.group_weights(groups.len())?;
+    let (mut sum, mut weight_sum) = (0.0, 0.0);
+    for ((start, end), w) in groups.iter().zip(weights) {
+        let labels = &data.labels[*start..*end];
+        let predictions = &predictions[*start..*end];
+        let mut order: Vec<usize> = (0..labels.len()).collect();
+        // stable, so tied predictions keep their row order
+        order.sort_by(|a, b| predictions[*b].total_cmp(&predictions[*a]));
+        let cut = cut.map_or(labels.len(), |k| (k as usize).min(labels.len()));
+        let score = match metric {
+            Ranking::Ndcg => {
+                let mut ideal: Vec<f32> = labels.to_vec();
+                ideal.sort_by(|a, b| b.total_cmp(a));
+                let idcg = dcg(ideal.iter().take(cut));
+                if idcg == 0.0 {
+                    None
+                } else {
+                    Some(dcg(order.iter().take(cut).map(|i| &labels[*i])) / idcg)
+                }
+            }
+            Ranking::Map => {
+                let num_relevant = labels.iter().filter(|label| **label > 0.0).count();
+                let (mut hits, mut precision_sum) = (0, 0.0);
+                for (rank, i) in order.iter().take(cut).enumerate() {
+                    if labels[*i] > 0.0 {
+                        hits += 1;
+                        precision_sum += hits as f64 / (rank + 1) as f64;
+                    }
+                }
+                (num_relevant > 0).then(|| precision_sum / num_relevant as f64)
+            }
+        };
+        sum += w * score.unwrap_or(if minus { 0.0 } else { 1.0 });
+        weight_sum += w;
+    }
+    Ok(sum / weight_sum)
+}
+
+/// Discounted cumulative gain of labels in ranked order, with exponential gain.
+fn dcg<'b, I: Iterator<Item = &'b f32>>(labels: I) -> f64 {
+    labels
+        .enumerate()
+        .map(|(rank, label)| (2f64.powf(*label as f64) - 1.0) / (rank as f64 + 2.0).log2())
+        .sum()
+}
+
+/// Negative partial log likelihood of Cox proportional hazards, with predictions as hazard ratios and labels as
+/// survival times (negative for censored rows).
+fn cox_log_loss(predictions: &[f32], data: &EvalData) -> XGBResult<f64> {
+    check_num_predictions(predictions, data.num_rows())?;
+    let labels = data.labels;
+    let mut order: Vec<usize> = (0..labels.len()).collect();
+    order.sort_by(|a, b| labels[*a].abs().total_cmp(&labels[*b].abs()));
+
+    // sum of hazard ratios of rows still at risk
+    let mut at_risk: f64 = predictions.iter().map(|p| *p as f64).sum();
+    let (mut loss, mut accumulated, mut num_events) = (0.0, 0.0, 0);
+    for (i, row) in order.iter().enumerate() {
+        let label = labels[*row];
+        if label > 0.0 {
+            loss -= (predictions[*row] as f64).ln() - at_risk.ln();
+            num_events += 1;
+        }
+        accumulated += predictions[*row] as f64;
+        // only remove rows from the risk set once time moves forward
+        if i == order.len() - 1 || label.abs() < labels[order[i + 1]].abs() {
+            at_risk -= accumulated;
+            accumulated = 0.0;
+        }
+    }
+    Ok(loss / num_events as f64)
+}
+
+/// Natural logarithm of the gamma function, using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        return PI.ln() - (PI * x).sin().abs().ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::learning::{LearningTaskParametersBuilder, Metrics, Objective};
+    use crate::testing;
+    use crate::DMatrix;
+
+    fn eval(metric: EvaluationMetric, predictions: &[f32], data: &EvalData) -> f32 {
+        evaluate(&metric, predictions, data).unwrap()
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn regression() {
+        let labels = [1.0, 2.0, 3.0, 4.0];
+        let predictions = [1.5, 2.0, 2.0, 5.0];
+        let data = EvalData::new(&labels);
+        assert_close(
+            eval(EvaluationMetric::RMSE, &predictions, &data),
+            (2.25f32 / 4.0).sqrt(),
+        );
+        assert_close(eval(EvaluationMetric::MAE, &predictions, &data), 2.5 / 4.0);
+
+        let weights = [1.0, 1.0, 2.0, 0.0];
+        let data = data.with_weights(&weights);
+        assert_close(
+            eval(EvaluationMetric::RMSE, &predictions, &data),
+            (2.25f32 / 4.0).sqrt(),
+        );
+        assert_close(eval(EvaluationMetric::MAE, &predictions, &data), 2.5 / 4.0);
+
+        assert!(evaluate(&EvaluationMetric::RMSE, &predictions[..3], &data).is_err());
+        assert!(
+            evaluate(
+                &EvaluationMetric::RMSE,
+                &predictions,
+                &EvalData::new(&labels).with_weights(&[1.0])
+            )
+            .is_err()
+        );
+        assert!(evaluate(&EvaluationMetric::RMSE, &[], &EvalData::new(&[])).is_err());
+    }
+
+    #[test]
+    fn count_and_positive_regression() {
+        let labels = [0.0, 1.0, 3.0];
+        let predictions = [0.5, 1.0, 2.0];
+        let data = EvalData::new(&labels);
+        // ln(y!) + p - y ln(p)
+        let poisson = (0.5 + (1.0 - 0.0) + (6f64.ln() + 2.0 - 3.0 * 2f64.ln())) / 3.0;
+        assert_close(
+            eval(EvaluationMetric::PoissonLogLoss, &predictions, &data),
+            poisson as f32,
+        );
+
+        let labels = [1.0, 2.0, 4.0];
+        let data = EvalData::new(&labels);
+        let gamma = (2.0 + 0.5f64.ln() + 2.0 + 4.0 / 2.0 + 2f64.ln()) / 3.0;
+        assert_close(eval(EvaluationMetric::GammaLogLoss, &predictions, &data), gamma as f32);
+        let deviance = 2.0 * (1.0 - 2f64.ln());
+        assert_close(
+            eval(EvaluationMetric::GammaDeviance, &predictions, &data),
+            deviance as f32,
+        );
+
+        let tweedie = |rho: f64| {
+            labels
+                .iter()
+                .zip(&predictions)
+                .map(|(y, p)| {
+                    let (y, p) = (*y as f64, *p as f64);
+                    -y * p.powf(1.0 - rho) / (1.0 - rho) + p.powf(2.0 - rho) / (2.0 - rho)
+                })
+                .sum::<f64>()
+                / 3.0
+        };
+        assert_close(
+            eval(EvaluationMetric::TweedieLogLoss, &predictions, &data),
+            tweedie(1.5) as f32,
+        );
+        let data = data.with_tweedie_variance_power(1.2);
+        assert_close(
+            eval(EvaluationMetric::TweedieLogLoss, &predictions, &data),
+            tweedie(1.2) as f32,
+        );
+    }
+
+    #[test]
+    fn binary_classification() {
+        let labels = [1.0, 0.0, 1.0, 0.0, 1.0];
+        let predictions = [0.9, 0.2, 0.4, 0.6, 0.0];
+        let data = EvalData::new(&labels);
+        let log_loss = -(0.9f64.ln() + 0.8f64.ln() + 0.4f64.ln() + 0.4f64.ln() + 1e-16f64.ln()) / 5.0;
+        assert_close(eval(EvaluationMetric::LogLoss, &predictions, &data), log_loss as f32);
+        assert_close(eval(EvaluationMetric::BinaryError, &predictions, &data), 0.6);
+        assert_close(eval(EvaluationMetric::BinaryErrorRate(0.3), &predictions, &data), 0.4);
+        assert_close(eval(EvaluationMetric::BinaryErrorRate(0.1), &predictions, &data), 0.6);
+    }
+
+    #[test]
+    fn auc() {
+        let labels = [1.0, 0.0, 1.0, 0.0];
+        let data = EvalData::new(&labels);
+        assert_eq!(eval(EvaluationMetric::AUC, &[0.9, 0.2, 0.4, 0.6], &data), 0.75);
+        assert_eq!(eval(EvaluationMetric::AUC, &[0.9, 0.1, 0.8, 0.2], &data), 1.0);
+        // ties count as half
+        assert_eq!(eval(EvaluationMetric::AUC, &[0.5, 0.5, 0.5, 0.5], &data), 0.5);
+        assert!(eval(EvaluationMetric::AUC, &[0.5, 0.5], &EvalData::new(&[1.0, 1.0])).is_nan());
+
+        // weights count as duplicated rows
+        let weighted = eval(
+            EvaluationMetric::AUC,
+            &[0.9, 0.2, 0.4, 0.6],
+            &data.with_weights(&[1.0, 1.0, 2.0, 1.0]),
+        );
+        let duplicated = eval(
+            EvaluationMetric::AUC,
+            &[0.9, 0.2, 0.4, 0.4, 0.6],
+            &EvalData::new(&[1.0, 0.0, 1.0, 1.0, 0.0]),
+        );
+        assert_close(weighted, duplicated);
+
+        // groups are averaged, skipping groups with a single label
+        let labels = [1.0, 0.0, 1.0, 0.0, 1.0, 1.0];
+        let predictions = [0.9, 0.2, 0.4, 0.6, 0.1, 0.2];
+        let group_ptr = [0, 2, 4, 6];
+        let data = EvalData::new(&labels).with_group_ptr(&group_ptr);
+        assert_eq!(eval(EvaluationMetric::AUC, &predictions, &data), 0.5);
+        assert_eq!(
+            eval(
+                EvaluationMetric::AUC,
+                &[0.1, 0.2, 0.3],
+                &EvalData::new(&[2.0, 1.0, 0.0]).with_group_ptr(&[0, 3])
+            ),
+            0.0
+        );
+
+        // one-vs-rest weighted by class prevalence
+        let labels = [0.0, 1.0, 2.0, 2.0];
+        let predictions = [
+            0.8, 0.1, 0.1, //
+            0.3, 0.4, 0.6, //
+            0.2, 0.3, 0.5, //
+            0.1, 0.1, 0.8,
+        ];
+        let data = EvalData::new(&labels);
+        // classes 0 and 1 are perfectly ranked, class 2 has one of its rows ranked below class 1's row
+        let expected = (1.0 + 1.0 + 2.0 * 0.75) / 4.0;
+        assert_close(eval(EvaluationMetric::AUC, &predictions, &data), expected);
+    }
+
+    #[test]
+    fn aucpr() {
+        let labels = [1.0, 0.0, 1.0, 0.0];
+        let data = EvalData::new(&labels);
+        assert_close(eval(EvaluationMetric::AUCPR, &[0.9, 0.1, 0.8, 0.2], &data), 1.0);
+
+        // precision 1 at recall 0.5, then a false positive before the second true positive
+        let area = eval(EvaluationMetric::AUCPR, &[0.9, 0.2, 0.4, 0.6], &data);
+        assert_close(area, (1.0 - 0.5 * 1.5f64.ln()) as f32);
+        assert!(eval(EvaluationMetric::AUCPR, &[0.5], &EvalData::new(&[0.0])).is_nan());
+    }
+
+    #[test]
+    fn multiclass() {
+        let labels = [0.0, 1.0, 2.0];
+        let predictions = [
+            0.7, 0.2, 0.1, //
+            0.5, 0.4, 0.1, //
+            0.2, 0.2, 0.6,
+        ];
+        let data = EvalData::new(&labels);
+        assert_close(
+            eval(EvaluationMetric::MultiClassErrorRate, &predictions, &data),
+            1.0 / 3.0,
+        );
+        let log_loss = -(0.7f64.ln() + 0.4f64.ln() + 0.6f64.ln()) / 3.0;
+        assert_close(
+            eval(EvaluationMetric::MultiClassLogLoss, &predictions, &data),
+            log_loss as f32,
+        );
+
+        // multi:softmax outputs the class directly
+        assert_close(
+            eval(EvaluationMetric::MultiClassErrorRate, &[0.0, 0.0, 2.0], &data),
+            1.0 / 3.0,
+        );
+
+        assert!(evaluate(&EvaluationMetric::MultiClassLogLoss, &predictions[..8], &data).is_err());
+        assert!(
+            evaluate(
+                &EvaluationMetric::MultiClassLogLoss,
+                &predictions,
+                &EvalData::new(&[0.0, 3.0, 1.0])
+            )
+            .is_err()
+        );
+        assert!(
+            evaluate(
+                &EvaluationMetric::MultiClassLogLoss,
+                &predictions,
+                &EvalData::new(&[0.0, 0.5, 1.0])
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn ranking_metrics() {
+        let labels = [2.0, 0.0, 1.0, 0.0, 0.0];
+        let predictions = [0.1, 0.9, 0.5, 0.3, 0.2];
+        let group_ptr = [0, 3, 5];
+        let data = EvalData::new(&labels).with_group_ptr(&group_ptr);
+
+        // group 0 is ranked [0, 1, 2] by labels [0, 1, 2] ideally, but as [1, 2, 0]
+        let dcg = 1.0 / 3f64.log2() + 3.0 / 4f64.log2();
+        let idcg = 3.0 + 1.0 / 3f64.log2();
+        // group 1 has no relevant rows
+        assert_close(
+            eval(EvaluationMetric::NDCG, &predictions, &data),
+            ((dcg / idcg + 1.0) / 2.0) as f32,
+        );
+        assert_close(
+            eval(EvaluationMetric::NDCGNegative, &predictions, &data),
+            ((dcg / idcg) / 2.0) as f32,
+        );
+        let dcg_1 = 0.0;
+        assert_close(
+            eval(EvaluationMetric::NDCGCut(1), &predictions, &data),
+            ((dcg_1 / 3.0 + 1.0) / 2.0) as f32,
+        );
+        let dcg_2 = 1.0 / 3f64.log2();
+        assert_close(
+            eval(EvaluationMetric::NDCGCutNegative(2), &predictions, &data),
+            (dcg_2 / idcg / 2.0) as f32,
+        );
+
+        // relevant rows at ranks 2 and 3
+        let ap = (1.0 / 2.0 + 2.0 / 3.0) / 2.0;
+        assert_close(
+            eval(EvaluationMetric::MAP, &predictions, &data),
+            ((ap + 1.0) / 2.0) as f32,
+        );
+        assert_close(
+            eval(EvaluationMetric::MAPNegative, &predictions, &data),
+            (ap / 2.0) as f32,
+        );
+        assert_close(
+            eval(EvaluationMetric::MAPCut(2), &predictions, &data),
+            ((0.25 + 1.0) / 2.0) as f32,
+        );
+        assert_close(eval(EvaluationMetric::MAPCutNegative(2), &predictions, &data), 0.125);
+
+        // weights are per group
+        let weights = [3.0, 1.0];
+        let data = data.with_weights(&weights);
+        assert_close(
+            eval(EvaluationMetric::MAPNegative, &predictions, &data),
+            (ap * 3.0 / 4.0) as f32,
+        );
+        assert!(evaluate(&EvaluationMetric::MAP, &predictions, &data.with_weights(&labels)).is_err());
+        assert!(evaluate(&EvaluationMetric::MAP, &predictions, &data.with_group_ptr(&[0, 2])).is_err());
+    }
+
+    #[test]
+    fn cox() {
+        // times 1, 2 (censored), 3, 3
+        let labels = [1.0, -2.0, 3.0, 3.0];
+        let predictions = [2.0, 1.0, 1.0, 0.5];
+        let data = EvalData::new(&labels);
+        let expected = -((2.0f64 / 4.5).ln() + (1.0f64 / 1.5).ln() + (0.5f64 / 1.5).ln()) / 3.0;
+        assert_close(eval(EvaluationMetric::CoxLogLoss, &predictions, &data), expected as f32);
+    }
+
+    #[test]
+    fn gamma_function() {
+        assert!(ln_gamma(1.0).abs() < 1e-12);
+        assert!(ln_gamma(2.0).abs() < 1e-12);
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - PI.sqrt().ln()).abs() < 1e-12);
+        assert!((ln_gamma(101.0) - (1..=100).map(|i| (i as f64).ln()).sum::<f64>()).abs() < 1e-9);
+    }
+
+    /// Train on `dtrain` with `objective` and check every metric matches XGBoost's own evaluation.
+    fn check_native(dtrain: &DMatrix, objective: Objective, metrics: Vec<EvaluationMetric>, data: &EvalData) {
+        let learning_params = LearningTaskParametersBuilder::default()
+            .objective(objective)
+            .eval_metrics(Metrics::Custom(metrics.clone()))
+            .build()
+            .unwrap();
+        let booster = testing::train_with(dtrain, learning_params, 5);
+        let native = booster.evaluate(dtrain).unwrap();
+        let predictions = booster.predict(dtrain).unwrap();
+        for metric in &metrics {
+            // some metrics are reported with their parameters, e.g. tweedie-nloglik@1.5
+            let name = metric.to_string();
+            let (_, expected) = native
+                .iter()
+                .find(|(key, _)| **key == name || key.starts_with(&format!("{}@", name)))
+                .unwrap();
+            let actual = evaluate(metric, &predictions, data).unwrap();
+            assert!(
+                (actual - expected).abs() <= 1e-4 * expected.abs().max(1.0),
+                "{}: {} != {}",
+                metric,
+                actual,
+                expected
+            );
+        }
+    }
+
+    fn features(num_rows: usize) -> Vec<f32> {
+        (0..num_rows * 3).map(|i| ((i * 7) % 11) as f32).collect()
+    }
+
+    #[test]
+    fn matches_native_binary() {
+        let x = features(100);
+        let labels: Vec<f32> = x.chunks(3).map(|row| (row[0] + row[1] > 10.0) as u8 as f32).collect();
+        let weights: Vec<f32> = (0..100).map(|i| 1.0 + (i % 3) as f32).collect();
+        let mut dtrain = DMatrix::from_dense(&x, 100).unwrap();
+        dtrain.set_labels(&labels).unwrap();
+        dtrain.set_weights(&weights).unwrap();
+        let data = EvalData::new(&labels).with_weights(&weights);
+        let metrics = vec![
+            EvaluationMetric::LogLoss,
+            EvaluationMetric::BinaryError,
+            EvaluationMetric::BinaryErrorRate(0.7),
+            EvaluationMetric::AUC,
+            EvaluationMetric::AUCPR,
+            EvaluationMetric::RMSE,
+            EvaluationMetric::MAE,
+        ];
+        check_native(&dtrain, Objective::BinaryLogistic, metrics, &data);
+    }
+
+    #[test]
+    fn matches_native_multiclass() {
+        let x = features(90);
+        let labels: Vec<f32> = x.chunks(3).map(|row| (row[0] as u32 % 3) as f32).collect();
+        let mut dtrain = DMatrix::from_dense(&x, 90).unwrap();
+        dtrain.set_labels(&labels).unwrap();
+        let metrics = vec![
+            EvaluationMetric::MultiClassLogLoss,
+            EvaluationMetric::MultiClassErrorRate,
+            EvaluationMetric::AUC,
+        ];
+        check_native(&dtrain, Objective::MultiSoftprob(3), metrics, &EvalData::new(&labels));
+    }
+
+    #[test]
+    fn matches_native_count() {
+        let x = features(100);
+        let labels: Vec<f32> = x.chunks(3).map(|row| 1.0 + (row[0] + row[2]) / 4.0).collect();
+        let mut dtrain = DMatrix::from_dense(&x, 100).unwrap();
+        dtrain.set_labels(&labels).unwrap();
+        let data = EvalData::new(&labels);
+        check_native(
+            &dtrain,
+            Objective::CountPoisson,
+            vec![EvaluationMetric::PoissonLogLoss],
+            &data,
+        );
+        let metrics = vec![EvaluationMetric::GammaLogLoss, EvaluationMetric::GammaDeviance];
+        check_native(&dtrain, Objective::RegGamma, metrics, &data);
+        let metrics = vec![EvaluationMetric::TweedieLogLoss];
+        check_native(&dtrain, Objective::RegTweedie(None), metrics, &data);
+    }
+
+    #[test]
+    fn matches_native_ranking() {
+        let x = features(60);
+        let mut labels: Vec<f32> = x.chunks(3).map(|row| (row[1] as u32 % 3) as f32).collect();
+        // the last group has no relevant rows, where the `-` variants score 0 instead of 1
+        labels[50..].fill(0.0);
+        let group_ptr: Vec<u32> = (0..=6).map(|i| i * 10).collect();
+        let mut dtrain = DMatrix::from_dense(&x, 60).unwrap();
+        dtrain.set_labels(&labels).unwrap();
+        dtrain.set_group(&[10; 6]).unwrap();
+        let data = EvalData::new(&labels).with_group_ptr(&group_ptr);
+        let metrics = vec![
+            EvaluationMetric::NDCG,
+            EvaluationMetric::NDCGCut(5),
+            EvaluationMetric::MAP,
+            EvaluationMetric::MAPCut(3),
+            EvaluationMetric::NDCGNegative,
+            EvaluationMetric::NDCGCutNegative(5),
+            EvaluationMetric::MAPNegative,
+            EvaluationMetric::MAPCutNegative(3),
+        ];
+        check_native(&dtrain, Objective::RankPairwise, metrics, &data);
+    }
+
+    #[test]
+    fn matches_native_survival() {
+        let x = features(80);
+        // every fourth survival time is right censored
+        let labels: Vec<f32> = x
+            .chunks(3)
+            .enumerate()
+            .map(|(i, row)| {
+                let time = 1.0 + row[0] + row[2] / 2.0;
+                if i % 4 == 3 { -time } else { time }
+            })
+            .collect();
+        let mut dtrain = DMatrix::from_dense(&x, 80).unwrap();
+        dtrain.set_labels(&labels).unwrap();
+        let metrics = vec![EvaluationMetric::CoxLogLoss];
+        check_native(&dtrain, Objective::SurvivalCox, metrics, &EvalData::new(&labels));
+    }
+}
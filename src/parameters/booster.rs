@@ -21,6 +21,7 @@
 use std::default::Default;
 
 use super::{dart, linear, tree};
+use crate::{DMatrix, XGBResult};
 
 /// Type of booster to use when training a [Booster](../struct.Booster.html) model.
 #[derive(Clone)]
@@ -59,4 +60,12 @@ impl BoosterType {
             BoosterType::Dart(ref p) => p.as_string_pairs(),
         }
     }
+
+    /// Validate any feature constraints against the training matrix (if known), returning them in XGBoost's format.
+    pub(crate) fn resolve_constraints(&self, dtrain: Option<&DMatrix>) -> XGBResult<Vec<(String, String)>> {
+        match *self {
+            BoosterType::Tree(ref p) => p.resolve_constraints(dtrain),
+            BoosterType::Linear(_) | BoosterType::Dart(_) => Ok(Vec::new()),
+        }
+    }
 }
@@ -68,6 +68,13 @@ pub enum Objective {
     ///
     /// Set to `None` to use XGBoost's default (currently `1.5`).
     RegTweedie(Option<f32>),
+
+    /// Quantile regression, minimising the pinball loss of each of the
+    /// [`quantile_alpha`](struct.LearningTaskParametersBuilder.html#method.quantile_alpha) quantiles.
+    ///
+    /// Predicts one column per quantile, see
+    /// [`Booster::predict_multi_output`](../../struct.Booster.html#method.predict_multi_output).
+    RegQuantileError,
 }
 
 impl Copy for Objective {}
@@ -96,6 +103,7 @@ impl std::fmt::Display for Objective {
             Objective::RankPairwise => "rank:pairwise".to_owned(),
             Objective::RegGamma => "reg:gamma".to_owned(),
             Objective::RegTweedie(_) => "reg:tweedie".to_owned(),
+            Objective::RegQuantileError => "reg:quantileerror".to_owned(),
         };
         write!(f, "{}", result)
     }
@@ -142,6 +150,9 @@ pub enum EvaluationMetric {
     /// Area under the curve for ranking evaluation.
     AUC,
 
+    /// Area under the precision-recall curve.
+    AUCPR,
+
     /// Normalized Discounted Cumulative Gain.
     NDCG,
 
@@ -195,6 +206,7 @@ impl std::fmt::Display for EvaluationMetric {
             EvaluationMetric::MultiClassErrorRate => "merror".to_owned(),
             EvaluationMetric::MultiClassLogLoss => "mlogloss".to_owned(),
             EvaluationMetric::AUC => "auc".to_owned(),
+            EvaluationMetric::AUCPR => "aucpr".to_owned(),
             EvaluationMetric::NDCG => "ndcg".to_owned(),
             EvaluationMetric::NDCGCut(n) => format!("ndcg@{}", n),
             EvaluationMetric::NDCGNegative => "ndcg-".to_owned(),
@@ -236,6 +248,13 @@ pub struct LearningTaskParameters {
     /// *default*: [`Auto`](enum.Metrics.html#variant.Auto) (i.e. metrics selected automatically based on objective)
     pub(crate) eval_metrics: Metrics,
 
+    /// Quantiles to fit with the [`RegQuantileError`](enum.Objective.html#variant.RegQuantileError) objective.
+    ///
+    /// *range*: each in (0, 1)
+    ///
+    /// *default*: empty (i.e. XGBoost's default of the median, `[0.5]`)
+    quantile_alpha: Vec<f32>,
+
     /// Random seed.
     ///
     /// *default*: 0
@@ -248,6 +267,7 @@ impl Default for LearningTaskParameters {
             objective: Objective::default(),
             base_score: 0.5,
             eval_metrics: Metrics::Auto,
+            quantile_alpha: Vec::new(),
             seed: 0,
         }
     }
@@ -278,6 +298,14 @@ impl LearningTaskParameters {
         self.eval_metrics = eval_metrics.into();
     }
 
+    pub fn quantile_alpha(&self) -> &[f32] {
+        &self.quantile_alpha
+    }
+
+    pub fn set_quantile_alpha(&mut self, quantile_alpha: Vec<f32>) {
+        self.quantile_alpha = quantile_alpha;
+    }
+
     pub fn seed(&self) -> u64 {
         self.seed
     }
@@ -295,6 +323,11 @@ impl LearningTaskParameters {
             v.push(("num_class".to_owned(), n.to_string()));
         } else if let Objective::RegTweedie(Some(n)) = self.objective {
             v.push(("tweedie_variance_power".to_owned(), n.to_string()));
+        } else if let Objective::RegQuantileError = self.objective
+            && !self.quantile_alpha.is_empty()
+        {
+            let alphas: Vec<String> = self.quantile_alpha.iter().map(|alpha| alpha.to_string()).collect();
+            v.push(("quantile_alpha".to_owned(), format!("[{}]", alphas.join(","))));
         }
 
         v.push(("objective".to_owned(), self.objective.to_string()));
@@ -316,6 +349,11 @@ impl LearningTaskParametersBuilder {
         if let Some(Objective::RegTweedie(variance_power)) = self.objective {
             Interval::new_closed_closed(1.0, 2.0).validate(&variance_power, "tweedie_variance_power")?;
         }
+        if let Some(quantile_alpha) = &self.quantile_alpha {
+            for alpha in quantile_alpha {
+                Interval::new_open_open(0.0, 1.0).validate(&Some(*alpha), "quantile_alpha")?;
+            }
+        }
         Ok(())
     }
 }
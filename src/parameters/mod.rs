@@ -17,6 +17,7 @@ pub mod tree;
 pub use self::booster::BoosterType;
 use super::DMatrix;
 use super::booster::CustomObjective;
+use crate::CheckpointConfig;
 
 /// Parameters for training boosters.
 /// Created using [`BoosterParametersBuilder`](struct.BoosterParametersBuilder.html).
@@ -170,6 +171,12 @@ pub struct TrainingParameters<'a> {
     /// *default*: `None`
     #[builder(default = "None")]
     pub(crate) callbacks: Option<Vec<TrainingCallback>>,
+
+    /// Optional config for saving checkpoints during training, and resuming from the latest one.
+    ///
+    /// *default*: `None`
+    #[builder(default = "None")]
+    pub(crate) checkpoint: Option<CheckpointConfig>,
 }
 
 impl<'a> TrainingParameters<'a> {
@@ -228,6 +235,14 @@ impl<'a> TrainingParameters<'a> {
     pub fn set_callbacks(&mut self, callbacks: Option<Vec<TrainingCallback>>) {
         self.callbacks = callbacks;
     }
+
+    pub fn checkpoint(&self) -> Option<&CheckpointConfig> {
+        self.checkpoint.as_ref()
+    }
+
+    pub fn set_checkpoint(&mut self, checkpoint: Option<CheckpointConfig>) {
+        self.checkpoint = checkpoint;
+    }
 }
 
 enum Inclusion {
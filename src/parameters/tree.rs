@@ -4,6 +4,7 @@
 use std::default::Default;
 
 use super::Interval;
+use crate::{DMatrix, XGBError, XGBResult};
 
 /// The tree construction algorithm used in XGBoost (see description in the
 /// [reference paper](http://arxiv.org/abs/1603.02754)).
@@ -186,6 +187,110 @@ impl std::fmt::Display for Predictor {
     }
 }
 
+/// Strategy used for training multi-target models (multi-output regression, multi-class classification, etc.).
+#[derive(Clone, Default)]
+pub enum MultiStrategy {
+    /// Build one model (i.e. one tree per boosting round) for each target.
+    #[default]
+    OneOutputPerTree,
+
+    /// Build a single tree per boosting round whose leaves hold a vector with a value for every target.
+    /// Currently only supported by the `TreeMethod::Hist` tree method.
+    MultiOutputTree,
+}
+
+impl std::fmt::Display for MultiStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = match *self {
+            MultiStrategy::OneOutputPerTree => "one_output_per_tree".to_owned(),
+            MultiStrategy::MultiOutputTree => "multi_output_tree".to_owned(),
+        };
+        write!(f, "{}", result)
+    }
+}
+
+/// Direction of a monotone constraint placed on a single feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Monotonicity {
+    /// Predictions may not increase as the feature value increases.
+    Decreasing,
+
+    /// No constraint is placed on the feature.
+    #[default]
+    Unconstrained,
+
+    /// Predictions may not decrease as the feature value increases.
+    Increasing,
+}
+
+impl std::fmt::Display for Monotonicity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = match *self {
+            Monotonicity::Decreasing => "-1",
+            Monotonicity::Unconstrained => "0",
+            Monotonicity::Increasing => "1",
+        };
+        write!(f, "{}", result)
+    }
+}
+
+/// Monotone constraints on the relationship between features and predictions.
+///
+/// See the XGBoost [monotonic constraints](https://xgboost.readthedocs.io/en/latest/tutorials/monotonic.html)
+/// tutorial for details.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MonotoneConstraints {
+    /// One constraint per feature, in column order. Columns beyond the end of the list are unconstrained.
+    ByIndex(Vec<Monotonicity>),
+
+    /// Constraints for named features, any feature not listed is unconstrained.
+    ///
+    /// Names are resolved against the feature names of the training matrix, so can only be used with
+    /// [`Booster::train`](../../struct.Booster.html#method.train) or
+    /// [`Booster::new_with_cached_dmats`](../../struct.Booster.html#method.new_with_cached_dmats).
+    ByName(Vec<(String, Monotonicity)>),
+}
+
+/// Sets of features that are allowed to interact with each other within a tree.
+///
+/// See the XGBoost [feature interaction constraints](https://xgboost.readthedocs.io/en/latest/tutorials/feature_interaction_constraint.html)
+/// tutorial for details.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InteractionConstraints {
+    /// Groups of column indices which may interact.
+    ByIndex(Vec<Vec<usize>>),
+
+    /// Groups of feature names which may interact.
+    ///
+    /// Names are resolved against the feature names of the training matrix, so can only be used with
+    /// [`Booster::train`](../../struct.Booster.html#method.train) or
+    /// [`Booster::new_with_cached_dmats`](../../struct.Booster.html#method.new_with_cached_dmats).
+    ByName(Vec<Vec<String>>),
+}
+
+fn format_monotone_constraints(constraints: &[Monotonicity]) -> String {
+    let values: Vec<String> = constraints.iter().map(|c| c.to_string()).collect();
+    format!("({})", values.join(","))
+}
+
+fn format_interaction_constraints(groups: &[Vec<usize>]) -> String {
+    let groups: Vec<String> = groups
+        .iter()
+        .map(|group| {
+            let indices: Vec<String> = group.iter().map(|i| i.to_string()).collect();
+            format!("[{}]", indices.join(","))
+        })
+        .collect();
+    format!("[{}]", groups.join(","))
+}
+
+fn feature_index(feature_names: &[String], name: &str, param: &str) -> Result<usize, String> {
+    feature_names
+        .iter()
+        .position(|n| n == name)
+        .ok_or_else(|| format!("Invalid value for '{}' parameter, unknown feature '{}'.", param, name))
+}
+
 /// BoosterParameters for Tree Booster. Create using
 /// [`TreeBoosterParametersBuilder`](struct.TreeBoosterParametersBuilder.html).
 #[derive(Builder, Clone)]
@@ -198,21 +303,21 @@ pub struct TreeBoosterParameters {
     ///
     /// * range: [0.0, 1.0]
     /// * default: 0.3
-    eta: f32,
+    pub(crate) eta: f32,
 
     /// Minimum loss reduction required to make a further partition on a leaf node of the tree.
     /// The larger, the more conservative the algorithm will be.
     ///
     /// * range: [0,∞]
     /// * default: 0
-    gamma: f32,
+    pub(crate) gamma: f32,
 
     /// Maximum depth of a tree, increase this value will make the model more complex / likely to be overfitting.
     /// 0 indicates no limit, limit is required for depth-wise grow policy.
     ///
     /// * range: [0,∞]
     /// * default: 6
-    max_depth: u32,
+    pub(crate) max_depth: u32,
 
     /// Minimum sum of instance weight (hessian) needed in a child. If the tree partition step results in a leaf
     /// node with the sum of instance weight less than min_child_weight, then the building process will give up
@@ -222,7 +327,7 @@ pub struct TreeBoosterParameters {
     ///
     /// * range: [0,∞]
     /// * default: 1
-    min_child_weight: f32,
+    pub(crate) min_child_weight: f32,
 
     /// Maximum delta step we allow each tree’s weight estimation to be.
     /// If the value is set to 0, it means there is no constraint. If it is set to a positive value,
@@ -232,42 +337,42 @@ pub struct TreeBoosterParameters {
     ///
     /// * range: [0,∞]
     /// * default: 0
-    max_delta_step: f32,
+    pub(crate) max_delta_step: f32,
 
     /// Subsample ratio of the training instance. Setting it to 0.5 means that XGBoost randomly collected half
     /// of the data instances to grow trees and this will prevent overfitting.
     ///
     /// * range: (0, 1]
     /// * default: 1.0
-    subsample: f32,
+    pub(crate) subsample: f32,
 
     /// Subsample ratio of columns when constructing each tree.
     ///
     /// * range: (0.0, 1.0]
     /// * default: 1.0
-    colsample_bytree: f32,
+    pub(crate) colsample_bytree: f32,
 
     /// Subsample ratio of columns for each split, in each level.
     ///
     /// * range: (0.0, 1.0]
     /// * default: 1.0
-    colsample_bylevel: f32,
+    pub(crate) colsample_bylevel: f32,
 
     /// Subsample ratio of columns for each node.
     ///
     /// * range: (0.0, 1.0]
     /// * default: 1.0
-    colsample_bynode: f32,
+    pub(crate) colsample_bynode: f32,
 
     /// L2 regularization term on weights, increase this value will make model more conservative.
     ///
     /// * default: 1
-    lambda: f32,
+    pub(crate) lambda: f32,
 
     /// L1 regularization term on weights, increase this value will make model more conservative.
     ///
     /// * default: 0
-    alpha: f32,
+    pub(crate) alpha: f32,
 
     /// The tree construction algorithm used in XGBoost.
     #[builder(default = "TreeMethod::default()")]
@@ -286,7 +391,7 @@ pub struct TreeBoosterParameters {
     /// A typical value to consider: sum(negative cases) / sum(positive cases).
     ///
     /// default: 1.0
-    scale_pos_weight: f32,
+    pub(crate) scale_pos_weight: f32,
 
     /// Sequence of tree updaters to run, providing a modular way to construct and to modify the trees.
     ///
@@ -311,14 +416,14 @@ pub struct TreeBoosterParameters {
     /// policy.
     ///
     /// * default: 0
-    max_leaves: u32,
+    pub(crate) max_leaves: u32,
 
     /// This is only used if 'hist' is specified as tree_method.
     /// Maximum number of discrete bins to bucket continuous features.
     /// Increasing this number improves the optimality of splits at the cost of higher computation time.
     ///
     /// * default: 256
-    max_bin: u32,
+    pub(crate) max_bin: u32,
 
     /// Number of trees to train in parallel for boosted random forest.
     ///
@@ -329,6 +434,27 @@ pub struct TreeBoosterParameters {
     ///
     /// * default: [`Predictor::Cpu`](enum.Predictor.html#variant.Cpu)
     predictor: Predictor,
+
+    /// Strategy used for training multi-target models.
+    ///
+    /// * default: [`MultiStrategy::OneOutputPerTree`](enum.MultiStrategy.html#variant.OneOutputPerTree)
+    multi_strategy: MultiStrategy,
+
+    /// Monotone constraints on features, validated against the training matrix by
+    /// [`Booster::train`](../../struct.Booster.html#method.train) and
+    /// [`Booster::new_with_cached_dmats`](../../struct.Booster.html#method.new_with_cached_dmats) (taking the
+    /// first matrix as the training matrix).
+    ///
+    /// * default: None
+    monotone_constraints: Option<MonotoneConstraints>,
+
+    /// Feature interaction constraints, validated against the training matrix by
+    /// [`Booster::train`](../../struct.Booster.html#method.train) and
+    /// [`Booster::new_with_cached_dmats`](../../struct.Booster.html#method.new_with_cached_dmats) (taking the
+    /// first matrix as the training matrix).
+    ///
+    /// * default: None
+    interaction_constraints: Option<InteractionConstraints>,
 }
 
 impl Default for TreeBoosterParameters {
@@ -356,6 +482,9 @@ impl Default for TreeBoosterParameters {
             max_bin: 256,
             num_parallel_tree: 1,
             predictor: Predictor::default(),
+            multi_strategy: MultiStrategy::default(),
+            monotone_constraints: None,
+            interaction_constraints: None,
         }
     }
 }
@@ -385,6 +514,7 @@ impl TreeBoosterParameters {
             ("max_bin".to_owned(), self.max_bin.to_string()),
             ("num_parallel_tree".to_owned(), self.num_parallel_tree.to_string()),
             ("predictor".to_owned(), self.predictor.to_string()),
+            ("multi_strategy".to_owned(), self.multi_strategy.to_string()),
         ];
 
         // Don't pass anything to XGBoost if the user didn't specify anything.
@@ -404,6 +534,107 @@ impl TreeBoosterParameters {
 
         v
     }
+
+    /// Validate monotone and interaction constraints against the training matrix, resolving any feature
+    /// names to column indices. Without a training matrix, constraints by index are passed on unchecked and
+    /// constraints by name are an error.
+    pub(crate) fn resolve_constraints(&self, dtrain: Option<&DMatrix>) -> XGBResult<Vec<(String, String)>> {
+        if self.monotone_constraints.is_none() && self.interaction_constraints.is_none() {
+            return Ok(Vec::new());
+        }
+        let (num_cols, feature_names) = match dtrain {
+            Some(dtrain) => (dtrain.num_cols(), dtrain.get_feature_names()?),
+            None => (0, Vec::new()),
+        };
+        self.constraint_string_pairs(num_cols, &feature_names)
+            .map_err(XGBError::new)
+    }
+
+    fn constraint_string_pairs(
+        &self,
+        num_cols: usize,
+        feature_names: &[String],
+    ) -> Result<Vec<(String, String)>, String> {
+        let mut v = Vec::new();
+
+        let by_name_error = |param: &str| {
+            format!(
+                "Invalid value for '{}' parameter, constraints given by name require a training matrix with \
+                 feature names.",
+                param
+            )
+        };
+
+        match self.monotone_constraints {
+            Some(MonotoneConstraints::ByIndex(ref constraints)) => {
+                // a column count of 0 means it's unknown (e.g. inferred from CSR data), so can't be checked
+                if num_cols > 0 && constraints.len() > num_cols {
+                    return Err(format!(
+                        "Invalid value for 'monotone_constraints' parameter, got {} constraints but training \
+                         matrix has {} columns.",
+                        constraints.len(),
+                        num_cols
+                    ));
+                }
+                v.push((
+                    "monotone_constraints".to_owned(),
+                    format_monotone_constraints(constraints),
+                ));
+            }
+            Some(MonotoneConstraints::ByName(ref named)) => {
+                if feature_names.is_empty() {
+                    return Err(by_name_error("monotone_constraints"));
+                }
+                let mut constraints = vec![Monotonicity::Unconstrained; feature_names.len()];
+                for (name, monotonicity) in named {
+                    constraints[feature_index(feature_names, name, "monotone_constraints")?] = *monotonicity;
+                }
+                v.push((
+                    "monotone_constraints".to_owned(),
+                    format_monotone_constraints(&constraints),
+                ));
+            }
+            None => {}
+        }
+
+        match self.interaction_constraints {
+            Some(InteractionConstraints::ByIndex(ref groups)) => {
+                if num_cols > 0
+                    && let Some(index) = groups.iter().flatten().find(|i| **i >= num_cols)
+                {
+                    return Err(format!(
+                        "Invalid value for 'interaction_constraints' parameter, feature index {} is out of range \
+                         for training matrix with {} columns.",
+                        index, num_cols
+                    ));
+                }
+                v.push((
+                    "interaction_constraints".to_owned(),
+                    format_interaction_constraints(groups),
+                ));
+            }
+            Some(InteractionConstraints::ByName(ref named_groups)) => {
+                if feature_names.is_empty() {
+                    return Err(by_name_error("interaction_constraints"));
+                }
+                let mut groups = Vec::with_capacity(named_groups.len());
+                for named_group in named_groups {
+                    let group = named_group
+                        .iter()
+                        .map(|name| feature_index(feature_names, name, "interaction_constraints"))
+                        .collect::<Result<Vec<usize>, String>>()?;
+                    groups.push(group);
+                }
+                v.push((
+                    "interaction_constraints".to_owned(),
+                    format_interaction_constraints(&groups),
+                ));
+            }
+            None => {}
+        }
+
+        Ok(v)
+    }
 }
 
 impl TreeBoosterParametersBuilder {
@@ -429,4 +660,81 @@ mod tests {
         let p = TreeBoosterParametersBuilder::default().build().unwrap();
         assert_eq!(p.eta, 0.3);
     }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn constraints_by_index() {
+        let p = TreeBoosterParametersBuilder::default()
+            .monotone_constraints(Some(MonotoneConstraints::ByIndex(vec![
+                Monotonicity::Increasing,
+                Monotonicity::Unconstrained,
+                Monotonicity::Decreasing,
+            ])))
+            .interaction_constraints(Some(InteractionConstraints::ByIndex(vec![vec![0, 1], vec![2]])))
+            .build()
+            .unwrap();
+
+        // constraints are only passed to XGBoost once resolved, so they're set exactly once
+        let pairs = p.as_string_pairs();
+        assert!(
+            !pairs
+                .iter()
+                .any(|(k, _)| k == "monotone_constraints" || k == "interaction_constraints")
+        );
+
+        // without a training matrix, constraints by index are passed on unchecked
+        let resolved = p.resolve_constraints(None).unwrap();
+        assert_eq!(resolved, p.constraint_string_pairs(0, &[]).unwrap());
+        let resolved = p.constraint_string_pairs(3, &[]).unwrap();
+        assert_eq!(
+            resolved,
+            vec![
+                ("monotone_constraints".to_owned(), "(1,0,-1)".to_owned()),
+                ("interaction_constraints".to_owned(), "[[0,1],[2]]".to_owned()),
+            ]
+        );
+
+        // unknown number of columns can't be validated
+        assert!(p.constraint_string_pairs(0, &[]).is_ok());
+        assert!(p.constraint_string_pairs(2, &[]).is_err());
+    }
+
+    #[test]
+    fn constraints_by_name() {
+        let p = TreeBoosterParametersBuilder::default()
+            .monotone_constraints(Some(MonotoneConstraints::ByName(vec![(
+                "income".to_owned(),
+                Monotonicity::Increasing,
+            )])))
+            .interaction_constraints(Some(InteractionConstraints::ByName(vec![
+                names(&["age", "income"]),
+                names(&["tenure"]),
+            ])))
+            .build()
+            .unwrap();
+
+        // names can't be resolved without a training matrix
+        let err = p.resolve_constraints(None).unwrap_err();
+        assert!(err.to_string().contains("require a training matrix"), "{}", err);
+
+        let resolved = p
+            .constraint_string_pairs(3, &names(&["age", "income", "tenure"]))
+            .unwrap();
+        assert_eq!(
+            resolved,
+            vec![
+                ("monotone_constraints".to_owned(), "(0,1,0)".to_owned()),
+                ("interaction_constraints".to_owned(), "[[0,1],[2]]".to_owned()),
+            ]
+        );
+
+        assert!(p.constraint_string_pairs(3, &[]).is_err());
+        let err = p
+            .constraint_string_pairs(3, &names(&["age", "salary", "tenure"]))
+            .unwrap_err();
+        assert!(err.contains("unknown feature 'income'"), "{}", err);
+    }
 }
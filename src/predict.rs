@@ -0,0 +1,330 @@
+//! Configuration and results of predictions made with a [`Booster`](struct.Booster.html).
+use std::ops::Range;
+
+use crate::util::json_float;
+
+/// Type of output produced by a prediction.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredictType {
+    /// Transformed prediction values (e.g. probabilities for logistic objectives).
+    #[default]
+    Normal = 0,
+
+    /// Raw untransformed margin values.
+    OutputMargin = 1,
+
+    /// Feature contributions (SHAP values).
+    PredictContribitions = 2,
+
+    /// Approximate feature contributions.
+    PredictApproximateContributions = 3,
+
+    /// SHAP interaction values for each pair of features.
+    PredictFeatureInteractions = 4,
+
+    /// Approximate SHAP interaction values.
+    PredictApproximateFeatureInteractions = 5,
+
+    /// Leaf index of each tree for each sample.
+    PredictLeafTraining = 6,
+}
+
+/// Options controlling a prediction. Create using
+/// [`PredictConfigBuilder`](struct.PredictConfigBuilder.html).
+///
+/// ```
+/// use xgb::{PredictConfigBuilder, PredictType};
+///
+/// // margin output from the first 10 boosting rounds only
+/// let config = PredictConfigBuilder::default()
+///     .predict_type(PredictType::OutputMargin)
+///     .iteration_range(0..10)
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.iteration_range(), 0..10);
+/// ```
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+#[builder(default)]
+pub struct PredictConfig {
+    /// Type of output to predict.
+    ///
+    /// *default*: [`Normal`](enum.PredictType.html#variant.Normal)
+    predict_type: PredictType,
+
+    /// Whether the prediction is made as part of training (e.g. by a custom objective function). Only affects
+    /// models which behave differently during training, such as DART boosters dropping trees.
+    ///
+    /// *default*: `false`
+    training: bool,
+
+    /// Range of boosting rounds whose trees are used for the prediction. An end of 0 means all rounds are used.
+    ///
+    /// *default*: `0..0`
+    iteration_range: Range<u32>,
+
+    /// Always shape output as (rows, groups, ...), even for models with a single output group, instead of
+    /// dropping dimensions of size 1.
+    ///
+    /// *default*: `false`
+    strict_shape: bool,
+
+    /// Value treated as missing when predicting directly from dense data with
+    /// [`Booster::predict_inplace`](struct.Booster.html#method.predict_inplace).
+    ///
+    /// *default*: `NaN`
+    missing: f32,
+}
+
+impl Default for PredictConfig {
+    fn default() -> Self {
+        PredictConfig {
+            predict_type: PredictType::default(),
+            training: false,
+            iteration_range: 0..0,
+            strict_shape: false,
+            missing: f32::NAN,
+        }
+    }
+}
+
+impl PredictConfig {
+    /// Get the type of output to predict.
+    pub fn predict_type(&self) -> PredictType {
+        self.predict_type
+    }
+
+    /// Check whether the prediction is made as part of training.
+    pub fn training(&self) -> bool {
+        self.training
+    }
+
+    /// Get the range of boosting rounds used for the prediction.
+    pub fn iteration_range(&self) -> Range<u32> {
+        self.iteration_range.clone()
+    }
+
+    /// Check whether output is always shaped as (rows, groups, ...).
+    pub fn strict_shape(&self) -> bool {
+        self.strict_shape
+    }
+
+    /// Get the value treated as missing when predicting directly from dense data.
+    pub fn missing(&self) -> f32 {
+        self.missing
+    }
+
+    /// Returns 0 terminated JSON of the config, for usage with
+    /// [`Booster::predict_matrix`](struct.Booster.html#method.predict_matrix).
+    pub fn as_json(&self) -> String {
+        format!("{}\0", self.to_json())
+    }
+
+    /// JSON configuration accepted by `XGBoosterPredictFromDMatrix`.
+    pub(crate) fn to_json(&self) -> String {
+        format!(
+            r#"{{"type":{},"training":{},"iteration_begin":{},"iteration_end":{},"strict_shape":{}}}"#,
+            self.predict_type as usize,
+            self.training,
+            self.iteration_range.start,
+            self.iteration_range.end,
+            self.strict_shape
+        )
+    }
+
+    /// JSON configuration accepted by the inplace prediction functions, e.g. `XGBoosterPredictFromDense`.
+    pub(crate) fn to_inplace_json(&self) -> String {
+        format!(
+            r#"{{"type":{},"training":{},"iteration_begin":{},"iteration_end":{},"strict_shape":{},"missing":{},"cache_id":0}}"#,
+            self.predict_type as usize,
+            self.training,
+            self.iteration_range.start,
+            self.iteration_range.end,
+            self.strict_shape,
+            json_float(self.missing)
+        )
+    }
+}
+
+impl PredictConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(ref range) = self.iteration_range
+            && range.end != 0
+            && range.start > range.end
+        {
+            return Err(format!(
+                "Invalid value for 'iteration_range' parameter, start {} is greater than end {}.",
+                range.start, range.end
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Result of a prediction: a flat, row-major array of values along with the shape reported by XGBoost.
+///
+/// E.g. a multi-class model with `MultiSoftprob(3)` predicting 10 rows has shape `[10, 3]`, and contributions
+/// for a single-output model with 4 features have shape `[rows, 5]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Prediction {
+    data: Vec<f32>,
+    shape: Vec<usize>,
+}
+
+impl Prediction {
+    pub(crate) fn new(data: Vec<f32>, shape: Vec<usize>) -> Self {
+        Prediction { data, shape }
+    }
+
+    /// Get the predicted values as a flat, row-major slice.
+    pub fn data(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Get the shape of the predicted values, the first dimension is always the number of rows.
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Get the number of rows predicted.
+    pub fn num_rows(&self) -> usize {
+        self.shape.first().copied().unwrap_or(0)
+    }
+
+    /// Get the values predicted for row `i`, flattened if there are more than 2 dimensions.
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn row(&self, i: usize) -> &[f32] {
+        let row_len = self.shape.iter().skip(1).product::<usize>();
+        &self.data[i * row_len..(i + 1) * row_len]
+    }
+
+    /// Consume this prediction, returning the flat, row-major values.
+    pub fn into_vec(self) -> Vec<f32> {
+        self.data
+    }
+
+    /// Consume this prediction, returning the flat, row-major values and their shape.
+    pub fn into_parts(self) -> (Vec<f32>, Vec<usize>) {
+        (self.data, self.shape)
+    }
+}
+
+/// Prediction borrowing the output buffer owned by XGBoost, created by
+/// [`Booster::predict_view`](struct.Booster.html#method.predict_view).
+///
+/// XGBoost reuses the same buffer for every prediction made with a `Booster`, so a view is only valid until the
+/// next prediction. This is enforced by the view mutably borrowing the `Booster` it was created from; to keep
+/// values for longer, copy them with [`to_prediction`](#method.to_prediction) or `data().to_vec()`.
+#[derive(Debug)]
+pub struct PredictionView<'a> {
+    data: &'a [f32],
+    shape: &'a [u64],
+}
+
+impl<'a> PredictionView<'a> {
+    pub(crate) fn new(data: &'a [f32], shape: &'a [u64]) -> Self {
+        PredictionView { data, shape }
+    }
+
+    /// Get the predicted values as a flat, row-major slice.
+    pub fn data(&self) -> &'a [f32] {
+        self.data
+    }
+
+    /// Get the shape of the predicted values, the first dimension is always the number of rows.
+    pub fn shape(&self) -> &'a [u64] {
+        self.shape
+    }
+
+    /// Get the number of rows predicted.
+    pub fn num_rows(&self) -> usize {
+        self.shape.first().map(|rows| *rows as usize).unwrap_or(0)
+    }
+
+    /// Get the values predicted for row `i`, flattened if there are more than 2 dimensions.
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn row(&self, i: usize) -> &'a [f32] {
+        let row_len = self.shape.iter().skip(1).product::<u64>() as usize;
+        &self.data[i * row_len..(i + 1) * row_len]
+    }
+
+    /// Copy the values out of XGBoost's buffer into an owned [`Prediction`](struct.Prediction.html).
+    pub fn to_prediction(&self) -> Prediction {
+        Prediction::new(self.data.to_vec(), self.shape.iter().map(|dim| *dim as usize).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_json() {
+        let config = PredictConfig::default();
+        assert_eq!(
+            config.to_json(),
+            r#"{"type":0,"training":false,"iteration_begin":0,"iteration_end":0,"strict_shape":false}"#
+        );
+        assert!(config.as_json().ends_with('\0'));
+
+        let config = PredictConfigBuilder::default()
+            .predict_type(PredictType::OutputMargin)
+            .training(true)
+            .iteration_range(2..5)
+            .strict_shape(true)
+            .missing(-999.0)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.to_json(),
+            r#"{"type":1,"training":true,"iteration_begin":2,"iteration_end":5,"strict_shape":true}"#
+        );
+        assert_eq!(
+            config.to_inplace_json(),
+            r#"{"type":1,"training":true,"iteration_begin":2,"iteration_end":5,"strict_shape":true,"missing":-999,"cache_id":0}"#
+        );
+        assert!(PredictConfig::default().to_inplace_json().contains(r#""missing":NaN"#));
+    }
+
+    #[test]
+    fn config_iteration_range() {
+        let reversed = Range { start: 5, end: 2 };
+        assert!(
+            PredictConfigBuilder::default()
+                .iteration_range(reversed)
+                .build()
+                .is_err()
+        );
+        // an end of 0 means all rounds from start onwards
+        let open_ended = Range { start: 5, end: 0 };
+        assert!(
+            PredictConfigBuilder::default()
+                .iteration_range(open_ended)
+                .build()
+                .is_ok()
+        );
+        assert!(PredictConfigBuilder::default().iteration_range(2..2).build().is_ok());
+    }
+
+    #[test]
+    fn prediction_rows() {
+        let prediction = Prediction::new(vec![0.1, 0.9, 0.8, 0.2, 0.5, 0.5], vec![3, 2]);
+        assert_eq!(prediction.num_rows(), 3);
+        assert_eq!(prediction.row(1), &[0.8, 0.2]);
+        assert_eq!(prediction.into_parts().1, vec![3, 2]);
+
+        let prediction = Prediction::new(vec![1.0, 2.0], vec![2]);
+        assert_eq!(prediction.row(1), &[2.0]);
+    }
+
+    #[test]
+    fn prediction_view_rows() {
+        let data = [0.1, 0.9, 0.8, 0.2, 0.5, 0.5];
+        let view = PredictionView::new(&data, &[3, 2]);
+        assert_eq!(view.num_rows(), 3);
+        assert_eq!(view.row(2), &[0.5, 0.5]);
+        assert_eq!(view.to_prediction(), Prediction::new(data.to_vec(), vec![3, 2]));
+    }
+}
@@ -0,0 +1,988 @@
+//! SHAP values explaining the predictions of tree models, and summaries of them.
+//!
+//! [`ShapValues`](struct.ShapValues.html) holds the contribution of each feature to the margin predicted for each
+//! row, along with a bias term, so that they sum to the margin. They can be computed by the native library with
+//! [`ShapValues::from_booster`](struct.ShapValues.html#method.from_booster), or in pure Rust with
+//! [`TreeModel::shap_values`](struct.TreeModel.html#method.shap_values), which implements TreeSHAP
+//! ([Lundberg et al., 2020](https://doi.org/10.1038/s42256-019-0138-9)) over a model parsed from XGBoost's JSON
+//! format, so needs no `Booster` or `DMatrix`.
+//!
+//! ```no_run
+//! use xgb::shap::TreeModel;
+//!
+//! let model = TreeModel::load("model.json").unwrap();
+//! let x = &[1.0, 0.5, f32::NAN,
+//!           0.0, 2.5, 1.0];
+//! let shap = model.shap_values(x, 2).unwrap();
+//! for (feature, importance) in shap.importance() {
+//!     println!("{}: {}", feature, importance);
+//! }
+//! let dependence = shap.dependence(x, "f1").unwrap();
+//! ```
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::{Booster, DMatrix, PredictConfigBuilder, PredictType, XGBError, XGBResult};
+
+/// Name of the bias term in [`ShapValues::feature_names`](struct.ShapValues.html#method.feature_names).
+pub const BIAS_NAME: &str = "bias";
+
+/// Feature contributions (SHAP values) of each row, for each output of the model (one per class for multiclass
+/// models), shaped (rows, outputs, features + 1). The last column of each row and output is the bias term.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShapValues {
+    values: Vec<f32>,
+    shape: (usize, usize, usize),
+    feature_names: Vec<String>,
+}
+
+impl ShapValues {
+    /// Create from flat `values` shaped (rows, outputs, features + 1) and the names of each feature (without the
+    /// bias term).
+    pub fn new(values: Vec<f32>, shape: (usize, usize, usize), feature_names: Vec<String>) -> XGBResult<Self> {
+        let (num_rows, num_outputs, num_columns) = shape;
+        if num_columns == 0 || values.len() != num_rows * num_outputs * num_columns {
+            return Err(XGBError::new(format!(
+                "Can't shape {} SHAP values as {:?}",
+                values.len(),
+                shape
+            )));
+        }
+        if feature_names.len() != num_columns - 1 {
+            return Err(XGBError::new(format!(
+                "Expected {} feature names, got {}",
+                num_columns - 1,
+                feature_names.len()
+            )));
+        }
+        let mut feature_names = feature_names;
+        feature_names.push(BIAS_NAME.to_owned());
+        Ok(ShapValues {
+            values,
+            shape,
+            feature_names,
+        })
+    }
+
+    /// Compute SHAP values of `booster` for each row of `dmat` with the native library, naming features after the
+    /// feature names of `dmat` (or `f0`, `f1`, ... if not set).
+    pub fn from_booster(booster: &Booster, dmat: &DMatrix) -> XGBResult<Self> {
+        let config = PredictConfigBuilder::default()
+            .predict_type(PredictType::PredictContribitions)
+            .strict_shape(true)
+            .build()
+            .unwrap();
+        let (values, shape) = booster.predict_with(dmat, &config)?.into_parts();
+        let shape = match shape[..] {
+            [num_rows, num_outputs, num_columns] => (num_rows, num_outputs, num_columns),
+            _ => {
+                return Err(XGBError::new(format!(
+                    "Expected 3-dimensional contributions output, got shape {:?}",
+                    shape
+                )));
+            }
+        };
+        let names = dmat.get_feature_names()?;
+        let feature_names = (0..shape.2 - 1)
+            .map(|i| names.get(i).cloned().unwrap_or_else(|| format!("f{}", i)))
+            .collect();
+        ShapValues::new(values, shape, feature_names)
+    }
+
+    /// Get all values as a flat array.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Get the shape (rows, outputs, features + 1).
+    pub fn shape(&self) -> (usize, usize, usize) {
+        self.shape
+    }
+
+    /// Get the number of rows explained.
+    pub fn num_rows(&self) -> usize {
+        self.shape.0
+    }
+
+    /// Get the number of outputs of the model (one per class for multiclass models).
+    pub fn num_outputs(&self) -> usize {
+        self.shape.1
+    }
+
+    /// Get the number of features, excluding the bias term.
+    pub fn num_features(&self) -> usize {
+        self.shape.2 - 1
+    }
+
+    /// Get the name of each column, i.e. each feature followed by [`BIAS_NAME`](constant.BIAS_NAME.html).
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Get the index of the feature called `name`.
+    pub fn feature_index(&self, name: &str) -> XGBResult<usize> {
+        self.feature_names[..self.num_features()]
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| XGBError::new(format!("Unknown feature '{}'", name)))
+    }
+
+    /// Get the SHAP values of each feature followed by the bias term, for given row and output.
+    pub fn row(&self, row: usize, output: usize) -> &[f32] {
+        let num_columns = self.shape.2;
+        let start = (row * self.shape.1 + output) * num_columns;
+        &self.values[start..start + num_columns]
+    }
+
+    /// Get the mean absolute SHAP value of each feature (without the bias term) over all rows, summed over outputs.
+    pub fn mean_abs(&self) -> Vec<f32> {
+        let num_features = self.num_features();
+        let mut sums = vec![0.0f64; num_features];
+        for row in self.values.chunks(self.shape.2) {
+            for (sum, value) in sums.iter_mut().zip(row) {
+                *sum += value.abs() as f64;
+            }
+        }
+        sums.iter()
+            .map(|sum| (sum / self.num_rows().max(1) as f64) as f32)
+            .collect()
+    }
+
+    /// Get global feature importance, as the name and [`mean_abs`](#method.mean_abs) SHAP value of each feature,
+    /// most important first.
+    pub fn importance(&self) -> Vec<(String, f32)> {
+        let mut importance: Vec<(String, f32)> = self.feature_names.iter().cloned().zip(self.mean_abs()).collect();
+        importance.sort_by(|a, b| b.1.total_cmp(&a.1));
+        importance
+    }
+
+    /// Get the values of `feature` in dense row-major `data` (rows x features, `NaN` for missing values) along with
+    /// its SHAP values, to plot how the feature's value affects predictions.
+    pub fn dependence(&self, data: &[f32], feature: &str) -> XGBResult<Dependence> {
+        let num_rows = self.num_rows();
+        if num_rows == 0 || !data.len().is_multiple_of(num_rows) || data.len() / num_rows < self.num_features() {
+            return Err(XGBError::new(format!(
+                "Expected data for {} rows of at least {} features, got {} values",
+                num_rows,
+                self.num_features(),
+                data.len()
+            )));
+        }
+        let index = self.feature_index(feature)?;
+        let num_columns = data.len() / num_rows;
+        let feature_values = data.iter().skip(index).step_by(num_columns).copied().collect();
+        Ok(self.dependence_of(index, feature_values))
+    }
+
+    /// Like [`dependence`](#method.dependence), taking the feature's values from `dmat`.
+    pub fn dependence_dmatrix(&self, dmat: &DMatrix, feature: &str) -> XGBResult<Dependence> {
+        if dmat.num_rows() != self.num_rows() {
+            return Err(XGBError::new(format!(
+                "Expected {} rows, got {}",
+                self.num_rows(),
+                dmat.num_rows()
+            )));
+        }
+        let index = self.feature_index(feature)?;
+        let (indptr, indices, data) = dmat.get_data_as_csr()?;
+        let feature_values = indptr
+            .windows(2)
+            .map(|row| {
+                let (start, end) = (row[0] as usize, row[1] as usize);
+                indices[start..end]
+                    .iter()
+                    .position(|i| *i as usize == index)
+                    .map_or(f32::NAN, |i| data[start + i])
+            })
+            .collect();
+        Ok(self.dependence_of(index, feature_values))
+    }
+
+    fn dependence_of(&self, index: usize, feature_values: Vec<f32>) -> Dependence {
+        Dependence {
+            feature: self.feature_names[index].clone(),
+            feature_values,
+            shap_values: self.values.iter().skip(index).step_by(self.shape.2).copied().collect(),
+            num_outputs: self.num_outputs(),
+        }
+    }
+}
+
+/// Values of a feature and their SHAP values, see [`ShapValues::dependence`](struct.ShapValues.html#method.dependence).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dependence {
+    /// Name of the feature.
+    pub feature: String,
+    /// Value of the feature in each row, `NaN` if missing.
+    pub feature_values: Vec<f32>,
+    /// SHAP values of the feature for each row and output, shaped (rows, outputs).
+    pub shap_values: Vec<f32>,
+    /// Number of outputs of the model.
+    pub num_outputs: usize,
+}
+
+/// A single regression tree, in XGBoost's array layout.
+#[derive(Clone, Debug)]
+struct Tree {
+    left: Vec<i32>,
+    right: Vec<i32>,
+    feature: Vec<usize>,
+    /// Split threshold of split nodes, value of leaves.
+    threshold: Vec<f32>,
+    default_left: Vec<bool>,
+    cover: Vec<f64>,
+    /// Cover-weighted mean of the leaves below each node.
+    mean: Vec<f64>,
+}
+
+impl Tree {
+    fn from_json(tree: &Value, num_features: usize) -> XGBResult<Self> {
+        if let Some(size) = tree.pointer("/tree_param/size_leaf_vector")
+            && size.as_str().is_some_and(|s| s != "0" && s != "1")
+        {
+            return Err(XGBError::new("Trees with vector leaves are not supported"));
+        }
+        if let Some(split_type) = tree.get("split_type")
+            && numbers(split_type)?.iter().any(|t| *t != 0.0)
+        {
+            return Err(XGBError::new("Trees with categorical splits are not supported"));
+        }
+
+        let field = |key: &str| {
+            tree.get(key)
+                .ok_or_else(|| XGBError::new(format!("Tree is missing '{}'", key)))
+                .and_then(numbers)
+        };
+        let left: Vec<i32> = field("left_children")?.iter().map(|i| *i as i32).collect();
+        let right: Vec<i32> = field("right_children")?.iter().map(|i| *i as i32).collect();
+        let feature: Vec<usize> = field("split_indices")?.iter().map(|i| *i as usize).collect();
+        let threshold: Vec<f32> = field("split_conditions")?.iter().map(|t| *t as f32).collect();
+        let default_left: Vec<bool> = field("default_left")?.iter().map(|d| *d != 0.0).collect();
+        let cover = field("sum_hessian")?;
+
+        let num_nodes = left.len();
+        if num_nodes == 0
+            || [
+                right.len(),
+                feature.len(),
+                threshold.len(),
+                default_left.len(),
+                cover.len(),
+            ]
+            .iter()
+            .any(|len| *len != num_nodes)
+            // children come after their parent, so traversals can't loop, and splits are on known features
+            || (0..num_nodes).any(|node| {
+                left[node] >= 0
+                    && (feature[node] >= num_features
+                        || [left[node], right[node]]
+                            .iter()
+                            .any(|child| *child <= node as i32 || *child >= num_nodes as i32))
+            })
+        {
+            return Err(XGBError::new("Malformed tree"));
+        }
+
+        let mut tree = Tree {
+            left,
+            right,
+            feature,
+            threshold,
+            default_left,
+            cover,
+            mean: vec![0.0; num_nodes],
+        };
+        tree.fill_mean(0);
+        Ok(tree)
+    }
+
+    fn is_leaf(&self, node: usize) -> bool {
+        self.left[node] < 0
+    }
+
+    fn fill_mean(&mut self, node: usize) -> f64 {
+        let mean = if self.is_leaf(node) {
+            self.threshold[node] as f64
+        } else {
+            let (left, right) = (self.left[node] as usize, self.right[node] as usize);
+            let sum = self.fill_mean(left) * self.cover[left] + self.fill_mean(right) * self.cover[right];
+            if self.cover[node] > 0.0 { sum / self.cover[node] } else { 0.0 }
+        };
+        self.mean[node] = mean;
+        mean
+    }
+
+    /// Child of split `node` taken by `row`.
+    fn next(&self, node: usize, row: &[f32]) -> usize {
+        let value = row.get(self.feature[node]).copied().unwrap_or(f32::NAN);
+        let left = if value.is_nan() {
+            self.default_left[node]
+        } else {
+            value < self.threshold[node]
+        };
+        if left { self.left[node] as usize } else { self.right[node] as usize }
+    }
+
+    fn leaf_value(&self, row: &[f32]) -> f32 {
+        let mut node = 0;
+        while !self.is_leaf(node) {
+            node = self.next(node, row);
+        }
+        self.threshold[node]
+    }
+
+    /// Add the SHAP values of `row`, scaled by `scale`, to `phi` (features + 1, bias last).
+    fn shap(&self, row: &[f32], scale: f64, phi: &mut [f64]) {
+        *phi.last_mut().unwrap() += scale * self.mean[0];
+        self.shap_recurse(row, scale, phi, 0, &[], 1.0, 1.0, -1);
+    }
+
+    /// Algorithm 2 of Lundberg et al., following the path of `row` while tracking the fraction of all subsets of
+    /// the features on the path that reach `node`.
+    #[allow(clippy::too_many_arguments)]
+    fn shap_recurse(
+        &self,
+        row: &[f32],
+        scale: f64,
+        phi: &mut [f64],
+        node: usize,
+        parent_path: &[PathElement],
+        zero_fraction: f64,
+        one_fraction: f64,
+        feature: i64,
+    ) {
+        let mut path = parent_path.to_vec();
+        extend_path(&mut path, zero_fraction, one_fraction, feature);
+
+        if self.is_leaf(node) {
+            let value = self.threshold[node] as f64;
+            for i in 1..path.len() {
+                let weight = unwound_path_sum(&path, i);
+                let element = &path[i];
+                phi[element.feature as usize] +=
+                    weight * (element.one_fraction - element.zero_fraction) * value * scale;
+            }
+            return;
+        }
+
+        let hot = self.next(node, row);
+        let cold = if hot == self.left[node] as usize {
+            self.right[node] as usize
+        } else {
+            self.left[node] as usize
+        };
+        let cover = self.cover[node];
+        let (hot_zero_fraction, cold_zero_fraction) = if cover > 0.0 {
+            (self.cover[hot] / cover, self.cover[cold] / cover)
+        } else {
+            (0.0, 0.0)
+        };
+
+        // a feature splitting twice on the path is only counted once
+        let split_feature = self.feature[node] as i64;
+        let (mut incoming_zero_fraction, mut incoming_one_fraction) = (1.0, 1.0);
+        if let Some(index) = path.iter().position(|element| element.feature == split_feature) {
+            incoming_zero_fraction = path[index].zero_fraction;
+            incoming_one_fraction = path[index].one_fraction;
+            unwind_path(&mut path, index);
+        }
+
+        self.shap_recurse(
+            row,
+            scale,
+            phi,
+            hot,
+            &path,
+            hot_zero_fraction * incoming_zero_fraction,
+            incoming_one_fraction,
+            split_feature,
+        );
+        self.shap_recurse(
+            row,
+            scale,
+            phi,
+            cold,
+            &path,
+            cold_zero_fraction * incoming_zero_fraction,
+            0.0,
+            split_feature,
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PathElement {
+    feature: i64,
+    zero_fraction: f64,
+    one_fraction: f64,
+    weight: f64,
+}
+
+fn extend_path(path: &mut Vec<PathElement>, zero_fraction: f64, one_fraction: f64, feature: i64) {
+    let depth = path.len();
+    path.push(PathElement {
+        feature,
+        zero_fraction,
+        one_fraction,
+        weight: if depth == 0 { 1.0 } else { 0.0 },
+    });
+    for i in (0..depth).rev() {
+        path[i + 1].weight += one_fraction * path[i].weight * (i + 1) as f64 / (depth + 1) as f64;
+        path[i].weight = zero_fraction * path[i].weight * (depth - i) as f64 / (depth + 1) as f64;
+    }
+}
+
+fn unwind_path(path: &mut Vec<PathElement>, index: usize) {
+    let depth = path.len() - 1;
+    let PathElement {
+        zero_fraction,
+        one_fraction,
+        ..
+    } = path[index];
+    let mut next_one_portion = path[depth].weight;
+    for i in (0..depth).rev() {
+        if one_fraction != 0.0 {
+            let weight = path[i].weight;
+            path[i].weight = next_one_portion * (depth + 1) as f64 / ((i + 1) as f64 * one_fraction);
+            next_one_portion = weight - path[i].weight * zero_fraction * (depth - i) as f64 / (depth + 1) as f64;
+        } else {
+            path[i].weight = path[i].weight * (depth + 1) as f64 / (zero_fraction * (depth - i) as f64);
+        }
+    }
+    for i in index..depth {
+        path[i].feature = path[i + 1].feature;
+        path[i].zero_fraction = path[i + 1].zero_fraction;
+        path[i].one_fraction = path[i + 1].one_fraction;
+    }
+    path.pop();
+}
+
+/// Total weight of the path if the element at `index` was unwound.
+fn unwound_path_sum(path: &[PathElement], index: usize) -> f64 {
+    let depth = path.len() - 1;
+    let PathElement {
+        zero_fraction,
+        one_fraction,
+        ..
+    } = path[index];
+    let mut next_one_portion = path[depth].weight;
+    let mut total = 0.0;
+    for i in (0..depth).rev() {
+        if one_fraction != 0.0 {
+            let weight = next_one_portion * (depth + 1) as f64 / ((i + 1) as f64 * one_fraction);
+            total += weight;
+            next_one_portion = path[i].weight - weight * zero_fraction * (depth - i) as f64 / (depth + 1) as f64;
+        } else if zero_fraction != 0.0 {
+            total += path[i].weight / zero_fraction / ((depth - i) as f64 / (depth + 1) as f64);
+        }
+    }
+    total
+}
+
+/// A tree model (`gbtree` or `dart` booster) parsed from XGBoost's JSON model format, for predicting margins and
+/// SHAP values without the native library.
+///
+/// Categorical splits and multi-target trees with vector leaves aren't supported.
+#[derive(Clone, Debug)]
+pub struct TreeModel {
+    trees: Vec<Tree>,
+    tree_groups: Vec<usize>,
+    tree_weights: Vec<f64>,
+    base_margin: Vec<f32>,
+    num_features: usize,
+    feature_names: Vec<String>,
+}
+
+impl TreeModel {
+    /// Parse a model saved in JSON format, e.g. by [`Booster::save`](../struct.Booster.html#method.save) to a file
+    /// with a `.json` extension.
+    pub fn from_json(json: &[u8]) -> XGBResult<Self> {
+        let model: Value = serde_json::from_slice(&quote_non_finite(json))
+            .map_err(|err| XGBError::new(format!("Invalid JSON model: {}", err)))?;
+        let learner = get(&model, "/learner")?;
+        let param = |key: &str| -> XGBResult<&str> {
+            get(learner, &format!("/learner_model_param/{}", key))?
+                .as_str()
+                .ok_or_else(|| XGBError::new(format!("Model parameter '{}' is not a string", key)))
+        };
+        let parse_usize = |key: &str| -> XGBResult<usize> {
+            let value = param(key)?;
+            value
+                .parse()
+                .map_err(|_| XGBError::new(format!("Invalid model parameter {}='{}'", key, value)))
+        };
+        let num_features = parse_usize("num_feature")?;
+        let num_groups = parse_usize("num_class")?
+            .max(param("num_target").map_or(Ok(1), |_| parse_usize("num_target"))?)
+            .max(1);
+
+        let objective = get(learner, "/objective/name")?.as_str().unwrap_or_default();
+        let base_score = param("base_score")?;
+        let base_scores = base_score
+            .trim_matches(|c| c == '[' || c == ']')
+            .split(',')
+            .map(|s| s.trim().parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| XGBError::new(format!("Invalid base_score '{}'", base_score)))?;
+        let base_margin = match base_scores[..] {
+            [score] => vec![prob_to_margin(objective, score); num_groups],
+            _ if base_scores.len() == num_groups => base_scores
+                .iter()
+                .map(|score| prob_to_margin(objective, *score))
+                .collect(),
+            _ => return Err(XGBError::new(format!("Invalid base_score '{}'", base_score))),
+        };
+
+        let booster = get(learner, "/gradient_booster")?;
+        let (gbtree, tree_weights) = match get(booster, "/name")?.as_str() {
+            Some("gbtree") => (booster, None),
+            Some("dart") => (get(booster, "/gbtree")?, Some(numbers(get(booster, "/weight_drop")?)?)),
+            name => {
+                return Err(XGBError::new(format!(
+                    "Only tree boosters are supported, got {}",
+                    name.unwrap_or_default()
+                )));
+            }
+        };
+        let trees = get(gbtree, "/model/trees")?
+            .as_array()
+            .ok_or_else(|| XGBError::new("Model trees are not an array"))?
+            .iter()
+            .map(|tree| Tree::from_json(tree, num_features))
+            .collect::<XGBResult<Vec<_>>>()?;
+        let tree_groups: Vec<usize> = numbers(get(gbtree, "/model/tree_info")?)?
+            .iter()
+            .map(|g| *g as usize)
+            .collect();
+        let tree_weights = tree_weights.unwrap_or_else(|| vec![1.0; trees.len()]);
+        if tree_groups.len() != trees.len()
+            || tree_weights.len() != trees.len()
+            || tree_groups.iter().any(|g| *g >= num_groups)
+        {
+            return Err(XGBError::new("Malformed tree model"));
+        }
+
+        let names: Vec<&str> = learner
+            .get("feature_names")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let feature_names = (0..num_features)
+            .map(|i| names.get(i).map_or_else(|| format!("f{}", i), |name| name.to_string()))
+            .collect();
+
+        Ok(TreeModel {
+            trees,
+            tree_groups,
+            tree_weights,
+            base_margin,
+            num_features,
+            feature_names,
+        })
+    }
+
+    /// Load a model saved in JSON format.
+    pub fn load<P: AsRef<Path>>(path: P) -> XGBResult<Self> {
+        let json = fs::read(path.as_ref())
+            .map_err(|err| XGBError::new(format!("Failed to read {}: {}", path.as_ref().display(), err)))?;
+        TreeModel::from_json(&json)
+    }
+
+    /// Parse the model of `booster`.
+    pub fn from_booster(booster: &Booster) -> XGBResult<Self> {
+        TreeModel::from_json(&booster.save_buffer(false)?)
+    }
+
+    /// Get the number of features the model was trained on.
+    pub fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    /// Get the number of outputs of the model (one per class for multiclass models).
+    pub fn num_outputs(&self) -> usize {
+        self.base_margin.len()
+    }
+
+    /// Get the number of trees in the model.
+    pub fn num_trees(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// Get the name of each feature, from the model or `f0`, `f1`, ... if it has none.
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Predict the untransformed margin of each row of dense row-major `data` (rows x features, `NaN` for missing
+    /// values), shaped (rows, outputs).
+    pub fn predict_margin(&self, data: &[f32], num_rows: usize) -> XGBResult<Vec<f32>> {
+        let num_outputs = self.num_outputs();
+        let mut margins = Vec::with_capacity(num_rows * num_outputs);
+        for row in self.rows(data, num_rows)? {
+            let mut margin: Vec<f64> = self.base_margin.iter().map(|m| *m as f64).collect();
+            for ((tree, group), weight) in self.trees.iter().zip(&self.tree_groups).zip(&self.tree_weights) {
+                margin[*group] += weight * tree.leaf_value(row) as f64;
+            }
+            margins.extend(margin.iter().map(|m| *m as f32));
+        }
+        Ok(margins)
+    }
+
+    /// Compute exact SHAP values of each row of dense row-major `data` (rows x features, `NaN` for missing values)
+    /// with TreeSHAP, as computed by the native library.
+    pub fn shap_values(&self, data: &[f32], num_rows: usize) -> XGBResult<ShapValues> {
+        let num_outputs = self.num_outputs();
+        let num_columns = self.num_features + 1;
+        let mut values = Vec::with_capacity(num_rows * num_outputs * num_columns);
+        for row in self.rows(data, num_rows)? {
+            let mut phi = vec![0.0f64; num_outputs * num_columns];
+            for (output, margin) in self.base_margin.iter().enumerate() {
+                phi[(output + 1) * num_columns - 1] = *margin as f64;
+            }
+            for ((tree, group), weight) in self.trees.iter().zip(&self.tree_groups).zip(&self.tree_weights) {
+                tree.shap(row, *weight, &mut phi[group * num_columns..(group + 1) * num_columns]);
+            }
+            values.extend(phi.iter().map(|v| *v as f32));
+        }
+        ShapValues::new(values, (num_rows, num_outputs, num_columns), self.feature_names.clone())
+    }
+
+    fn rows<'a>(&self, data: &'a [f32], num_rows: usize) -> XGBResult<std::slice::Chunks<'a, f32>> {
+        if num_rows == 0 || !data.len().is_multiple_of(num_rows) {
+            return Err(XGBError::new(format!(
+                "Can't split {} values into {} rows",
+                data.len(),
+                num_rows
+            )));
+        }
+        Ok(data.chunks(data.len() / num_rows))
+    }
+}
+
+/// Convert a base score to a margin, as done by the objective's `ProbToMargin`.
+fn prob_to_margin(objective: &str, base_score: f32) -> f32 {
+    match objective {
+        "binary:logistic" | "binary:logitraw" | "reg:logistic" => -(1.0 / base_score - 1.0).ln(),
+        "count:poisson" | "reg:gamma" | "reg:tweedie" | "survival:cox" | "survival:aft" => base_score.ln(),
+        _ => base_score,
+    }
+}
+
+fn get<'a>(value: &'a Value, pointer: &str) -> XGBResult<&'a Value> {
+    value
+        .pointer(pointer)
+        .ok_or_else(|| XGBError::new(format!("Model is missing '{}'", pointer)))
+}
+
+/// Non-finite numbers XGBoost writes into JSON models, which standard JSON has no literals for.
+const NON_FINITE: [&str; 3] = ["NaN", "Infinity", "-Infinity"];
+
+/// Quote the non-finite numbers of a JSON model, so it can be parsed as standard JSON.
+fn quote_non_finite(json: &[u8]) -> Vec<u8> {
+    let mut quoted = Vec::with_capacity(json.len());
+    let (mut in_string, mut escaped) = (false, false);
+    let mut i = 0;
+    while i < json.len() {
+        let byte = json[i];
+        if in_string {
+            in_string = escaped || byte != b'"';
+            escaped = !escaped && byte == b'\\';
+        } else if byte == b'"' {
+            in_string = true;
+        } else if let Some(literal) = NON_FINITE
+            .iter()
+            .find(|literal| json[i..].starts_with(literal.as_bytes()))
+        {
+            quoted.push(b'"');
+            quoted.extend_from_slice(literal.as_bytes());
+            quoted.push(b'"');
+            i += literal.len();
+            continue;
+        }
+        quoted.push(byte);
+        i += 1;
+    }
+    quoted
+}
+
+/// Numbers (or booleans, as 0 and 1) of a JSON array, with non-finite numbers quoted by `quote_non_finite`.
+fn numbers(value: &Value) -> XGBResult<Vec<f64>> {
+    value
+        .as_array()
+        .ok_or_else(|| XGBError::new(format!("Expected array, got {}", value)))?
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .or_else(|| v.as_bool().map(|b| b as u8 as f64))
+                .or_else(|| {
+                    v.as_str()
+                        .filter(|s| NON_FINITE.contains(s))
+                        .and_then(|s| s.parse().ok())
+                })
+                .ok_or_else(|| XGBError::new(format!("Expected number, got {}", v)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::learning::Objective;
+    use crate::testing;
+
+    /// Depth 2 trees over 3 features, the second splitting twice on feature 0.
+    const MODEL: &str = r#"{
+        "learner": {
+            "learner_model_param": {"base_score": "5E-1", "num_class": "0", "num_feature": "3", "num_target": "1"},
+            "objective": {"name": "binary:logistic"},
+            "feature_names": ["a", "b", "c"],
+            "gradient_booster": {
+                "name": "gbtree",
+                "model": {
+                    "tree_info": [0, 0],
+                    "trees": [
+                        {
+                            "left_children": [1, 3, 5, -1, -1, -1, -1],
+                            "right_children": [2, 4, 6, -1, -1, -1, -1],
+                            "split_indices": [0, 1, 2, 0, 0, 0, 0],
+                            "split_conditions": [0.5, 1.5, 2.5, 1.0, -2.0, 0.5, 3.0],
+                            "default_left": [1, 0, 1, 0, 0, 0, 0],
+                            "sum_hessian": [10.0, 6.0, 4.0, 2.0, 4.0, 3.0, 1.0]
+                        },
+                        {
+                            "left_children": [1, 3, -1, -1, -1],
+                            "right_children": [2, 4, -1, -1, -1],
+                            "split_indices": [0, 0, 0, 0, 0],
+                            "split_conditions": [1.0, 0.0, -0.5, 0.25, 0.75],
+                            "default_left": [false, true, false, false, false],
+                            "sum_hessian": [10.0, 7.0, 3.0, 5.0, 2.0]
+                        }
+                    ]
+                }
+            }
+        }
+    }"#;
+
+    /// Expected value of `tree` given only the features in `known`, following `row` at splits on known features.
+    fn conditional_expectation(tree: &Tree, row: &[f32], known: &[bool], node: usize) -> f64 {
+        if tree.is_leaf(node) {
+            return tree.threshold[node] as f64;
+        }
+        if known[tree.feature[node]] {
+            return conditional_expectation(tree, row, known, tree.next(node, row));
+        }
+        let (left, right) = (tree.left[node] as usize, tree.right[node] as usize);
+        (conditional_expectation(tree, row, known, left) * tree.cover[left]
+            + conditional_expectation(tree, row, known, right) * tree.cover[right])
+            / tree.cover[node]
+    }
+
+    /// Shapley values by enumerating all subsets of features.
+    fn brute_force_shap(model: &TreeModel, row: &[f32]) -> Vec<f64> {
+        let m = model.num_features();
+        let factorial = |n: usize| (1..=n).product::<usize>() as f64;
+        let mut phi = vec![0.0; m + 1];
+        phi[m] = model.base_margin[0] as f64;
+        for tree in &model.trees {
+            phi[m] += conditional_expectation(tree, row, &vec![false; m], 0);
+            for (i, phi_i) in phi.iter_mut().enumerate().take(m) {
+                for subset in 0..(1 << m) {
+                    if subset & (1 << i) != 0 {
+                        continue;
+                    }
+                    let mut known: Vec<bool> = (0..m).map(|j| subset & (1 << j) != 0).collect();
+                    let size = known.iter().filter(|k| **k).count();
+                    let without = conditional_expectation(tree, row, &known, 0);
+                    known[i] = true;
+                    let with = conditional_expectation(tree, row, &known, 0);
+                    *phi_i += factorial(size) * factorial(m - size - 1) / factorial(m) * (with - without);
+                }
+            }
+        }
+        phi
+    }
+
+    #[test]
+    fn parse() {
+        let model = TreeModel::from_json(MODEL.as_bytes()).unwrap();
+        assert_eq!(model.num_features(), 3);
+        assert_eq!(model.num_outputs(), 1);
+        assert_eq!(model.num_trees(), 2);
+        assert_eq!(model.feature_names(), &["a", "b", "c"]);
+        assert_eq!(model.base_margin, vec![0.0]);
+        assert_eq!(model.trees[0].mean[0], (2.0 - 8.0 + 1.5 + 3.0) / 10.0);
+
+        assert!(TreeModel::from_json(b"{}").is_err());
+        assert!(TreeModel::from_json(b"not json").is_err());
+        let categorical = MODEL.replace(
+            r#""sum_hessian": [10.0, 7.0"#,
+            r#""split_type": [1, 0, 0, 0, 0], "sum_hessian": [10.0, 7.0"#,
+        );
+        assert!(TreeModel::from_json(categorical.as_bytes()).is_err());
+        let linear = MODEL.replace("gbtree", "gblinear");
+        assert!(TreeModel::from_json(linear.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn malformed_trees() {
+        let malformed = |from: &str, to: &str| {
+            assert!(MODEL.contains(from));
+            let json = MODEL.replace(from, to);
+            let result = TreeModel::from_json(json.as_bytes());
+            assert!(
+                result.is_err_and(|err| err.to_string().contains("Malformed tree")),
+                "{}",
+                to
+            );
+        };
+        let left = r#""left_children": [1, 3, -1, -1, -1]"#;
+        // a node that is its own child, or a child of its descendant, would recurse forever
+        malformed(left, r#""left_children": [1, 1, -1, -1, -1]"#);
+        malformed(left, r#""left_children": [1, 0, -1, -1, -1]"#);
+        malformed(left, r#""left_children": [1, 3, -1, -1, 5]"#);
+        malformed(
+            r#""right_children": [2, 4, -1, -1, -1]"#,
+            r#""right_children": [2, -1, -1, -1, -1]"#,
+        );
+        malformed(left, r#""left_children": [1, 3, -1, -1]"#);
+        // splits on a feature the model doesn't have, while leaves' split indices are unused
+        let indices = r#""split_indices": [0, 1, 2, 0, 0, 0, 0]"#;
+        malformed(indices, r#""split_indices": [0, 1, 3, 0, 0, 0, 0]"#);
+        let unused = MODEL.replace(indices, r#""split_indices": [0, 1, 2, 7, 0, 0, 0]"#);
+        assert!(TreeModel::from_json(unused.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn non_finite_numbers() {
+        assert_eq!(
+            quote_non_finite(br#"["a\"NaN", NaN, [-Infinity], {"Infinity": Infinity}]"#),
+            br#"["a\"NaN", "NaN", ["-Infinity"], {"Infinity": "Infinity"}]"#
+        );
+
+        let json = MODEL
+            .replace(r#""a", "b""#, r#""NaN", "b""#)
+            .replace("[1.0, 0.0, -0.5, 0.25, 0.75]", "[1.0, 0.0, NaN, -Infinity, Infinity]");
+        let model = TreeModel::from_json(json.as_bytes()).unwrap();
+        assert_eq!(model.feature_names()[0], "NaN");
+        let threshold = &model.trees[1].threshold;
+        assert!(threshold[2].is_nan());
+        assert_eq!(threshold[3..], [f32::NEG_INFINITY, f32::INFINITY]);
+    }
+
+    #[test]
+    fn predict_margin() {
+        let model = TreeModel::from_json(MODEL.as_bytes()).unwrap();
+        let x = [
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+            2.0,
+            0.0,
+            1.0,
+            0.0,
+            f32::NAN,
+            f32::NAN,
+            f32::NAN,
+            f32::NAN,
+        ];
+        let margins = model.predict_margin(&x, 4).unwrap();
+        assert_eq!(margins, vec![1.0 + 0.75, -2.0 + 0.75, 0.5 - 0.5, -2.0 - 0.5]);
+        assert!(model.predict_margin(&x, 5).is_err());
+    }
+
+    #[test]
+    fn tree_shap() {
+        let model = TreeModel::from_json(MODEL.as_bytes()).unwrap();
+        let x = [0.0, 1.0, 0.0, 0.0, 2.0, 3.0, 0.7, 0.0, 5.0, f32::NAN, f32::NAN, 1.0];
+        let shap = model.shap_values(&x, 4).unwrap();
+        assert_eq!(shap.shape(), (4, 1, 4));
+        let margins = model.predict_margin(&x, 4).unwrap();
+        for (i, row) in x.chunks(3).enumerate() {
+            let expected = brute_force_shap(&model, row);
+            let actual = shap.row(i, 0);
+            for (a, e) in actual.iter().zip(&expected) {
+                assert!(
+                    (*a as f64 - e).abs() < 1e-5,
+                    "row {}: {:?} != {:?}",
+                    i,
+                    actual,
+                    expected
+                );
+            }
+            let sum: f32 = actual.iter().sum();
+            assert!((sum - margins[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn summaries() {
+        let values = vec![1.0, -2.0, 0.5, -1.0, 0.0, 0.5];
+        let names = vec!["x".to_owned(), "y".to_owned()];
+        let shap = ShapValues::new(values, (2, 1, 3), names).unwrap();
+        assert_eq!(shap.feature_names(), &["x", "y", BIAS_NAME]);
+        assert_eq!(shap.mean_abs(), vec![1.0, 1.0]);
+        assert_eq!(shap.row(1, 0), &[-1.0, 0.0, 0.5]);
+        assert_eq!(shap.feature_index("y").unwrap(), 1);
+        assert!(shap.feature_index(BIAS_NAME).is_err());
+
+        let shap = ShapValues::new(
+            vec![1.0, -3.0, 0.5, -1.0, 0.0, 0.5],
+            (2, 1, 3),
+            shap.feature_names()[..2].to_vec(),
+        )
+        .unwrap();
+        assert_eq!(shap.importance(), vec![("y".to_owned(), 1.5), ("x".to_owned(), 1.0)]);
+
+        let dependence = shap.dependence(&[10.0, 20.0, f32::NAN, 21.0], "x").unwrap();
+        assert_eq!(dependence.feature, "x");
+        assert!(dependence.feature_values[1].is_nan());
+        assert_eq!(dependence.shap_values, vec![1.0, -1.0]);
+        assert!(shap.dependence(&[1.0, 2.0, 3.0], "x").is_err());
+        assert!(shap.dependence(&[1.0, 2.0, 3.0, 4.0], "z").is_err());
+
+        assert!(ShapValues::new(vec![0.0; 5], (2, 1, 3), vec!["x".to_owned(), "y".to_owned()]).is_err());
+        assert!(ShapValues::new(vec![0.0; 6], (2, 1, 3), vec!["x".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn matches_native() {
+        let mut x = testing::features(600);
+        x.iter_mut().step_by(17).for_each(|v| *v = f32::NAN);
+        let binary: Vec<f32> = x.chunks(3).map(|row| (row[0] + row[1] > 12.0) as u8 as f32).collect();
+        let multiclass: Vec<f32> = x.chunks(3).map(|row| (row[2] as u32 % 3) as f32).collect();
+
+        for (objective, labels) in [
+            (Objective::BinaryLogistic, binary.clone()),
+            (Objective::RegLinear, binary),
+            (Objective::MultiSoftprob(3), multiclass),
+        ] {
+            let dmat = testing::dmatrix(&x, &labels);
+            let booster = testing::train(&dmat, objective, 10);
+            let model = TreeModel::from_booster(&booster).unwrap();
+
+            let margins = model.predict_margin(&x, 200).unwrap();
+            let native_margins = booster.predict_margin(&dmat).unwrap();
+            assert_eq!(margins.len(), native_margins.len());
+            for (a, b) in margins.iter().zip(&native_margins) {
+                assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+            }
+
+            let shap = model.shap_values(&x, 200).unwrap();
+            let native = ShapValues::from_booster(&booster, &dmat).unwrap();
+            assert_eq!(shap.shape(), native.shape());
+            assert_eq!(shap.feature_names(), native.feature_names());
+            for (a, b) in shap.values().iter().zip(native.values()) {
+                assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+            }
+
+            let dependence = native.dependence_dmatrix(&dmat, "f1").unwrap();
+            let expected = native.dependence(&x, "f1").unwrap();
+            assert_eq!(dependence.shap_values, expected.shap_values);
+            let bits = |values: &[f32]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+            assert_eq!(bits(&dependence.feature_values), bits(&expected.feature_values));
+        }
+    }
+}
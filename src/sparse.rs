@@ -0,0 +1,107 @@
+//! Conversions from [sprs](https://docs.rs/sprs) and [nalgebra-sparse](https://docs.rs/nalgebra-sparse) matrices
+//! to XGBoost data.
+//!
+//! Only available when the `sprs` or `nalgebra-sparse` feature is enabled.
+use crate::{DMatrix, XGBError, XGBResult};
+
+#[cfg(feature = "sprs")]
+mod sprs_impl {
+    use std::ops::Deref;
+
+    use sprs::{CsMatBase, SpIndex};
+
+    use super::*;
+    use crate::dmatrix::SparseIndex;
+
+    impl<I, Iptr, IptrStorage, IndStorage, DataStorage>
+        TryFrom<&CsMatBase<f32, I, IptrStorage, IndStorage, DataStorage, Iptr>> for DMatrix
+    where
+        I: SpIndex + SparseIndex,
+        Iptr: SpIndex + SparseIndex,
+        IptrStorage: Deref<Target = [Iptr]>,
+        IndStorage: Deref<Target = [I]>,
+        DataStorage: Deref<Target = [f32]>,
+    {
+        type Error = XGBError;
+
+        /// Create a new `DMatrix` from a `sprs` matrix in either CSR or CSC storage, keeping its number of rows and
+        /// columns (including trailing empty ones).
+        fn try_from(mat: &CsMatBase<f32, I, IptrStorage, IndStorage, DataStorage, Iptr>) -> XGBResult<Self> {
+            // views of a subset of rows/columns may have an indptr that doesn't start at 0
+            let indptr = mat.proper_indptr();
+            if mat.is_csr() {
+                DMatrix::from_csr(&indptr, mat.indices(), mat.data(), Some(mat.cols()))
+            } else {
+                DMatrix::from_csc(&indptr, mat.indices(), mat.data(), Some(mat.rows()))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra-sparse")]
+mod nalgebra_impl {
+    use nalgebra_sparse::{CscMatrix, CsrMatrix};
+
+    use super::*;
+
+    impl TryFrom<&CsrMatrix<f32>> for DMatrix {
+        type Error = XGBError;
+
+        /// Create a new `DMatrix` from a `nalgebra-sparse` CSR matrix, keeping its number of columns.
+        fn try_from(mat: &CsrMatrix<f32>) -> XGBResult<Self> {
+            DMatrix::from_csr(mat.row_offsets(), mat.col_indices(), mat.values(), Some(mat.ncols()))
+        }
+    }
+
+    impl TryFrom<&CscMatrix<f32>> for DMatrix {
+        type Error = XGBError;
+
+        /// Create a new `DMatrix` from a `nalgebra-sparse` CSC matrix, keeping its number of rows.
+        fn try_from(mat: &CscMatrix<f32>) -> XGBResult<Self> {
+            DMatrix::from_csc(mat.col_offsets(), mat.row_indices(), mat.values(), Some(mat.nrows()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "sprs")]
+    #[test]
+    fn from_sprs() {
+        use sprs::{CsMat, CsMatI, TriMat};
+
+        let mut triplets = TriMat::new((4, 5));
+        triplets.add_triplet(0, 0, 1.0f32);
+        triplets.add_triplet(1, 2, 2.0);
+        triplets.add_triplet(2, 1, 3.0);
+        let csr: CsMat<f32> = triplets.to_csr();
+        let csc: CsMat<f32> = triplets.to_csc();
+
+        // trailing empty row and column are kept
+        assert_eq!(DMatrix::try_from(&csr).unwrap().shape(), (4, 5));
+        assert_eq!(DMatrix::try_from(&csc).unwrap().shape(), (4, 5));
+        assert_eq!(DMatrix::try_from(&csr.view()).unwrap().shape(), (4, 5));
+
+        let middle = csr.slice_outer(1..3);
+        assert_eq!(DMatrix::try_from(&middle).unwrap().shape(), (2, 5));
+
+        let csr_u32: CsMatI<f32, u32> = csr.to_other_types();
+        assert_eq!(DMatrix::try_from(&csr_u32).unwrap().shape(), (4, 5));
+    }
+
+    #[cfg(feature = "nalgebra-sparse")]
+    #[test]
+    fn from_nalgebra_sparse() {
+        use nalgebra_sparse::{CooMatrix, CscMatrix, CsrMatrix};
+
+        let mut coo = CooMatrix::new(4, 5);
+        coo.push(0, 0, 1.0f32);
+        coo.push(1, 2, 2.0);
+        coo.push(2, 1, 3.0);
+
+        assert_eq!(DMatrix::try_from(&CsrMatrix::from(&coo)).unwrap().shape(), (4, 5));
+        assert_eq!(DMatrix::try_from(&CscMatrix::from(&coo)).unwrap().shape(), (4, 5));
+    }
+}
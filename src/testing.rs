@@ -0,0 +1,41 @@
+//! Helpers shared by unit tests that train models on small generated data sets.
+use crate::parameters::learning::{LearningTaskParameters, LearningTaskParametersBuilder, Objective};
+use crate::parameters::{BoosterParametersBuilder, TrainingParametersBuilder};
+use crate::{Booster, DMatrix};
+
+/// `n` feature values cycling through 0 to 12 in a scrambled order.
+pub(crate) fn features(n: usize) -> Vec<f32> {
+    (0..n).map(|i| ((i * 7) % 13) as f32).collect()
+}
+
+/// Dense row-major `x` with one row per label.
+pub(crate) fn dmatrix(x: &[f32], labels: &[f32]) -> DMatrix {
+    let mut dmat = DMatrix::from_dense(x, labels.len()).unwrap();
+    dmat.set_labels(labels).unwrap();
+    dmat
+}
+
+/// Train a quiet booster on `dtrain` with `objective` for `rounds` rounds.
+pub(crate) fn train(dtrain: &DMatrix, objective: Objective, rounds: u32) -> Booster {
+    let learning_params = LearningTaskParametersBuilder::default()
+        .objective(objective)
+        .build()
+        .unwrap();
+    train_with(dtrain, learning_params, rounds)
+}
+
+/// Train a quiet booster on `dtrain` with `learning_params` for `rounds` rounds.
+pub(crate) fn train_with(dtrain: &DMatrix, learning_params: LearningTaskParameters, rounds: u32) -> Booster {
+    let booster_params = BoosterParametersBuilder::default()
+        .learning_params(learning_params)
+        .verbose(false)
+        .build()
+        .unwrap();
+    let params = TrainingParametersBuilder::default()
+        .dtrain(dtrain)
+        .booster_params(booster_params)
+        .boost_rounds(rounds)
+        .build()
+        .unwrap();
+    Booster::train(&params).unwrap()
+}
@@ -0,0 +1,1087 @@
+//! Reading and writing data in the LIBSVM and CSV text formats understood by XGBoost.
+//!
+//! [`read_csv`](fn.read_csv.html) and [`read_libsvm`](fn.read_libsvm.html) (or
+//! [`DMatrix::load_csv`](../struct.DMatrix.html#method.load_csv) and
+//! [`DMatrix::load_libsvm`](../struct.DMatrix.html#method.load_libsvm) for possibly gzip compressed files) parse
+//! data in Rust, configured through [`CsvOptions`](struct.CsvOptions.html) and
+//! [`LibSvmOptions`](struct.LibSvmOptions.html), and report the line and column of any invalid values.
+//!
+//! Data written here can be loaded with these readers, with [`DMatrix::load`](../struct.DMatrix.html#method.load)
+//! or passed to the `xgboost` command line tool:
+//!
+//! * LIBSVM files use 0-based feature indices, with each row written as
+//!   `<label>[:<weight>] [qid:<group>] <index>:<value> ...`. Missing values are omitted.
+//! * CSV files have no header, and hold the label and weight columns (where set) followed by the features.
+//!   Missing values are written as `nan`. Load them by appending
+//!   [`RowInfo::csv_uri_options`](struct.RowInfo.html#method.csv_uri_options) to the path, e.g.
+//!   `?format=csv&label_column=0&weight_column=1`. Query groups can't be written, as XGBoost's CSV loader has no
+//!   query group column.
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+use crate::dmatrix::Compressed;
+use crate::{DMatrix, SparseIndex, XGBError, XGBResult};
+
+/// Labels, weights and query groups written alongside the features of each row.
+///
+/// Empty slices are treated as not set. Labels default to 0 in LIBSVM files, as the format requires a label.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RowInfo<'a> {
+    labels: &'a [f32],
+    weights: &'a [f32],
+    group_ptr: &'a [u32],
+}
+
+impl<'a> RowInfo<'a> {
+    /// Create row info with one label per row.
+    pub fn new(labels: &'a [f32]) -> Self {
+        RowInfo {
+            labels,
+            ..Default::default()
+        }
+    }
+
+    /// Set one weight per row.
+    pub fn with_weights(mut self, weights: &'a [f32]) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Set query group boundaries, where rows `group_ptr[i]..group_ptr[i + 1]` belong to group _i_ (as returned
+    /// by [`DMatrix::get_group`](../struct.DMatrix.html#method.get_group)).
+    pub fn with_group_ptr(mut self, group_ptr: &'a [u32]) -> Self {
+        self.group_ptr = group_ptr;
+        self
+    }
+
+    /// Get the labels of each row.
+    pub fn labels(&self) -> &'a [f32] {
+        self.labels
+    }
+
+    /// Get the weights of each row.
+    pub fn weights(&self) -> &'a [f32] {
+        self.weights
+    }
+
+    /// Get the query group boundaries.
+    pub fn group_ptr(&self) -> &'a [u32] {
+        self.group_ptr
+    }
+
+    /// Get the URI options for loading a CSV file written with this info through
+    /// [`DMatrix::load`](../struct.DMatrix.html#method.load), marking the label and weight columns.
+    ///
+    /// ```
+    /// use xgb::text::RowInfo;
+    ///
+    /// let info = RowInfo::new(&[1.0, 0.0]).with_weights(&[0.5, 2.0]);
+    /// assert_eq!(info.csv_uri_options(), "?format=csv&label_column=0&weight_column=1");
+    /// assert_eq!(RowInfo::default().with_weights(&[0.5]).csv_uri_options(), "?format=csv&weight_column=0");
+    /// ```
+    pub fn csv_uri_options(&self) -> String {
+        let mut options = "?format=csv".to_owned();
+        let mut column = 0;
+        if !self.labels.is_empty() {
+            options.push_str("&label_column=0");
+            column += 1;
+        }
+        if !self.weights.is_empty() {
+            options.push_str(&format!("&weight_column={}", column));
+        }
+        options
+    }
+
+    /// Check the info matches `num_rows`, returning the query group of each row if groups are set.
+    fn validate(&self, num_rows: usize) -> XGBResult<Option<Vec<usize>>> {
+        for (name, values) in [("labels", self.labels), ("weights", self.weights)] {
+            if !values.is_empty() && values.len() != num_rows {
+                return Err(XGBError::new(format!(
+                    "Expected one value per row for {} ({} rows), got {} values",
+                    name,
+                    num_rows,
+                    values.len()
+                )));
+            }
+        }
+
+        if self.group_ptr.is_empty() {
+            return Ok(None);
+        }
+        let valid = self.group_ptr[0] == 0
+            && self.group_ptr.windows(2).all(|bounds| bounds[0] <= bounds[1])
+            && self.group_ptr[self.group_ptr.len() - 1] as usize == num_rows;
+        if !valid {
+            return Err(XGBError::new(format!(
+                "Group boundaries must start at 0, be non-decreasing and end at the number of rows ({})",
+                num_rows
+            )));
+        }
+        let mut qids = Vec::with_capacity(num_rows);
+        for (group, bounds) in self.group_ptr.windows(2).enumerate() {
+            qids.extend(std::iter::repeat_n(group, (bounds[1] - bounds[0]) as usize));
+        }
+        Ok(Some(qids))
+    }
+}
+
+/// Write a sparse CSR matrix in LIBSVM format, see [`DMatrix::from_csr`](../struct.DMatrix.html#method.from_csr)
+/// for the layout of `indptr`, `indices` and `data`.
+///
+/// ```
+/// use xgb::text::{self, RowInfo};
+///
+/// let mut out = Vec::new();
+/// let info = RowInfo::new(&[1.0, 0.0]).with_weights(&[0.5, 2.0]);
+/// text::write_libsvm(&mut out, &[0u32, 2, 3], &[0u32, 3, 1], &[1.5, 2.0, 3.0], &info).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "1:0.5 0:1.5 3:2\n0:2 1:3\n");
+/// ```
+pub fn write_libsvm<W: Write, P: SparseIndex, I: SparseIndex>(
+    writer: W,
+    indptr: &[P],
+    indices: &[I],
+    data: &[f32],
+    info: &RowInfo,
+) -> XGBResult<()> {
+    if indices.len() != data.len() {
+        return Err(XGBError::new(format!(
+            "Number of indices ({}) does not match number of values ({})",
+            indices.len(),
+            data.len()
+        )));
+    }
+    Compressed::Csr.validate(indptr, indices, data.len(), None)?;
+    let num_rows = indptr.len() - 1;
+    let qids = info.validate(num_rows)?;
+
+    let mut writer = BufWriter::new(writer);
+    for row in 0..num_rows {
+        write!(writer, "{}", info.labels.get(row).copied().unwrap_or(0.0))?;
+        if let Some(weight) = info.weights.get(row) {
+            write!(writer, ":{}", weight)?;
+        }
+        if let Some(qids) = &qids {
+            write!(writer, " qid:{}", qids[row])?;
+        }
+        // bounds were checked by validate
+        let start = indptr[row].to_usize().unwrap();
+        let end = indptr[row + 1].to_usize().unwrap();
+        for (index, value) in indices[start..end].iter().zip(&data[start..end]) {
+            write!(writer, " {}:{}", index.to_usize().unwrap(), value)?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a dense row-major matrix with `num_cols` columns in CSV format, writing `NaN` values as `nan`.
+///
+/// Returns an error if `info` has query groups, which XGBoost can't load from CSV files; use
+/// [`write_libsvm`](fn.write_libsvm.html) instead.
+///
+/// ```
+/// use xgb::text::{self, RowInfo};
+///
+/// let mut out = Vec::new();
+/// let info = RowInfo::new(&[1.0, 0.0]);
+/// text::write_csv(&mut out, &[1.5, f32::NAN, 0.0, 2.0], 2, &info).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "1,1.5,nan\n0,0,2\n");
+/// ```
+pub fn write_csv<W: Write>(writer: W, data: &[f32], num_cols: usize, info: &RowInfo) -> XGBResult<()> {
+    if num_cols == 0 || !data.len().is_multiple_of(num_cols) {
+        return Err(XGBError::new(format!(
+            "Data of length {} can't be split into rows of {} columns",
+            data.len(),
+            num_cols
+        )));
+    }
+    let num_rows = data.len() / num_cols;
+    if info.validate(num_rows)?.is_some() {
+        return Err(XGBError::new(
+            "CSV files can't hold query groups, as XGBoost's CSV loader has no query group column, use LIBSVM instead",
+        ));
+    }
+
+    let mut writer = BufWriter::new(writer);
+    for (row, values) in data.chunks(num_cols).enumerate() {
+        let mut fields = Vec::with_capacity(num_cols + 2);
+        if let Some(label) = info.labels.get(row) {
+            fields.push(label.to_string());
+        }
+        if let Some(weight) = info.weights.get(row) {
+            fields.push(weight.to_string());
+        }
+        fields.extend(values.iter().map(|value| csv_value(*value)));
+        writeln!(writer, "{}", fields.join(","))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn csv_value(value: f32) -> String {
+    if value.is_nan() { "nan".to_owned() } else { value.to_string() }
+}
+
+/// Options used when reading CSV data with [`read_csv`](fn.read_csv.html) or
+/// [`DMatrix::load_csv`](../struct.DMatrix.html#method.load_csv). Create using
+/// [`CsvOptionsBuilder`](struct.CsvOptionsBuilder.html).
+///
+/// Columns are referred to by their 0-based index. Quoted fields aren't supported.
+///
+/// ```
+/// use xgb::text::CsvOptionsBuilder;
+///
+/// // tab separated file with a header row, labels in the first column and "?" for missing values
+/// let options = CsvOptionsBuilder::default()
+///     .delimiter(b'\t')
+///     .has_header(true)
+///     .label_column(Some(0))
+///     .missing_tokens(vec!["?".to_owned()])
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+#[builder(default)]
+pub struct CsvOptions {
+    /// Byte separating fields.
+    ///
+    /// *default*: `b','`
+    delimiter: u8,
+
+    /// Whether the first line holds column names instead of data.
+    ///
+    /// *default*: `false`
+    has_header: bool,
+
+    /// Column holding the label of each row.
+    ///
+    /// *default*: `None`
+    label_column: Option<usize>,
+
+    /// Column holding the weight of each row.
+    ///
+    /// *default*: `None`
+    weight_column: Option<usize>,
+
+    /// Column holding integer query group ids, rows of the same group must be contiguous.
+    ///
+    /// *default*: `None`
+    qid_column: Option<usize>,
+
+    /// Tokens treated as missing values (after trimming whitespace), in addition to values that parse as `NaN`.
+    ///
+    /// *default*: `["", "NA"]`
+    missing_tokens: Vec<String>,
+
+    /// Columns used as features, in the given order. If not set, all columns other than the label, weight and
+    /// query group columns are used.
+    ///
+    /// *default*: `None`
+    feature_columns: Option<Vec<usize>>,
+
+    /// Whether to use the header's column names as feature names, ignored if there is no header.
+    ///
+    /// *default*: `true`
+    feature_names_from_header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            has_header: false,
+            label_column: None,
+            weight_column: None,
+            qid_column: None,
+            missing_tokens: vec![String::new(), "NA".to_owned()],
+            feature_columns: None,
+            feature_names_from_header: true,
+        }
+    }
+}
+
+impl CsvOptionsBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(delimiter) = self.delimiter
+            && (!delimiter.is_ascii() || delimiter == b'\n' || delimiter == b'\r')
+        {
+            return Err(format!(
+                "Invalid delimiter {:?}, expected an ASCII character",
+                delimiter as char
+            ));
+        }
+        let info_columns: Vec<usize> = [self.label_column, self.weight_column, self.qid_column]
+            .into_iter()
+            .filter_map(|column| column.flatten())
+            .collect();
+        for (i, column) in info_columns.iter().enumerate() {
+            if info_columns[..i].contains(column) {
+                return Err(format!(
+                    "Column {} is used for more than one of label, weight and qid",
+                    column
+                ));
+            }
+        }
+        if let Some(Some(feature_columns)) = &self.feature_columns
+            && let Some(column) = feature_columns.iter().find(|column| info_columns.contains(column))
+        {
+            return Err(format!(
+                "Column {} is used as both a feature and label, weight or qid",
+                column
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl CsvOptions {
+    /// Get the byte separating fields.
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    /// Get whether the first line holds column names.
+    pub fn has_header(&self) -> bool {
+        self.has_header
+    }
+
+    /// Get the column holding labels.
+    pub fn label_column(&self) -> Option<usize> {
+        self.label_column
+    }
+
+    /// Get the column holding weights.
+    pub fn weight_column(&self) -> Option<usize> {
+        self.weight_column
+    }
+
+    /// Get the column holding query group ids.
+    pub fn qid_column(&self) -> Option<usize> {
+        self.qid_column
+    }
+
+    /// Get the tokens treated as missing values.
+    pub fn missing_tokens(&self) -> &[String] {
+        &self.missing_tokens
+    }
+
+    /// Get the columns used as features, if set.
+    pub fn feature_columns(&self) -> Option<&[usize]> {
+        self.feature_columns.as_deref()
+    }
+
+    /// Get whether the header's column names are used as feature names.
+    pub fn feature_names_from_header(&self) -> bool {
+        self.feature_names_from_header
+    }
+
+    /// Columns used as features in a file with `num_fields` fields per line.
+    fn resolve_feature_columns(&self, num_fields: usize) -> Vec<usize> {
+        match &self.feature_columns {
+            Some(columns) => columns.clone(),
+            None => (0..num_fields)
+                .filter(|column| ![self.label_column, self.weight_column, self.qid_column].contains(&Some(*column)))
+                .collect(),
+        }
+    }
+}
+
+/// Options used when reading LIBSVM data with [`read_libsvm`](fn.read_libsvm.html) or
+/// [`DMatrix::load_libsvm`](../struct.DMatrix.html#method.load_libsvm). Create using
+/// [`LibSvmOptionsBuilder`](struct.LibSvmOptionsBuilder.html).
+///
+/// ```
+/// use xgb::text::LibSvmOptionsBuilder;
+///
+/// let options = LibSvmOptionsBuilder::default().one_based(true).num_cols(Some(128)).build().unwrap();
+/// ```
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(default)]
+pub struct LibSvmOptions {
+    /// Whether feature indices start at 1 instead of 0.
+    ///
+    /// *default*: `false`
+    one_based: bool,
+
+    /// Number of features, inferred from the largest feature index if not set.
+    ///
+    /// *default*: `None`
+    num_cols: Option<usize>,
+}
+
+impl LibSvmOptions {
+    /// Get whether feature indices start at 1.
+    pub fn one_based(&self) -> bool {
+        self.one_based
+    }
+
+    /// Get the number of features, if set.
+    pub fn num_cols(&self) -> Option<usize> {
+        self.num_cols
+    }
+}
+
+fn parse_error(line: usize, column: usize, msg: impl Display) -> XGBError {
+    XGBError::new(format!("line {}, column {}: {}", line, column, msg))
+}
+
+/// Labels, weights and query groups parsed from a text file.
+#[derive(Debug, Default, PartialEq)]
+struct ParsedInfo {
+    labels: Vec<f32>,
+    weights: Vec<f32>,
+    group_sizes: Vec<u32>,
+    last_qid: Option<u64>,
+    seen_qids: HashSet<u64>,
+}
+
+impl ParsedInfo {
+    /// Add a row's query group, which must either continue the previous row's group or start a new one.
+    fn push_qid(&mut self, qid: u64, line: usize, column: usize) -> XGBResult<()> {
+        if self.last_qid == Some(qid) {
+            *self.group_sizes.last_mut().unwrap() += 1;
+            return Ok(());
+        }
+        if !self.seen_qids.insert(qid) {
+            return Err(parse_error(
+                line,
+                column,
+                format!("rows of query group {} are not contiguous", qid),
+            ));
+        }
+        self.group_sizes.push(1);
+        self.last_qid = Some(qid);
+        Ok(())
+    }
+
+    fn apply(&self, dmat: &mut DMatrix) -> XGBResult<()> {
+        if !self.labels.is_empty() {
+            dmat.set_labels(&self.labels)?;
+        }
+        if !self.weights.is_empty() {
+            dmat.set_weights(&self.weights)?;
+        }
+        if !self.group_sizes.is_empty() {
+            dmat.set_group(&self.group_sizes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Dense row-major features parsed from a CSV file.
+#[derive(Debug, PartialEq)]
+struct CsvData {
+    data: Vec<f32>,
+    num_rows: usize,
+    feature_names: Vec<String>,
+    info: ParsedInfo,
+}
+
+fn parse_csv<R: BufRead>(reader: R, options: &CsvOptions) -> XGBResult<CsvData> {
+    let parse_value = |field: &str, line: usize, column: usize| -> XGBResult<f32> {
+        let field = field.trim();
+        if options.missing_tokens.iter().any(|token| token == field) {
+            return Ok(f32::NAN);
+        }
+        field
+            .parse()
+            .map_err(|_| parse_error(line, column, format!("invalid number '{}'", field)))
+    };
+    let parse_required = |field: &str, line: usize, column: usize, name: &str| -> XGBResult<f32> {
+        let value = parse_value(field, line, column)?;
+        if value.is_nan() {
+            return Err(parse_error(line, column, format!("missing {}", name)));
+        }
+        Ok(value)
+    };
+
+    let delimiter = options.delimiter as char;
+    let mut num_fields = None;
+    let mut feature_columns = Vec::new();
+    let mut header = None;
+    let mut data = Vec::new();
+    let mut num_rows = 0;
+    let mut info = ParsedInfo::default();
+    for (i, line) in reader.lines().enumerate() {
+        let line_num = i + 1;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(delimiter).collect();
+        match num_fields {
+            None => {
+                num_fields = Some(fields.len());
+                feature_columns = options.resolve_feature_columns(fields.len());
+                let referenced = [options.label_column, options.weight_column, options.qid_column];
+                if let Some(column) = referenced
+                    .into_iter()
+                    .flatten()
+                    .chain(feature_columns.iter().copied())
+                    .find(|column| *column >= fields.len())
+                {
+                    return Err(parse_error(
+                        line_num,
+                        column + 1,
+                        format!("column {} is out of range for {} fields", column, fields.len()),
+                    ));
+                }
+            }
+            Some(expected) if expected != fields.len() => {
+                return Err(parse_error(
+                    line_num,
+                    expected.min(fields.len()) + 1,
+                    format!("expected {} fields, got {}", expected, fields.len()),
+                ));
+            }
+            Some(_) => {}
+        }
+        if options.has_header && header.is_none() {
+            header = Some(fields.iter().map(|field| field.trim().to_owned()).collect::<Vec<_>>());
+            continue;
+        }
+
+        if let Some(column) = options.label_column {
+            info.labels
+                .push(parse_required(fields[column], line_num, column + 1, "label")?);
+        }
+        if let Some(column) = options.weight_column {
+            info.weights
+                .push(parse_required(fields[column], line_num, column + 1, "weight")?);
+        }
+        if let Some(column) = options.qid_column {
+            let field = fields[column].trim();
+            let qid = field
+                .parse()
+                .map_err(|_| parse_error(line_num, column + 1, format!("invalid query group id '{}'", field)))?;
+            info.push_qid(qid, line_num, column + 1)?;
+        }
+        for column in &feature_columns {
+            data.push(parse_value(fields[*column], line_num, column + 1)?);
+        }
+        num_rows += 1;
+    }
+
+    let feature_names = match header {
+        Some(header) if options.feature_names_from_header => {
+            feature_columns.iter().map(|column| header[*column].clone()).collect()
+        }
+        _ => Vec::new(),
+    };
+    Ok(CsvData {
+        data,
+        num_rows,
+        feature_names,
+        info,
+    })
+}
+
+/// Sparse CSR features parsed from a LIBSVM file.
+#[derive(Debug, PartialEq)]
+struct LibSvmData {
+    indptr: Vec<u64>,
+    indices: Vec<u32>,
+    data: Vec<f32>,
+    num_cols: usize,
+    info: ParsedInfo,
+}
+
+fn parse_libsvm<R: BufRead>(reader: R, options: &LibSvmOptions) -> XGBResult<LibSvmData> {
+    let mut indptr = vec![0];
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    let mut info = ParsedInfo::default();
+    let mut has_weights = None;
+    let mut has_qids = None;
+    let mut max_index = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line_num = i + 1;
+        let line = line?;
+        let content = line.split('#').next().unwrap();
+        let mut tokens = content
+            .split_whitespace()
+            .map(|token| (token.as_ptr() as usize - content.as_ptr() as usize + 1, token));
+        let Some((column, label_token)) = tokens.next() else {
+            continue;
+        };
+
+        let (label, weight) = match label_token.split_once(':') {
+            Some((label, weight)) => (label, Some(weight)),
+            None => (label_token, None),
+        };
+        let label = label
+            .parse()
+            .map_err(|_| parse_error(line_num, column, format!("invalid label '{}'", label)))?;
+        info.labels.push(label);
+        if *has_weights.get_or_insert(weight.is_some()) != weight.is_some() {
+            return Err(parse_error(
+                line_num,
+                column,
+                "weights must be given for all rows or none",
+            ));
+        }
+        if let Some(weight) = weight {
+            let weight = weight
+                .parse()
+                .map_err(|_| parse_error(line_num, column, format!("invalid weight '{}'", weight)))?;
+            info.weights.push(weight);
+        }
+
+        let mut row_has_qid = false;
+        for (column, token) in tokens {
+            let Some((key, value)) = token.split_once(':') else {
+                return Err(parse_error(
+                    line_num,
+                    column,
+                    format!("expected '<index>:<value>', got '{}'", token),
+                ));
+            };
+            if key == "qid" {
+                if row_has_qid {
+                    return Err(parse_error(line_num, column, "query group id given twice"));
+                }
+                let qid = value
+                    .parse()
+                    .map_err(|_| parse_error(line_num, column, format!("invalid query group id '{}'", value)))?;
+                info.push_qid(qid, line_num, column)?;
+                row_has_qid = true;
+                continue;
+            }
+
+            let index: usize = key
+                .parse()
+                .map_err(|_| parse_error(line_num, column, format!("invalid feature index '{}'", key)))?;
+            let index = if options.one_based {
+                index
+                    .checked_sub(1)
+                    .ok_or_else(|| parse_error(line_num, column, "feature index 0 in one-based data"))?
+            } else {
+                index
+            };
+            if let Some(num_cols) = options.num_cols
+                && index >= num_cols
+            {
+                return Err(parse_error(
+                    line_num,
+                    column,
+                    format!("feature index {} is out of range for {} features", key, num_cols),
+                ));
+            }
+            let index = u32::try_from(index)
+                .map_err(|_| parse_error(line_num, column, format!("feature index {} is too large", key)))?;
+            let value = value
+                .parse()
+                .map_err(|_| parse_error(line_num, column, format!("invalid value '{}'", value)))?;
+            max_index = max_index.max(Some(index));
+            indices.push(index);
+            data.push(value);
+        }
+        if *has_qids.get_or_insert(row_has_qid) != row_has_qid {
+            return Err(parse_error(
+                line_num,
+                1,
+                "query group ids must be given for all rows or none",
+            ));
+        }
+        indptr.push(indices.len() as u64);
+    }
+
+    let num_cols = options
+        .num_cols
+        .unwrap_or_else(|| max_index.map_or(0, |index| index as usize + 1));
+    Ok(LibSvmData {
+        indptr,
+        indices,
+        data,
+        num_cols,
+        info,
+    })
+}
+
+/// Read CSV data into a new `DMatrix`, see [`CsvOptions`](struct.CsvOptions.html) for the supported options.
+///
+/// Errors while parsing report the 1-based line and field number of the offending value.
+///
+/// ```
+/// use xgb::text::{self, CsvOptionsBuilder};
+///
+/// let csv = "y,age,income\n1,31,NA\n0,45,52000\n";
+/// let options = CsvOptionsBuilder::default().has_header(true).label_column(Some(0)).build().unwrap();
+/// let dmat = text::read_csv(csv.as_bytes(), &options).unwrap();
+/// assert_eq!(dmat.shape(), (2, 2));
+/// assert_eq!(dmat.get_feature_names().unwrap(), vec!["age", "income"]);
+/// ```
+pub fn read_csv<R: BufRead>(reader: R, options: &CsvOptions) -> XGBResult<DMatrix> {
+    let parsed = parse_csv(reader, options)?;
+    if parsed.num_rows == 0 {
+        return Err(XGBError::new("CSV data has no rows"));
+    }
+    let mut dmat = DMatrix::from_dense(&parsed.data, parsed.num_rows)?;
+    parsed.info.apply(&mut dmat)?;
+    if !parsed.feature_names.is_empty() {
+        let names: Vec<&str> = parsed.feature_names.iter().map(String::as_str).collect();
+        dmat.set_feature_names(&names)?;
+    }
+    Ok(dmat)
+}
+
+/// Read LIBSVM data into a new `DMatrix`, see [`LibSvmOptions`](struct.LibSvmOptions.html) for the supported
+/// options.
+///
+/// Each line holds `<label>[:<weight>] [qid:<group>] <index>:<value> ...`, text after `#` is ignored. Errors while
+/// parsing report the 1-based line and character position of the offending token.
+///
+/// ```
+/// use xgb::text::{self, LibSvmOptions};
+///
+/// let libsvm = "1 0:0.5 3:1\n0 1:1.5 # comment\n";
+/// let dmat = text::read_libsvm(libsvm.as_bytes(), &LibSvmOptions::default()).unwrap();
+/// assert_eq!(dmat.shape(), (2, 4));
+/// assert_eq!(dmat.get_weights().unwrap(), &[]);
+/// ```
+pub fn read_libsvm<R: BufRead>(reader: R, options: &LibSvmOptions) -> XGBResult<DMatrix> {
+    let parsed = parse_libsvm(reader, options)?;
+    let mut dmat = DMatrix::from_csr(&parsed.indptr, &parsed.indices, &parsed.data, Some(parsed.num_cols))?;
+    parsed.info.apply(&mut dmat)?;
+    Ok(dmat)
+}
+
+/// Open a file for reading, decompressing it if it starts with the gzip magic bytes.
+fn open_text<P: AsRef<Path>>(path: P) -> XGBResult<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+impl DMatrix {
+    /// Write the features, labels, weights and query groups of this matrix in LIBSVM format, see the
+    /// [`text`](text/index.html) module for details of the format.
+    pub fn write_libsvm<W: Write>(&self, writer: W) -> XGBResult<()> {
+        let (indptr, indices, data) = self.get_data_as_csr()?;
+        write_libsvm(writer, &indptr, &indices, &data, &self.row_info()?)
+    }
+
+    /// Write the features, labels and weights of this matrix in CSV format, see the [`text`](text/index.html)
+    /// module for details of the format. Returns an error if the matrix has query groups.
+    pub fn write_csv<W: Write>(&self, writer: W) -> XGBResult<()> {
+        let dense = self.get_data_as_dense()?;
+        write_csv(writer, &dense, self.num_cols().max(1), &self.row_info()?)
+    }
+
+    /// Write this matrix to a LIBSVM file at given path, see [`write_libsvm`](#method.write_libsvm).
+    ///
+    /// ```no_run
+    /// use xgb::DMatrix;
+    ///
+    /// let mut dmat = DMatrix::from_dense(&[1.0, 0.0, 0.0, 2.0], 2).unwrap();
+    /// dmat.set_labels(&[1.0, 0.0]).unwrap();
+    /// dmat.save_libsvm("data.libsvm").unwrap();
+    ///
+    /// let loaded = DMatrix::load("data.libsvm?format=libsvm").unwrap();
+    /// ```
+    pub fn save_libsvm<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        debug!("Writing DMatrix as LIBSVM to: {}", path.as_ref().display());
+        self.write_libsvm(File::create(path)?)
+    }
+
+    /// Write this matrix to a CSV file at given path, see [`write_csv`](#method.write_csv).
+    ///
+    /// ```no_run
+    /// use xgb::DMatrix;
+    ///
+    /// let mut dmat = DMatrix::from_dense(&[1.0, 0.0, 0.0, 2.0], 2).unwrap();
+    /// dmat.set_labels(&[1.0, 0.0]).unwrap();
+    /// dmat.save_csv("data.csv").unwrap();
+    ///
+    /// let loaded = DMatrix::load("data.csv?format=csv&label_column=0").unwrap();
+    /// ```
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> XGBResult<()> {
+        debug!("Writing DMatrix as CSV to: {}", path.as_ref().display());
+        self.write_csv(File::create(path)?)
+    }
+
+    /// Read a CSV file into a new `DMatrix`, see [`text::read_csv`](text/fn.read_csv.html). Files starting with
+    /// the gzip magic bytes are decompressed.
+    ///
+    /// ```no_run
+    /// use xgb::DMatrix;
+    /// use xgb::text::CsvOptionsBuilder;
+    ///
+    /// let options = CsvOptionsBuilder::default()
+    ///     .has_header(true)
+    ///     .label_column(Some(0))
+    ///     .weight_column(Some(1))
+    ///     .build()
+    ///     .unwrap();
+    /// let dmat = DMatrix::load_csv("train.csv.gz", &options).unwrap();
+    /// ```
+    pub fn load_csv<P: AsRef<Path>>(path: P, options: &CsvOptions) -> XGBResult<Self> {
+        debug!("Reading DMatrix from CSV: {}", path.as_ref().display());
+        read_csv(open_text(path)?, options)
+    }
+
+    /// Read a LIBSVM file into a new `DMatrix`, see [`text::read_libsvm`](text/fn.read_libsvm.html). Files
+    /// starting with the gzip magic bytes are decompressed.
+    pub fn load_libsvm<P: AsRef<Path>>(path: P, options: &LibSvmOptions) -> XGBResult<Self> {
+        debug!("Reading DMatrix from LIBSVM: {}", path.as_ref().display());
+        read_libsvm(open_text(path)?, options)
+    }
+
+    fn row_info(&self) -> XGBResult<RowInfo<'_>> {
+        Ok(RowInfo::new(self.get_labels()?)
+            .with_weights(self.get_weights()?)
+            .with_group_ptr(self.get_group()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_string<F: FnOnce(&mut Vec<u8>) -> XGBResult<()>>(write: F) -> String {
+        let mut out = Vec::new();
+        write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn libsvm() {
+        let indptr = [0u64, 2, 2, 3];
+        let indices = [0u32, 4, 1];
+        let data = [1.0, 0.25, -3.0];
+        let out = to_string(|out| write_libsvm(out, &indptr, &indices, &data, &RowInfo::default()));
+        assert_eq!(out, "0 0:1 4:0.25\n0\n0 1:-3\n");
+
+        let info = RowInfo::new(&[1.0, 0.0, 2.0])
+            .with_weights(&[1.0, 0.5, 2.0])
+            .with_group_ptr(&[0, 2, 3]);
+        let out = to_string(|out| write_libsvm(out, &indptr, &indices, &data, &info));
+        assert_eq!(out, "1:1 qid:0 0:1 4:0.25\n0:0.5 qid:0\n2:2 qid:1 1:-3\n");
+
+        let mut out = Vec::new();
+        assert!(write_libsvm(&mut out, &[0u64, 3, 2], &indices, &data, &RowInfo::default()).is_err());
+        assert!(write_libsvm(&mut out, &indptr, &indices, &data, &RowInfo::new(&[1.0])).is_err());
+        let bad_groups = RowInfo::default().with_group_ptr(&[0, 2]);
+        assert!(write_libsvm(&mut out, &indptr, &indices, &data, &bad_groups).is_err());
+    }
+
+    #[test]
+    fn csv() {
+        let data = [1.0, f32::NAN, 0.5, 2.0, 3.0, f32::INFINITY];
+        let out = to_string(|out| write_csv(out, &data, 3, &RowInfo::default()));
+        assert_eq!(out, "1,nan,0.5\n2,3,inf\n");
+
+        let info = RowInfo::new(&[0.0, 1.0]).with_weights(&[2.0, 1.0]);
+        let out = to_string(|out| write_csv(out, &data, 3, &info));
+        assert_eq!(out, "0,2,1,nan,0.5\n1,1,2,3,inf\n");
+        assert_eq!(info.csv_uri_options(), "?format=csv&label_column=0&weight_column=1");
+        assert_eq!(RowInfo::default().csv_uri_options(), "?format=csv");
+
+        // read back with the columns named by the URI options
+        let options = csv_options()
+            .label_column(Some(0))
+            .weight_column(Some(1))
+            .missing_tokens(vec!["nan".to_owned()])
+            .build()
+            .unwrap();
+        let parsed = parse_csv(out.as_bytes(), &options).unwrap();
+        assert_eq!(parsed.info.labels, vec![0.0, 1.0]);
+        assert_eq!(parsed.info.weights, vec![2.0, 1.0]);
+        assert_eq!(parsed.data[0], 1.0);
+        assert!(parsed.data[1].is_nan());
+        assert_eq!(&parsed.data[2..], &data[2..]);
+
+        let grouped = info.with_group_ptr(&[0, 1, 2]);
+        let mut out = Vec::new();
+        let err = write_csv(&mut out, &data, 3, &grouped).unwrap_err();
+        assert!(err.to_string().contains("can't hold query groups"));
+        assert!(out.is_empty());
+
+        let mut out = Vec::new();
+        assert!(write_csv(&mut out, &data, 4, &RowInfo::default()).is_err());
+        assert!(write_csv(&mut out, &data, 0, &RowInfo::default()).is_err());
+        assert!(write_csv(&mut out, &data, 3, &RowInfo::default().with_weights(&[1.0])).is_err());
+    }
+
+    fn csv_options() -> CsvOptionsBuilder {
+        CsvOptionsBuilder::default()
+    }
+
+    #[test]
+    fn csv_options_validation() {
+        assert!(
+            csv_options()
+                .label_column(Some(0))
+                .weight_column(Some(0))
+                .build()
+                .is_err()
+        );
+        assert!(
+            csv_options()
+                .qid_column(Some(1))
+                .feature_columns(Some(vec![0, 1]))
+                .build()
+                .is_err()
+        );
+        assert!(csv_options().delimiter(b'\n').build().is_err());
+        assert!(
+            csv_options()
+                .label_column(Some(0))
+                .feature_columns(Some(vec![2, 1]))
+                .build()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn parse_csv_columns() {
+        let csv = "qid;y;w;a;b\n\n7;1;0.5;1.5;NA\n7;0;1;?;2\n3;1;2; 3 ;4\n";
+        let options = csv_options()
+            .delimiter(b';')
+            .has_header(true)
+            .label_column(Some(1))
+            .weight_column(Some(2))
+            .qid_column(Some(0))
+            .missing_tokens(vec!["NA".to_owned(), "?".to_owned()])
+            .build()
+            .unwrap();
+        let parsed = parse_csv(csv.as_bytes(), &options).unwrap();
+        assert_eq!(parsed.num_rows, 3);
+        assert_eq!(parsed.feature_names, vec!["a", "b"]);
+        assert_eq!(parsed.info.labels, vec![1.0, 0.0, 1.0]);
+        assert_eq!(parsed.info.weights, vec![0.5, 1.0, 2.0]);
+        assert_eq!(parsed.info.group_sizes, vec![2, 1]);
+        assert_eq!(parsed.data[0], 1.5);
+        assert!(parsed.data[1].is_nan() && parsed.data[2].is_nan());
+        assert_eq!(&parsed.data[3..], &[2.0, 3.0, 4.0]);
+
+        // column selection without a header
+        let options = csv_options().feature_columns(Some(vec![2, 0])).build().unwrap();
+        let parsed = parse_csv("1,2,3\n4,5,6\n".as_bytes(), &options).unwrap();
+        assert_eq!(parsed.data, vec![3.0, 1.0, 6.0, 4.0]);
+        assert!(parsed.feature_names.is_empty());
+    }
+
+    #[test]
+    fn parse_csv_errors() {
+        let error = |csv: &str, options: CsvOptions| parse_csv(csv.as_bytes(), &options).unwrap_err().to_string();
+        let options = csv_options().label_column(Some(0)).build().unwrap();
+
+        assert!(error("1,2\n0,x\n", options.clone()).contains("line 2, column 2: invalid number 'x'"));
+        assert!(error("1,2\n0,1,3\n", options.clone()).contains("line 2, column 3: expected 2 fields, got 3"));
+        assert!(error("1,2\nNA,1\n", options.clone()).contains("line 2, column 1: missing label"));
+        let options = csv_options().feature_columns(Some(vec![3])).build().unwrap();
+        assert!(error("1,2\n", options).contains("column 3 is out of range for 2 fields"));
+        let options = csv_options().qid_column(Some(0)).build().unwrap();
+        assert!(error("1,2\n2,2\n1,3\n", options).contains("line 3, column 1: rows of query group 1"));
+    }
+
+    #[test]
+    fn parse_libsvm_rows() {
+        let libsvm = "# header comment\n1:0.5 qid:2 1:1.5 3:-2\n\n0:2 qid:2 2:1 # comment\n1:1 qid:5\n";
+        let options = LibSvmOptionsBuilder::default().one_based(true).build().unwrap();
+        let parsed = parse_libsvm(libsvm.as_bytes(), &options).unwrap();
+        assert_eq!(parsed.indptr, vec![0, 2, 3, 3]);
+        assert_eq!(parsed.indices, vec![0, 2, 1]);
+        assert_eq!(parsed.data, vec![1.5, -2.0, 1.0]);
+        assert_eq!(parsed.num_cols, 3);
+        assert_eq!(parsed.info.labels, vec![1.0, 0.0, 1.0]);
+        assert_eq!(parsed.info.weights, vec![0.5, 2.0, 1.0]);
+        assert_eq!(parsed.info.group_sizes, vec![2, 1]);
+
+        let options = LibSvmOptionsBuilder::default().num_cols(Some(10)).build().unwrap();
+        assert_eq!(parse_libsvm("1 0:1\n".as_bytes(), &options).unwrap().num_cols, 10);
+    }
+
+    #[test]
+    fn parse_libsvm_errors() {
+        let error =
+            |libsvm: &str, options: LibSvmOptions| parse_libsvm(libsvm.as_bytes(), &options).unwrap_err().to_string();
+        let options = LibSvmOptions::default();
+
+        assert!(error("1 0:1\n0 1:x\n", options.clone()).contains("line 2, column 3: invalid value 'x'"));
+        assert!(error("1 0:1 bad\n", options.clone()).contains("line 1, column 7: expected '<index>:<value>'"));
+        assert!(error("1 0:1\n0:2 1:1\n", options.clone()).contains("line 2, column 1: weights must be given"));
+        assert!(error("1 qid:1 0:1\n0 1:1\n", options.clone()).contains("query group ids must be given"));
+        assert!(
+            error("1 qid:1 0:1 qid:1\n", options.clone()).contains("line 1, column 13: query group id given twice")
+        );
+        assert!(error("a 0:1\n", options).contains("invalid label 'a'"));
+        let one_based = LibSvmOptionsBuilder::default().one_based(true).build().unwrap();
+        assert!(error("1 0:1\n", one_based).contains("feature index 0 in one-based data"));
+        let limited = LibSvmOptionsBuilder::default().num_cols(Some(2)).build().unwrap();
+        assert!(error("1 2:1\n", limited).contains("feature index 2 is out of range for 2 features"));
+    }
+
+    #[test]
+    fn load_gzip() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Read;
+
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp_dir.path().join("data.libsvm.gz");
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        encoder.write_all(b"1 0:1 2:3\n0 1:2\n").unwrap();
+        encoder.finish().unwrap();
+
+        let mut contents = String::new();
+        open_text(&path).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "1 0:1 2:3\n0 1:2\n");
+
+        let dmat = DMatrix::load_libsvm(&path, &LibSvmOptions::default()).unwrap();
+        assert_eq!(dmat.shape(), (2, 3));
+        assert_eq!(dmat.get_labels().unwrap(), &[1.0, 0.0]);
+    }
+
+    #[test]
+    fn dmatrix_round_trip() {
+        let mut dmat = DMatrix::from_dense(&[1.0, f32::NAN, 0.0, 2.0, 3.0, 4.0], 3).unwrap();
+        dmat.set_labels(&[1.0, 0.0, 1.0]).unwrap();
+        dmat.set_weights(&[0.5, 1.0, 2.0]).unwrap();
+
+        let tmp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let libsvm_path = tmp_dir.path().join("dmat.libsvm");
+        dmat.save_libsvm(&libsvm_path).unwrap();
+        let loaded = DMatrix::load(format!("{}?format=libsvm", libsvm_path.display())).unwrap();
+        assert_eq!(loaded.shape(), (3, 2));
+        assert_eq!(loaded.get_labels().unwrap(), &[1.0, 0.0, 1.0]);
+        assert_eq!(loaded.get_weights().unwrap(), &[0.5, 1.0, 2.0]);
+        assert_eq!(loaded.get_data_as_csr().unwrap(), dmat.get_data_as_csr().unwrap());
+
+        let csv_path = tmp_dir.path().join("dmat.csv");
+        dmat.save_csv(&csv_path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&csv_path).unwrap(),
+            "1,0.5,1,nan\n0,1,0,2\n1,2,3,4\n"
+        );
+        let uri_options = dmat.row_info().unwrap().csv_uri_options();
+        assert_eq!(uri_options, "?format=csv&label_column=0&weight_column=1");
+        let loaded = DMatrix::load(format!("{}{}", csv_path.display(), uri_options)).unwrap();
+        assert_eq!(loaded.shape(), (3, 2));
+        assert_eq!(loaded.get_labels().unwrap(), &[1.0, 0.0, 1.0]);
+        assert_eq!(loaded.get_weights().unwrap(), &[0.5, 1.0, 2.0]);
+        assert_eq!(loaded.get_data_as_csr().unwrap(), dmat.get_data_as_csr().unwrap());
+
+        let options = CsvOptionsBuilder::default()
+            .label_column(Some(0))
+            .weight_column(Some(1))
+            .missing_tokens(vec!["nan".to_owned()])
+            .build()
+            .unwrap();
+        let loaded = DMatrix::load_csv(&csv_path, &options).unwrap();
+        assert_eq!(loaded.shape(), (3, 2));
+        assert_eq!(loaded.get_weights().unwrap(), &[0.5, 1.0, 2.0]);
+        assert_eq!(loaded.get_data_as_csr().unwrap(), dmat.get_data_as_csr().unwrap());
+    }
+}
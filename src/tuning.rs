@@ -0,0 +1,1092 @@
+//! Hyperparameter search over tree booster and learning task parameters.
+//!
+//! A [`SearchSpace`](struct.SearchSpace.html) gives the values to try for each tuned [`Param`](enum.Param.html).
+//! Candidates are picked from it by a [`Strategy`](enum.Strategy.html) (grid, random or successive halving search),
+//! trained in parallel threads and scored on a holdout set or with k-fold cross-validation, optionally stopping
+//! early once the validation score stops improving. [`tune`](fn.tune.html) returns a
+//! [`Leaderboard`](struct.Leaderboard.html) of all candidates, best first.
+//!
+//! ```no_run
+//! use xgb::DMatrix;
+//! use xgb::tuning::{self, Distribution, Param, SearchSpace, Strategy, TuningConfigBuilder, Validation};
+//!
+//! let dtrain = DMatrix::load("train.libsvm?format=libsvm").unwrap();
+//! let space = SearchSpace::new()
+//!     .with(Param::Eta, Distribution::LogUniform(0.01, 0.3))
+//!     .with(Param::MaxDepth, Distribution::IntRange(2, 10))
+//!     .with(Param::Subsample, Distribution::Uniform(0.5, 1.0));
+//! let config = TuningConfigBuilder::default()
+//!     .search_space(space)
+//!     .strategy(Strategy::Random { candidates: 50 })
+//!     .boost_rounds(500)
+//!     .early_stopping_rounds(Some(20))
+//!     .build()
+//!     .unwrap();
+//! let leaderboard = tuning::tune(&dtrain, Validation::CrossValidation { folds: 5 }, &config).unwrap();
+//! println!("{}", leaderboard);
+//!
+//! // parameters of the best candidate, and the number of rounds it needed
+//! let best = leaderboard.best();
+//! let booster_params = best.booster_params().clone();
+//! let boost_rounds = best.best_rounds();
+//! ```
+use std::fmt::{self, Display};
+
+use indexmap::IndexMap;
+
+use crate::dmatrix::RowData;
+use crate::parameters::{BoosterParameters, BoosterType};
+use crate::util::{Rng, default_threads, is_maximized, run_jobs};
+use crate::{Booster, DMatrix, XGBError, XGBResult};
+
+/// A tunable field of [`TreeBoosterParameters`](../parameters/tree/struct.TreeBoosterParameters.html) or
+/// [`LearningTaskParameters`](../parameters/learning/struct.LearningTaskParameters.html).
+///
+/// See their documentation for the meaning and valid range of each parameter. Integer parameters are rounded to
+/// the nearest integer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Param {
+    Eta,
+    Gamma,
+    MaxDepth,
+    MinChildWeight,
+    MaxDeltaStep,
+    Subsample,
+    ColsampleBytree,
+    ColsampleBylevel,
+    ColsampleBynode,
+    Lambda,
+    Alpha,
+    ScalePosWeight,
+    MaxLeaves,
+    MaxBin,
+    BaseScore,
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Param::Eta => "eta",
+            Param::Gamma => "gamma",
+            Param::MaxDepth => "max_depth",
+            Param::MinChildWeight => "min_child_weight",
+            Param::MaxDeltaStep => "max_delta_step",
+            Param::Subsample => "subsample",
+            Param::ColsampleBytree => "colsample_bytree",
+            Param::ColsampleBylevel => "colsample_bylevel",
+            Param::ColsampleBynode => "colsample_bynode",
+            Param::Lambda => "lambda",
+            Param::Alpha => "alpha",
+            Param::ScalePosWeight => "scale_pos_weight",
+            Param::MaxLeaves => "max_leaves",
+            Param::MaxBin => "max_bin",
+            Param::BaseScore => "base_score",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Param {
+    fn is_integer(self) -> bool {
+        matches!(self, Param::MaxDepth | Param::MaxLeaves | Param::MaxBin)
+    }
+
+    /// Whether `value` is in the valid range of this parameter.
+    fn is_valid(self, value: f64) -> bool {
+        match self {
+            Param::BaseScore => value.is_finite(),
+            Param::Eta => (0.0..=1.0).contains(&value),
+            Param::Subsample | Param::ColsampleBytree | Param::ColsampleBylevel | Param::ColsampleBynode => {
+                value > 0.0 && value <= 1.0
+            }
+            Param::MaxBin => value >= 2.0 && value <= u32::MAX as f64,
+            Param::MaxDepth | Param::MaxLeaves => value >= 0.0 && value <= u32::MAX as f64,
+            _ => value >= 0.0 && value.is_finite(),
+        }
+    }
+
+    fn apply(self, value: f64, params: &mut BoosterParameters) -> XGBResult<()> {
+        if self == Param::BaseScore {
+            params.learning_params.set_base_score(value as f32);
+            return Ok(());
+        }
+        let BoosterType::Tree(tree) = params.booster_type() else {
+            return Err(XGBError::new(format!(
+                "Can't tune '{}' parameter, booster type isn't a tree booster",
+                self
+            )));
+        };
+        let mut tree = tree.clone();
+        match self {
+            Param::Eta => tree.eta = value as f32,
+            Param::Gamma => tree.gamma = value as f32,
+            Param::MaxDepth => tree.max_depth = value.round() as u32,
+            Param::MinChildWeight => tree.min_child_weight = value as f32,
+            Param::MaxDeltaStep => tree.max_delta_step = value as f32,
+            Param::Subsample => tree.subsample = value as f32,
+            Param::ColsampleBytree => tree.colsample_bytree = value as f32,
+            Param::ColsampleBylevel => tree.colsample_bylevel = value as f32,
+            Param::ColsampleBynode => tree.colsample_bynode = value as f32,
+            Param::Lambda => tree.lambda = value as f32,
+            Param::Alpha => tree.alpha = value as f32,
+            Param::ScalePosWeight => tree.scale_pos_weight = value as f32,
+            Param::MaxLeaves => tree.max_leaves = value.round() as u32,
+            Param::MaxBin => tree.max_bin = value.round() as u32,
+            // handled above
+            Param::BaseScore => {}
+        }
+        params.set_booster_type(BoosterType::Tree(tree));
+        Ok(())
+    }
+}
+
+/// Values a [`Param`](enum.Param.html) can take in a [`SearchSpace`](struct.SearchSpace.html).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Distribution {
+    /// One of the given values, each tried in turn by grid search.
+    Values(Vec<f64>),
+
+    /// Uniformly distributed between `low` and `high`.
+    Uniform(f64, f64),
+
+    /// Between `low` and `high` with a uniformly distributed logarithm, suited to parameters spanning several
+    /// orders of magnitude like `eta`. Both bounds must be positive.
+    LogUniform(f64, f64),
+
+    /// Any integer between `low` and `high` inclusive, each tried in turn by grid search.
+    IntRange(i64, i64),
+}
+
+impl Distribution {
+    fn validate(&self, param: Param) -> Result<(), String> {
+        let bounds = match *self {
+            Distribution::Values(ref values) => {
+                if values.is_empty() {
+                    return Err(format!("No values given for '{}' parameter.", param));
+                }
+                values.clone()
+            }
+            Distribution::Uniform(low, high) | Distribution::LogUniform(low, high) => {
+                if low > high {
+                    return Err(format!("Invalid range for '{}' parameter, {} > {}.", param, low, high));
+                }
+                if matches!(self, Distribution::LogUniform(..)) && low <= 0.0 {
+                    return Err(format!(
+                        "Invalid range for '{}' parameter, log-uniform bounds must be positive.",
+                        param
+                    ));
+                }
+                vec![low, high]
+            }
+            Distribution::IntRange(low, high) => {
+                if low > high {
+                    return Err(format!("Invalid range for '{}' parameter, {} > {}.", param, low, high));
+                }
+                vec![low as f64, high as f64]
+            }
+        };
+        // parameter ranges are intervals, so checking the bounds is enough
+        match bounds.iter().find(|value| !param.is_valid(**value)) {
+            Some(value) => Err(format!(
+                "Invalid value for '{}' parameter, {} is out of range.",
+                param, value
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn sample(&self, rng: &mut Rng) -> f64 {
+        match *self {
+            Distribution::Values(ref values) => values[rng.below(values.len())],
+            Distribution::Uniform(low, high) => low + rng.next_f64() * (high - low),
+            Distribution::LogUniform(low, high) => (low.ln() + rng.next_f64() * (high.ln() - low.ln())).exp(),
+            Distribution::IntRange(low, high) => (low + rng.below((high - low) as usize + 1) as i64) as f64,
+        }
+    }
+
+    /// All values to try in a grid search, if there are finitely many.
+    fn grid(&self) -> Option<Vec<f64>> {
+        match *self {
+            Distribution::Values(ref values) => Some(values.clone()),
+            Distribution::IntRange(low, high) => Some((low..=high).map(|v| v as f64).collect()),
+            Distribution::Uniform(..) | Distribution::LogUniform(..) => None,
+        }
+    }
+}
+
+/// Parameters to tune and the values each can take. Parameters not in the search space keep their value from
+/// [`TuningConfig::booster_params`](struct.TuningConfig.html#method.booster_params).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchSpace {
+    dimensions: Vec<(Param, Distribution)>,
+}
+
+impl SearchSpace {
+    /// Create an empty search space.
+    pub fn new() -> Self {
+        SearchSpace::default()
+    }
+
+    /// Tune `param` over `distribution`, replacing any distribution previously given for it.
+    pub fn with(mut self, param: Param, distribution: Distribution) -> Self {
+        match self.dimensions.iter_mut().find(|(p, _)| *p == param) {
+            Some(dimension) => dimension.1 = distribution,
+            None => self.dimensions.push((param, distribution)),
+        }
+        self
+    }
+
+    /// Get the tuned parameters and their distributions.
+    pub fn dimensions(&self) -> &[(Param, Distribution)] {
+        &self.dimensions
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.dimensions.is_empty() {
+            return Err("Search space must contain at least one parameter.".to_owned());
+        }
+        for (param, distribution) in &self.dimensions {
+            distribution.validate(*param)?;
+        }
+        Ok(())
+    }
+
+    fn sample(&self, rng: &mut Rng) -> Vec<(Param, f64)> {
+        self.dimensions
+            .iter()
+            .map(|(param, distribution)| {
+                let value = distribution.sample(rng);
+                (*param, if param.is_integer() { value.round() } else { value })
+            })
+            .collect()
+    }
+
+    /// Every combination of values, varying the last parameter fastest.
+    fn grid(&self) -> XGBResult<Vec<Vec<(Param, f64)>>> {
+        let mut candidates = vec![Vec::new()];
+        for (param, distribution) in &self.dimensions {
+            let values = distribution.grid().ok_or_else(|| {
+                XGBError::new(format!(
+                    "Can't grid search over '{}' parameter, its distribution is continuous",
+                    param
+                ))
+            })?;
+            candidates = candidates
+                .iter()
+                .flat_map(|candidate| {
+                    values.iter().map(move |value| {
+                        let mut candidate = candidate.clone();
+                        candidate.push((*param, *value));
+                        candidate
+                    })
+                })
+                .collect();
+        }
+        Ok(candidates)
+    }
+}
+
+/// How candidates are picked from the [`SearchSpace`](struct.SearchSpace.html).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Strategy {
+    /// Try every combination of values. All distributions must be
+    /// [`Values`](enum.Distribution.html#variant.Values) or [`IntRange`](enum.Distribution.html#variant.IntRange).
+    Grid,
+
+    /// Try `candidates` randomly sampled combinations.
+    Random { candidates: usize },
+
+    /// Randomly sample `candidates` combinations and train them for `min_rounds` rounds, then repeatedly keep the
+    /// best `1 / factor` of them and multiply the number of rounds by `factor`, until a single candidate is left or
+    /// the candidates are trained for the full number of boosting rounds.
+    ///
+    /// Spends most of the training time on promising candidates, so many more can be tried than with random
+    /// search for the same cost.
+    SuccessiveHalving {
+        candidates: usize,
+        min_rounds: u32,
+        factor: usize,
+    },
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Strategy::Random { candidates: 10 }
+    }
+}
+
+/// Data candidates are scored on.
+#[derive(Clone, Copy)]
+pub enum Validation<'a> {
+    /// Train on the training matrix and score on the given matrix.
+    Holdout(&'a DMatrix),
+
+    /// Split the rows of the training matrix into `folds` random folds, then train on all but one fold and score on
+    /// the remaining one for each fold in turn, averaging the scores. Not supported for ranking data.
+    CrossValidation { folds: usize },
+}
+
+/// Settings for [`tune`](fn.tune.html). Create using [`TuningConfigBuilder`](struct.TuningConfigBuilder.html).
+#[derive(Builder, Clone)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct TuningConfig {
+    /// Parameters to tune.
+    search_space: SearchSpace,
+
+    /// Parameters shared by all candidates, with the tuned parameters replaced by each candidate's values. Must
+    /// use a tree booster unless only `BaseScore` is tuned.
+    ///
+    /// If the number of threads isn't set, XGBoost's threads are shared evenly between the tuning threads.
+    ///
+    /// *default*: `BoosterParameters::default()`
+    #[builder(default)]
+    booster_params: BoosterParameters,
+
+    /// How candidates are picked from the search space.
+    ///
+    /// *default*: `Strategy::Random { candidates: 10 }`
+    #[builder(default)]
+    strategy: Strategy,
+
+    /// Maximum number of boosting rounds to train each candidate for.
+    ///
+    /// *default*: 100
+    #[builder(default = "100")]
+    boost_rounds: u32,
+
+    /// Stop training a candidate when its validation score hasn't improved for this many rounds.
+    ///
+    /// *default*: `None`
+    #[builder(default)]
+    early_stopping_rounds: Option<u32>,
+
+    /// Name of the evaluation metric candidates are scored by, e.g. `"auc"`.
+    ///
+    /// *default*: `None` (the last metric set in the learning task parameters, like XGBoost's early stopping)
+    #[builder(default)]
+    metric: Option<String>,
+
+    /// Whether higher scores are better.
+    ///
+    /// *default*: `None` (`true` for metrics like `auc`, `ndcg` and `map`, `false` otherwise)
+    #[builder(default)]
+    maximize: Option<bool>,
+
+    /// Number of candidates (or cross-validation folds) trained in parallel. Each thread holds its own copy of
+    /// the training data.
+    ///
+    /// *default*: number of available CPUs
+    #[builder(default = "default_threads()")]
+    threads: usize,
+
+    /// Seed for sampling candidates and splitting cross-validation folds.
+    ///
+    /// *default*: 0
+    #[builder(default)]
+    seed: u64,
+}
+
+impl TuningConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(ref search_space) = self.search_space {
+            search_space.validate()?;
+        }
+        let boost_rounds = self.boost_rounds.unwrap_or(100);
+        if boost_rounds == 0 {
+            return Err("Invalid value for 'boost_rounds' parameter, must be at least 1.".to_owned());
+        }
+        if self.early_stopping_rounds == Some(Some(0)) {
+            return Err("Invalid value for 'early_stopping_rounds' parameter, must be at least 1.".to_owned());
+        }
+        if self.threads == Some(0) {
+            return Err("Invalid value for 'threads' parameter, must be at least 1.".to_owned());
+        }
+        match self.strategy {
+            Some(Strategy::Random { candidates: 0 } | Strategy::SuccessiveHalving { candidates: 0, .. }) => {
+                Err("Invalid value for 'candidates' parameter, must be at least 1.".to_owned())
+            }
+            Some(Strategy::SuccessiveHalving { min_rounds, .. }) if min_rounds == 0 || min_rounds > boost_rounds => {
+                Err(format!(
+                    "Invalid value for 'min_rounds' parameter, {} is not in range [1, {}].",
+                    min_rounds, boost_rounds
+                ))
+            }
+            Some(Strategy::SuccessiveHalving { factor, .. }) if factor < 2 => {
+                Err("Invalid value for 'factor' parameter, must be at least 2.".to_owned())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl TuningConfig {
+    /// Get the parameters to tune.
+    pub fn search_space(&self) -> &SearchSpace {
+        &self.search_space
+    }
+
+    /// Get the parameters shared by all candidates.
+    pub fn booster_params(&self) -> &BoosterParameters {
+        &self.booster_params
+    }
+
+    /// Get how candidates are picked from the search space.
+    pub fn strategy(&self) -> &Strategy {
+        &self.strategy
+    }
+
+    /// Get the maximum number of boosting rounds to train each candidate for.
+    pub fn boost_rounds(&self) -> u32 {
+        self.boost_rounds
+    }
+
+    /// Get the number of rounds without improvement after which training a candidate stops.
+    pub fn early_stopping_rounds(&self) -> Option<u32> {
+        self.early_stopping_rounds
+    }
+
+    /// Get the name of the evaluation metric candidates are scored by.
+    pub fn metric(&self) -> Option<&str> {
+        self.metric.as_deref()
+    }
+
+    /// Get whether higher scores are better.
+    pub fn maximize(&self) -> Option<bool> {
+        self.maximize
+    }
+
+    /// Get the number of candidates trained in parallel.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Get the seed for sampling candidates and splitting folds.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+/// A scored candidate in a [`Leaderboard`](struct.Leaderboard.html).
+#[derive(Clone)]
+pub struct Trial {
+    values: Vec<(Param, f64)>,
+    booster_params: BoosterParameters,
+    score: f32,
+    fold_scores: Vec<f32>,
+    best_rounds: u32,
+    rounds: u32,
+}
+
+impl Trial {
+    /// Get the candidate's value for each tuned parameter.
+    pub fn values(&self) -> &[(Param, f64)] {
+        &self.values
+    }
+
+    /// Get the full booster parameters the candidate was trained with.
+    pub fn booster_params(&self) -> &BoosterParameters {
+        &self.booster_params
+    }
+
+    /// Get the candidate's best validation score, averaged over folds when cross-validating.
+    pub fn score(&self) -> f32 {
+        self.score
+    }
+
+    /// Get the best validation score on each fold (a single score for a holdout set).
+    pub fn fold_scores(&self) -> &[f32] {
+        &self.fold_scores
+    }
+
+    /// Get the number of boosting rounds after which the best validation score was reached, averaged over folds
+    /// when cross-validating.
+    pub fn best_rounds(&self) -> u32 {
+        self.best_rounds
+    }
+
+    /// Get the maximum number of boosting rounds the candidate was trained for, which is less than the configured
+    /// number of rounds for candidates eliminated early by successive halving.
+    pub fn rounds(&self) -> u32 {
+        self.rounds
+    }
+}
+
+impl Display for Trial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (param, value)) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", param, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Results of [`tune`](fn.tune.html), with trials sorted from best to worst.
+///
+/// Candidates trained for more rounds by successive halving rank above those eliminated earlier.
+#[derive(Clone)]
+pub struct Leaderboard {
+    trials: Vec<Trial>,
+    metric: String,
+    maximize: bool,
+}
+
+impl Leaderboard {
+    /// Get all trials, sorted from best to worst.
+    pub fn trials(&self) -> &[Trial] {
+        &self.trials
+    }
+
+    /// Get the best trial.
+    pub fn best(&self) -> &Trial {
+        &self.trials[0]
+    }
+
+    /// Get the booster parameters of the best trial.
+    pub fn best_params(&self) -> &BoosterParameters {
+        &self.best().booster_params
+    }
+
+    /// Get the name of the metric trials were scored by.
+    pub fn metric(&self) -> &str {
+        &self.metric
+    }
+
+    /// Get whether higher scores are better.
+    pub fn maximize(&self) -> bool {
+        self.maximize
+    }
+}
+
+impl Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>4}  {:>12}  {:>6}  params", "rank", self.metric, "rounds")?;
+        for (rank, trial) in self.trials.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:>12.6}  {:>6}  {}",
+                rank + 1,
+                trial.score,
+                trial.best_rounds,
+                trial
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Search for the best booster parameters for `dtrain`, scoring candidates as given by `validation`.
+///
+/// Returns an error if training any candidate fails, e.g. because its parameters are rejected by XGBoost.
+pub fn tune(dtrain: &DMatrix, validation: Validation, config: &TuningConfig) -> XGBResult<Leaderboard> {
+    let mut rng = Rng::new(config.seed);
+    let candidates = match config.strategy {
+        Strategy::Grid => config.search_space.grid()?,
+        Strategy::Random { candidates } | Strategy::SuccessiveHalving { candidates, .. } => {
+            (0..candidates).map(|_| config.search_space.sample(&mut rng)).collect()
+        }
+    };
+    let mut base_params = config.booster_params.clone();
+    if base_params.threads().is_none() {
+        base_params.set_threads((default_threads() / config.threads).max(1) as u32);
+    }
+    let booster_params = candidates
+        .iter()
+        .map(|values| {
+            let mut params = base_params.clone();
+            for (param, value) in values {
+                param.apply(*value, &mut params)?;
+            }
+            Ok(params)
+        })
+        .collect::<XGBResult<Vec<_>>>()?;
+    let splits = Splits::new(dtrain, validation, &mut rng)?;
+
+    let schedule = match config.strategy {
+        Strategy::SuccessiveHalving { min_rounds, factor, .. } => {
+            halving_schedule(candidates.len(), min_rounds, config.boost_rounds, factor)
+        }
+        _ => vec![(candidates.len(), config.boost_rounds)],
+    };
+    let mut scores: Vec<Option<CandidateScore>> = vec![None; candidates.len()];
+    let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+    let mut maximize = config.maximize.unwrap_or(false);
+    for (keep, rounds) in schedule {
+        if keep < remaining.len() {
+            remaining.sort_by(|a, b| compare(scores[*a].as_ref(), scores[*b].as_ref(), maximize));
+            remaining.truncate(keep);
+        }
+        debug!("Training {} candidates for {} rounds", remaining.len(), rounds);
+        let rung = evaluate(&splits, &booster_params, &remaining, rounds, config)?;
+        for (candidate, score) in remaining.iter().zip(rung) {
+            maximize = config.maximize.unwrap_or_else(|| is_maximized(&score.metric));
+            scores[*candidate] = Some(score);
+        }
+    }
+
+    let metric = scores
+        .iter()
+        .flatten()
+        .next()
+        .map_or_else(String::new, |s| s.metric.clone());
+    let mut trials: Vec<(Trial, CandidateScore)> = candidates
+        .into_iter()
+        .zip(booster_params)
+        .zip(scores)
+        .filter_map(|((values, booster_params), score)| {
+            let score = score?;
+            let trial = Trial {
+                values,
+                booster_params,
+                score: score.score,
+                fold_scores: score.fold_scores.clone(),
+                best_rounds: score.best_rounds,
+                rounds: score.rounds,
+            };
+            Some((trial, score))
+        })
+        .collect();
+    trials.sort_by(|(_, a), (_, b)| compare(Some(a), Some(b), maximize));
+    Ok(Leaderboard {
+        trials: trials.into_iter().map(|(trial, _)| trial).collect(),
+        metric,
+        maximize,
+    })
+}
+
+/// Number of candidates to keep and rounds to train them for in each rung of successive halving.
+fn halving_schedule(candidates: usize, min_rounds: u32, max_rounds: u32, factor: usize) -> Vec<(usize, u32)> {
+    let mut schedule = Vec::new();
+    let (mut candidates, mut rounds) = (candidates, min_rounds);
+    while candidates > 1 && rounds < max_rounds {
+        schedule.push((candidates, rounds));
+        candidates = candidates.div_ceil(factor);
+        rounds = rounds.saturating_mul(factor as u32);
+    }
+    schedule.push((candidates, max_rounds));
+    schedule
+}
+
+fn is_better(score: f32, best: f32, maximize: bool) -> bool {
+    best.is_nan() || if maximize { score > best } else { score < best }
+}
+
+/// Order scored candidates from best to worst, ranking those trained for more rounds first.
+fn compare(a: Option<&CandidateScore>, b: Option<&CandidateScore>, maximize: bool) -> std::cmp::Ordering {
+    let key = |s: Option<&CandidateScore>| s.map_or((0, f32::NAN), |s| (s.rounds, s.score));
+    let ((a_rounds, a_score), (b_rounds, b_score)) = (key(a), key(b));
+    b_rounds
+        .cmp(&a_rounds)
+        .then_with(|| match (a_score.is_nan(), b_score.is_nan()) {
+            (true, true) => std::cmp::Ordering::Equal,
+            (true, false) => std::cmp::Ordering::Greater,
+            (false, true) => std::cmp::Ordering::Less,
+            (false, false) if maximize => b_score.total_cmp(&a_score),
+            (false, false) => a_score.total_cmp(&b_score),
+        })
+}
+
+#[derive(Clone, Debug)]
+struct FoldScore {
+    metric: String,
+    score: f32,
+    best_rounds: u32,
+}
+
+#[derive(Clone, Debug)]
+struct CandidateScore {
+    metric: String,
+    score: f32,
+    fold_scores: Vec<f32>,
+    best_rounds: u32,
+    rounds: u32,
+}
+
+/// Train and score every fold of each of `candidates` for up to `rounds` rounds, in parallel threads.
+fn evaluate(
+    splits: &Splits,
+    booster_params: &[BoosterParameters],
+    candidates: &[usize],
+    rounds: u32,
+    config: &TuningConfig,
+) -> XGBResult<Vec<CandidateScore>> {
+    let num_folds = splits.len();
+    let jobs: Vec<(usize, usize)> = candidates
+        .iter()
+        .flat_map(|candidate| (0..num_folds).map(move |fold| (*candidate, fold)))
+        .collect();
+    // each thread creates its own matrices for a fold the first time it trains on it
+    let init = || -> Vec<Option<(DMatrix, DMatrix)>> { (0..num_folds).map(|_| None).collect() };
+    let fold_scores = run_jobs(jobs.len(), config.threads, init, |matrices, job| {
+        let (candidate, fold) = jobs[job];
+        let (dtrain, dvalid) = match matrices[fold] {
+            Some(ref matrices) => matrices,
+            None => &*matrices[fold].insert(splits.matrices(fold)?),
+        };
+        train_fold(&booster_params[candidate], dtrain, dvalid, rounds, config)
+    })?;
+    Ok(fold_scores
+        .chunks(num_folds)
+        .map(|folds| CandidateScore {
+            metric: folds[0].metric.clone(),
+            score: folds.iter().map(|f| f.score).sum::<f32>() / num_folds as f32,
+            fold_scores: folds.iter().map(|f| f.score).collect(),
+            best_rounds: (folds.iter().map(|f| f.best_rounds as f64).sum::<f64>() / num_folds as f64).round() as u32,
+            rounds,
+        })
+        .collect())
+}
+
+/// Train a booster on `dtrain` for up to `rounds` rounds, returning its best score on `dvalid`.
+fn train_fold(
+    params: &BoosterParameters,
+    dtrain: &DMatrix,
+    dvalid: &DMatrix,
+    rounds: u32,
+    config: &TuningConfig,
+) -> XGBResult<FoldScore> {
+    let mut bst = Booster::new_with_cached_dmats(params, &[dtrain, dvalid])?;
+    let mut best: Option<FoldScore> = None;
+    for i in 0..rounds {
+        bst.update(dtrain, i as i32)?;
+        let mut eval = bst.eval_set(&[(dvalid, "valid")], i as i32)?;
+        let metrics = eval.swap_remove("valid").unwrap_or_default();
+        let (metric, score) = select_metric(&metrics, config.metric.as_deref())?;
+        let maximize = config.maximize.unwrap_or_else(|| is_maximized(metric));
+        match best {
+            Some(ref best) if !is_better(score, best.score, maximize) => {
+                if let Some(patience) = config.early_stopping_rounds
+                    && i + 1 - best.best_rounds >= patience
+                {
+                    debug!("Stopping early after {} rounds", i + 1);
+                    break;
+                }
+            }
+            _ => {
+                best = Some(FoldScore {
+                    metric: metric.to_owned(),
+                    score,
+                    best_rounds: i + 1,
+                })
+            }
+        }
+    }
+    best.ok_or_else(|| XGBError::new("No boosting rounds were trained"))
+}
+
+/// Pick the score for `metric`, or the last metric if not given.
+fn select_metric<'a>(metrics: &'a IndexMap<String, f32>, metric: Option<&str>) -> XGBResult<(&'a str, f32)> {
+    let found = match metric {
+        Some(metric) => metrics.get_key_value(metric),
+        None => metrics.last(),
+    };
+    found.map(|(name, score)| (name.as_str(), *score)).ok_or_else(|| {
+        XGBError::new(format!(
+            "Evaluation metric '{}' not found, available metrics: {:?}",
+            metric.unwrap_or_default(),
+            metrics.keys().collect::<Vec<_>>()
+        ))
+    })
+}
+
+/// Training and validation data for each fold, kept as plain data so every thread can create its own matrices.
+enum Splits {
+    Holdout {
+        train: RowData,
+        valid: RowData,
+    },
+    CrossValidation {
+        data: RowData,
+        folds: Vec<(Vec<usize>, Vec<usize>)>,
+    },
+}
+
+impl Splits {
+    fn new(dtrain: &DMatrix, validation: Validation, rng: &mut Rng) -> XGBResult<Self> {
+        match validation {
+            Validation::Holdout(dvalid) => Ok(Splits::Holdout {
+                train: RowData::new(dtrain)?,
+                valid: RowData::new(dvalid)?,
+            }),
+            Validation::CrossValidation { folds } => {
+                let data = RowData::new(dtrain)?;
+                if data.has_groups() {
+                    return Err(XGBError::new(
+                        "Cross-validation isn't supported for ranking data, use a holdout set instead",
+                    ));
+                }
+                if folds < 2 || folds > data.num_rows() {
+                    return Err(XGBError::new(format!(
+                        "Invalid number of folds {}, must be between 2 and the number of rows ({})",
+                        folds,
+                        data.num_rows()
+                    )));
+                }
+                let folds = cv_folds(data.num_rows(), folds, rng);
+                Ok(Splits::CrossValidation { data, folds })
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Splits::Holdout { .. } => 1,
+            Splits::CrossValidation { folds, .. } => folds.len(),
+        }
+    }
+
+    fn matrices(&self, fold: usize) -> XGBResult<(DMatrix, DMatrix)> {
+        match self {
+            Splits::Holdout { train, valid } => Ok((train.to_dmatrix()?, valid.to_dmatrix()?)),
+            Splits::CrossValidation { data, folds } => {
+                let (train_rows, valid_rows) = &folds[fold];
+                Ok((
+                    data.subset(train_rows).to_dmatrix()?,
+                    data.subset(valid_rows).to_dmatrix()?,
+                ))
+            }
+        }
+    }
+}
+
+/// Randomly split `num_rows` rows into `folds` folds, returning the (training, validation) rows of each.
+fn cv_folds(num_rows: usize, folds: usize, rng: &mut Rng) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let mut rows: Vec<usize> = (0..num_rows).collect();
+    for i in (1..num_rows).rev() {
+        rows.swap(i, rng.below(i + 1));
+    }
+    (0..folds)
+        .map(|fold| {
+            let mut train = Vec::with_capacity(num_rows);
+            let mut valid = Vec::with_capacity(num_rows / folds + 1);
+            for (i, row) in rows.iter().enumerate() {
+                if i % folds == fold {
+                    valid.push(*row);
+                } else {
+                    train.push(*row);
+                }
+            }
+            train.sort_unstable();
+            valid.sort_unstable();
+            (train, valid)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::{BoosterParametersBuilder, learning, linear, tree};
+
+    fn space() -> SearchSpace {
+        SearchSpace::new()
+            .with(Param::Eta, Distribution::Values(vec![0.1, 0.3]))
+            .with(Param::MaxDepth, Distribution::IntRange(2, 4))
+    }
+
+    fn config(strategy: Strategy) -> TuningConfigBuilder {
+        let mut builder = TuningConfigBuilder::default();
+        builder.search_space(space()).strategy(strategy).threads(2);
+        builder
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(config(Strategy::Grid).build().is_ok());
+        assert!(TuningConfigBuilder::default().build().is_err());
+        assert!(config(Strategy::Grid).search_space(SearchSpace::new()).build().is_err());
+        assert!(config(Strategy::Grid).threads(0).build().is_err());
+        assert!(config(Strategy::Grid).boost_rounds(0).build().is_err());
+        assert!(config(Strategy::Grid).early_stopping_rounds(Some(0)).build().is_err());
+        assert!(config(Strategy::Random { candidates: 0 }).build().is_err());
+        let halving = |min_rounds, factor| Strategy::SuccessiveHalving {
+            candidates: 9,
+            min_rounds,
+            factor,
+        };
+        assert!(config(halving(10, 3)).build().is_ok());
+        assert!(config(halving(0, 3)).build().is_err());
+        assert!(config(halving(200, 3)).build().is_err());
+        assert!(config(halving(10, 1)).build().is_err());
+
+        for (param, distribution) in [
+            (Param::Eta, Distribution::Values(vec![])),
+            (Param::Eta, Distribution::Uniform(0.5, 0.1)),
+            (Param::Eta, Distribution::Uniform(0.1, 1.5)),
+            (Param::Gamma, Distribution::LogUniform(0.0, 1.0)),
+            (Param::MaxDepth, Distribution::IntRange(-1, 3)),
+            (Param::Subsample, Distribution::Values(vec![0.0, 0.5])),
+        ] {
+            let space = SearchSpace::new().with(param, distribution.clone());
+            assert!(
+                config(Strategy::Grid).search_space(space).build().is_err(),
+                "{:?} {:?}",
+                param,
+                distribution
+            );
+        }
+    }
+
+    #[test]
+    fn search_space() {
+        let space = space().with(Param::Eta, Distribution::Values(vec![0.1, 0.2, 0.3]));
+        assert_eq!(space.dimensions().len(), 2);
+
+        let grid = space.grid().unwrap();
+        assert_eq!(grid.len(), 9);
+        assert_eq!(grid[0], vec![(Param::Eta, 0.1), (Param::MaxDepth, 2.0)]);
+        assert_eq!(grid[1], vec![(Param::Eta, 0.1), (Param::MaxDepth, 3.0)]);
+        assert_eq!(grid[8], vec![(Param::Eta, 0.3), (Param::MaxDepth, 4.0)]);
+
+        let space = SearchSpace::new()
+            .with(Param::Eta, Distribution::LogUniform(0.01, 0.3))
+            .with(Param::MaxLeaves, Distribution::Uniform(10.0, 20.0))
+            .with(Param::Lambda, Distribution::Values(vec![1.0, 2.0]));
+        assert!(space.grid().is_err());
+
+        let mut rng = Rng::new(42);
+        let samples: Vec<_> = (0..100).map(|_| space.sample(&mut rng)).collect();
+        for sample in &samples {
+            assert!((0.01..=0.3).contains(&sample[0].1));
+            assert!((10.0..=20.0).contains(&sample[1].1));
+            assert_eq!(sample[1].1.fract(), 0.0);
+            assert!(sample[2].1 == 1.0 || sample[2].1 == 2.0);
+        }
+        let mut rng = Rng::new(42);
+        assert_eq!(space.sample(&mut rng), samples[0]);
+    }
+
+    #[test]
+    fn apply_params() {
+        let mut params = BoosterParameters::default();
+        Param::Eta.apply(0.1, &mut params).unwrap();
+        Param::MaxDepth.apply(4.0, &mut params).unwrap();
+        Param::BaseScore.apply(0.25, &mut params).unwrap();
+        let pairs = params.as_string_pairs();
+        assert!(pairs.contains(&("eta".to_owned(), "0.1".to_owned())));
+        assert!(pairs.contains(&("max_depth".to_owned(), "4".to_owned())));
+        assert!(pairs.contains(&("base_score".to_owned(), "0.25".to_owned())));
+
+        let mut params = BoosterParametersBuilder::default()
+            .booster_type(BoosterType::Linear(linear::LinearBoosterParameters::default()))
+            .build()
+            .unwrap();
+        assert!(Param::Eta.apply(0.1, &mut params).is_err());
+        assert!(Param::BaseScore.apply(0.1, &mut params).is_ok());
+    }
+
+    #[test]
+    fn successive_halving_schedule() {
+        assert_eq!(halving_schedule(9, 10, 100, 3), vec![(9, 10), (3, 30), (1, 100)]);
+        assert_eq!(halving_schedule(27, 10, 50, 3), vec![(27, 10), (9, 30), (3, 50)]);
+        assert_eq!(
+            halving_schedule(10, 5, 100, 2),
+            vec![(10, 5), (5, 10), (3, 20), (2, 40), (1, 100)]
+        );
+        assert_eq!(halving_schedule(1, 10, 100, 3), vec![(1, 100)]);
+        assert_eq!(halving_schedule(5, 100, 100, 3), vec![(5, 100)]);
+    }
+
+    #[test]
+    fn folds() {
+        let folds = cv_folds(10, 3, &mut Rng::new(0));
+        assert_eq!(folds.len(), 3);
+        let mut all_valid: Vec<usize> = Vec::new();
+        for (train, valid) in &folds {
+            assert_eq!(train.len() + valid.len(), 10);
+            assert!(valid.len() == 3 || valid.len() == 4);
+            assert!(train.iter().all(|row| !valid.contains(row)));
+            all_valid.extend(valid);
+        }
+        all_valid.sort();
+        assert_eq!(all_valid, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn metrics() {
+        let mut metrics = IndexMap::new();
+        metrics.insert("logloss".to_owned(), 0.5);
+        metrics.insert("auc".to_owned(), 0.75);
+        assert_eq!(select_metric(&metrics, None).unwrap(), ("auc", 0.75));
+        assert_eq!(select_metric(&metrics, Some("logloss")).unwrap(), ("logloss", 0.5));
+        assert!(select_metric(&metrics, Some("rmse")).is_err());
+
+        assert!(is_better(0.5, 0.6, false));
+        assert!(is_better(0.6, 0.5, true));
+        assert!(is_better(0.6, f32::NAN, true));
+        assert!(!is_better(f32::NAN, 0.5, false));
+    }
+
+    fn dtrain() -> DMatrix {
+        let num_rows = 200;
+        let x: Vec<f32> = (0..num_rows * 3).map(|i| ((i * 7) % 11) as f32).collect();
+        let y: Vec<f32> = x
+            .chunks(3)
+            .map(|row| row[0] * 2.0 - row[1] + (row[2] > 5.0) as u8 as f32)
+            .collect();
+        let mut dmat = DMatrix::from_dense(&x, num_rows).unwrap();
+        dmat.set_labels(&y).unwrap();
+        dmat
+    }
+
+    #[test]
+    fn tune_grid_holdout() {
+        let dtrain = dtrain();
+        let dvalid = dtrain.slice(&(0..50).collect::<Vec<_>>()).unwrap();
+        let config = config(Strategy::Grid)
+            .boost_rounds(20)
+            .early_stopping_rounds(Some(5))
+            .build()
+            .unwrap();
+        let leaderboard = tune(&dtrain, Validation::Holdout(&dvalid), &config).unwrap();
+        assert_eq!(leaderboard.trials().len(), 6);
+        assert_eq!(leaderboard.metric(), "rmse");
+        assert!(!leaderboard.maximize());
+        assert!(leaderboard.trials().windows(2).all(|w| w[0].score() <= w[1].score()));
+        for trial in leaderboard.trials() {
+            assert_eq!(trial.fold_scores().len(), 1);
+            assert!(trial.best_rounds() >= 1 && trial.best_rounds() <= 20);
+        }
+        assert!(leaderboard.to_string().starts_with("rank"));
+    }
+
+    #[test]
+    fn tune_successive_halving_cv() {
+        let dtrain = dtrain();
+        let learning_params = learning::LearningTaskParametersBuilder::default()
+            .eval_metrics(learning::Metrics::Custom(vec![learning::EvaluationMetric::MAE]))
+            .build()
+            .unwrap();
+        let booster_params = BoosterParametersBuilder::default()
+            .booster_type(BoosterType::Tree(tree::TreeBoosterParameters::default()))
+            .learning_params(learning_params)
+            .build()
+            .unwrap();
+        let space = space().with(Param::Subsample, Distribution::Uniform(0.5, 1.0));
+        let config = config(Strategy::SuccessiveHalving {
+            candidates: 9,
+            min_rounds: 2,
+            factor: 3,
+        })
+        .search_space(space)
+        .booster_params(booster_params)
+        .boost_rounds(10)
+        .build()
+        .unwrap();
+        let leaderboard = tune(&dtrain, Validation::CrossValidation { folds: 3 }, &config).unwrap();
+        assert_eq!(leaderboard.metric(), "mae");
+        let rounds: Vec<u32> = leaderboard.trials().iter().map(|t| t.rounds()).collect();
+        assert_eq!(rounds, vec![10, 6, 6, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(leaderboard.best().fold_scores().len(), 3);
+        assert!(
+            leaderboard
+                .best_params()
+                .as_string_pairs()
+                .contains(&("eval_metric".to_owned(), "mae".to_owned()))
+        );
+
+        assert!(tune(&dtrain, Validation::CrossValidation { folds: 1 }, &config).is_err());
+    }
+}
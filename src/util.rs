@@ -0,0 +1,165 @@
+//! Helpers shared between modules.
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use crate::XGBResult;
+
+/// Format a float as a JSON value understood by XGBoost's config parser, which accepts non-finite values.
+pub(crate) fn json_float(value: f32) -> String {
+    if value.is_nan() {
+        "NaN".to_owned()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Number of threads available to run in parallel.
+pub(crate) fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Run `job(state, i)` for each `i` in `0..num_jobs` on up to `threads` threads, returning the results in order, or
+/// the first error, after which no more jobs are started.
+///
+/// Booster and DMatrix handles can't be shared between threads, so each thread creates its own `state` with `init`
+/// to hold them.
+pub(crate) fn run_jobs<S, T>(
+    num_jobs: usize,
+    threads: usize,
+    init: impl Fn() -> S + Sync,
+    job: impl Fn(&mut S, usize) -> XGBResult<T> + Sync,
+) -> XGBResult<Vec<T>>
+where
+    T: Send,
+{
+    let next_job = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results: Mutex<Vec<Option<XGBResult<T>>>> = Mutex::new((0..num_jobs).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..threads.min(num_jobs) {
+            scope.spawn(|| {
+                let mut state = init();
+                while !failed.load(Ordering::Relaxed) {
+                    let i = next_job.fetch_add(1, Ordering::Relaxed);
+                    if i >= num_jobs {
+                        break;
+                    }
+                    let result = job(&mut state, i);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    results.lock().unwrap()[i] = Some(result);
+                }
+            });
+        }
+    });
+
+    // jobs are started in order and all started jobs finish, so jobs that never ran come after the first error
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect()
+}
+
+/// Whether higher is better for the evaluation metric called `metric`, such as `auc`, `ndcg@5` or `map-`.
+pub(crate) fn is_maximized(metric: &str) -> bool {
+    ["auc", "aucpr", "ndcg", "map", "pre"]
+        .iter()
+        .any(|name| match metric.strip_prefix(name) {
+            Some(rest) => rest.is_empty() || rest == "-" || rest.starts_with('@'),
+            None => false,
+        })
+}
+
+/// Small seeded random number generator (SplitMix64), so results are reproducible for a given seed.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in [0, 1).
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniformly distributed in [0, n).
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.next_f64() * n as f64) as usize).min(n - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_floats() {
+        assert_eq!(json_float(0.5), "0.5");
+        assert_eq!(json_float(-3.0), "-3");
+        assert_eq!(json_float(f32::NAN), "NaN");
+        assert_eq!(json_float(f32::INFINITY), "Infinity");
+        assert_eq!(json_float(f32::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    fn maximized_metrics() {
+        assert!(is_maximized("auc"));
+        assert!(is_maximized("ndcg@5"));
+        assert!(is_maximized("map@4-"));
+        assert!(is_maximized("ndcg-"));
+        assert!(is_maximized("map-"));
+        assert!(!is_maximized("rmse"));
+        assert!(!is_maximized("logloss"));
+        assert!(!is_maximized("mape"));
+    }
+
+    #[test]
+    fn jobs() {
+        let results = run_jobs(10, 3, || (), |_, i| Ok(i * 2)).unwrap();
+        assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+        assert!(run_jobs(0, 3, || (), |_, i| Ok(i)).unwrap().is_empty());
+
+        let result = run_jobs(
+            100,
+            4,
+            || (),
+            |_, i| {
+                if i % 10 == 3 {
+                    Err(crate::XGBError::new(format!("job {} failed", i)))
+                } else {
+                    Ok(i)
+                }
+            },
+        );
+        // later jobs may fail too before the threads stop, but the first error is returned
+        assert!(result.unwrap_err().to_string().contains("job 3 failed"));
+    }
+
+    #[test]
+    fn rng() {
+        let mut rng = Rng::new(7);
+        let values: Vec<f64> = (0..1000).map(|_| rng.next_f64()).collect();
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+        assert!(values.iter().any(|v| *v < 0.1) && values.iter().any(|v| *v > 0.9));
+        assert!((0..1000).all(|_| rng.below(3) < 3));
+
+        let mut again = Rng::new(7);
+        assert_eq!(again.next_f64(), values[0]);
+    }
+}
@@ -91,6 +91,9 @@ fn main() {
             .define("BUILD_WITH_CUDA", "ON")
             .define("BUILD_WITH_CUDA_CUB", "ON");
 
+        #[cfg(feature = "federated")]
+        let dst = dst.define("PLUGIN_FEDERATED", "ON");
+
         let dst = dst.build();
 
         println!("cargo:rustc-link-search=native={}", dst.display());